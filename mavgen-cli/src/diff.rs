@@ -0,0 +1,18 @@
+use std::io::Write;
+
+use mavgen::codegen::rust::api_diff::{total_impact, ApiChange};
+
+/// Prints the semver impact followed by all changes of the generated API.
+pub fn print_changes(changes: &[ApiChange], out: &mut impl Write) -> std::io::Result<()> {
+    let Some(impact) = total_impact(changes) else {
+        return writeln!(out, "No API changes");
+    };
+
+    writeln!(out, "Semver impact: {impact}")?;
+    writeln!(out)?;
+    for change in changes {
+        writeln!(out, "  {change}")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::io::Write;
+
+use mavgen::model;
+
+/// Prints a summary of the normalised module: enums, messages, their IDs,
+/// CRC_EXTRA values and payload lengths.
+pub fn print_module(module: &model::MavlinkModule, out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "Definition: {}", module.path.display())?;
+    if let Some(version) = module.version {
+        writeln!(out, "Version: {version}")?;
+    }
+    if let Some(dialect) = module.dialect {
+        writeln!(out, "Dialect: {dialect}")?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "Enums ({}):", module.enums.len())?;
+    for r#enum in &module.enums {
+        let kind = if r#enum.bitmask { "bitmask" } else { "enum" };
+        writeln!(
+            out,
+            "  {} ({}, {} entries, {})",
+            r#enum.name,
+            kind,
+            r#enum.entries.len(),
+            r#enum.min_rust_size(),
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "Messages ({}):", module.messages.len())?;
+    for message in &module.messages {
+        let base_len: usize = message
+            .fields
+            .iter()
+            .map(|field| field.r#type.wire_size())
+            .sum();
+
+        writeln!(
+            out,
+            "  {:>6} {} (crc_extra: {}, payload: {} bytes, with extensions: {} bytes)",
+            message.id,
+            message.name,
+            message.extra_crc(),
+            base_len,
+            message.wire_size(),
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::{io::Write, path::PathBuf};
+
+use mavgen::model;
+
+/// Prints the messages and enums of the flattened module and the files it
+/// includes as plain text.
+pub fn print_text(
+    module: &model::MavlinkModule,
+    includes: &[PathBuf],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(out, "Definition: {}", module.path.display())?;
+
+    writeln!(out)?;
+    writeln!(out, "Includes ({}):", includes.len())?;
+    for include in includes {
+        writeln!(out, "  {}", include.display())?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "Messages ({}):", module.messages.len())?;
+    for message in &module.messages {
+        writeln!(out, "  {:>6} {}", message.id, message.name)?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "Enums ({}):", module.enums.len())?;
+    for r#enum in &module.enums {
+        let bitmask = if r#enum.bitmask { ", bitmask" } else { "" };
+        writeln!(
+            out,
+            "  {} ({} entries{bitmask})",
+            r#enum.name,
+            r#enum.entries.len(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Prints the same listing as [`print_text`] as a JSON object.
+pub fn print_json(
+    module: &model::MavlinkModule,
+    includes: &[PathBuf],
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let messages = module
+        .messages
+        .iter()
+        .map(|message| {
+            serde_json::json!({
+                "id": message.id,
+                "name": message.name.as_ref(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let enums = module
+        .enums
+        .iter()
+        .map(|r#enum| {
+            serde_json::json!({
+                "name": r#enum.name.as_ref(),
+                "entries": r#enum.entries.len(),
+                "bitmask": r#enum.bitmask,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let listing = serde_json::json!({
+        "definition": module.path,
+        "includes": includes,
+        "messages": messages,
+        "enums": enums,
+    });
+
+    serde_json::to_writer_pretty(&mut *out, &listing)?;
+    writeln!(out)
+}
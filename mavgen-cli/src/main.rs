@@ -1,45 +1,454 @@
-use std::path::PathBuf;
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
 
 use anyhow::Context;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+
+mod diff;
+mod inspect;
+mod list;
 
 /// Generate Rust code from XML MAVLink definitions.
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    /// Path to definition files
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to definition files, directories or glob patterns like `defs/**/*.xml`,
+    /// `-` to read one definition from stdin
     #[arg(required = true)]
     input: Vec<PathBuf>,
 
-    /// Output file or directory
-    #[arg(short, long)]
-    output: PathBuf,
+    /// Output file or directory, several definitions are generated into
+    /// one file as a module per definition if it ends with `.rs`
+    #[arg(short, long, required_unless_present = "stdout")]
+    output: Option<PathBuf>,
+
+    /// Print the generated code of a single definition instead of writing it
+    #[arg(long, conflicts_with = "output")]
+    stdout: bool,
+
+    /// Run the generation, but only print the files, which would be written,
+    /// with their sizes
+    #[arg(long, conflicts_with = "stdout")]
+    dry_run: bool,
+
+    /// Print at most this number of errors of a definition
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// Generate the `Reflect` trait for field access by name
+    #[arg(long)]
+    reflection: bool,
+
+    /// Generate a dependency-free `framing` module for v1/v2 frames
+    #[arg(long)]
+    emit_framing: bool,
+
+    /// Generate the object-safe `DynMessage` trait for dynamic dispatch
+    #[arg(long)]
+    dyn_messages: bool,
+
+    /// Generate a module per included file instead of flattening the includes
+    #[arg(long)]
+    nested: bool,
+
+    /// Generate files included by several inputs as modules shared by them
+    #[arg(long, conflicts_with = "nested")]
+    deduplicate: bool,
+
+    /// Don't generate enums, which aren't referenced by any message field
+    #[arg(long, conflicts_with_all = ["nested", "deduplicate"])]
+    prune_unused_enums: bool,
+
+    /// Generate only the messages with these ids or names
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["nested", "deduplicate"])]
+    only_messages: Vec<mavgen::model::MessageSelector>,
+
+    /// Don't generate the messages with these ids or names
+    #[arg(
+        long,
+        value_delimiter = ',',
+        conflicts_with_all = ["nested", "deduplicate", "only_messages"]
+    )]
+    exclude_messages: Vec<mavgen::model::MessageSelector>,
+
+    /// Generate enums as newtypes over integers, which keep unknown values
+    #[arg(long)]
+    open_enums: bool,
+
+    /// Mark enums and the enum with all messages as `#[non_exhaustive]`
+    #[arg(long)]
+    non_exhaustive_enums: bool,
+
+    /// Generate enums with a single entry as a unit struct with a constant
+    #[arg(long)]
+    compact_single_entry_enums: bool,
+
+    /// Name of the enum with all messages, `{module}` is replaced with the module name
+    #[arg(long, value_name = "NAME", value_parser = parse_mav_message_name)]
+    mav_message_name: Option<String>,
+
+    /// Rename entries of enums to snake_case in serde
+    #[arg(long)]
+    serde_snake_case_enums: bool,
+
+    /// Don't derive serde traits for the enum or message with this definition name
+    #[arg(long, value_name = "NAME")]
+    serde_skip: Vec<String>,
+
+    /// Derive `defmt::Format` for messages and enums behind the `defmt` feature
+    #[arg(long)]
+    defmt: bool,
+
+    /// Derive `arbitrary::Arbitrary` for messages and enums behind the `arbitrary` feature
+    #[arg(long)]
+    arbitrary: bool,
+
+    /// Emit `fmt_telemetry` rendering messages as `name: value units` lines
+    #[arg(long)]
+    telemetry_formatter: bool,
+
+    /// Don't derive `Clone` and `Copy` for messages with a larger payload in bytes
+    #[arg(long, value_name = "BYTES")]
+    clone_size_threshold: Option<usize>,
+
+    /// Emit `{Message}Ref<'a>` borrowing string fields from the payload
+    #[arg(long)]
+    borrowed_messages: bool,
+
+    /// Name of the severity enum converted into `log::Level`, e.g. MAV_SEVERITY
+    #[arg(long, value_name = "ENUM")]
+    log_severity_enum: Option<String>,
+
+    /// Emit `field_metadata` with the units, the range and the multiplier of message fields
+    #[arg(long)]
+    field_metadata: bool,
+
+    /// Emit `validate` checking message fields against their range and increment
+    #[arg(long)]
+    validation: bool,
+
+    /// Emit `all_variants()` and `name()` for every enum
+    #[arg(long)]
+    enum_variants: bool,
+
+    /// Implement `FromStr` and `Display` for enums with the names of the entries
+    #[arg(long)]
+    enum_strings: bool,
+
+    /// Emit a `{Message}Builder` with `with_{field}` setters for every message
+    #[arg(long)]
+    message_builders: bool,
+
+    /// Gate every message behind a feature named after it and write the features to `{module}.features.toml`
+    #[arg(long)]
+    per_message_features: bool,
+
+    /// Keep the names of enum entries and message fields from the definition
+    #[arg(long)]
+    preserve_names: bool,
+
+    /// Gate WIP messages and enums behind the `wip` feature of the generated crate
+    #[arg(long)]
+    strict_wip: bool,
+
+    /// Derives added to the messages and the enums, e.g. `Hash,my_crate::Validate`
+    #[arg(long, value_delimiter = ',')]
+    extra_derives: Vec<String>,
+
+    /// Derives added only to the messages
+    #[arg(long, value_delimiter = ',')]
+    extra_message_derives: Vec<String>,
+
+    /// Derives added only to the enums
+    #[arg(long, value_delimiter = ',')]
+    extra_enum_derives: Vec<String>,
+
+    /// Fail on suspicious definitions, e.g. enum entries without the enum name as a prefix
+    #[arg(long)]
+    strict: bool,
+
+    /// Skip included files, which don't exist, with a warning instead of failing
+    #[arg(long)]
+    allow_missing_includes: bool,
+
+    /// Name of the generated module instead of the one derived from the file name of a single input
+    #[arg(long, value_name = "NAME")]
+    output_module_name: Option<String>,
+
+    /// Collections used by the generated code
+    #[arg(long, value_enum, default_value_t = Collections::Std)]
+    collections: Collections,
+
+    /// Mark serialisation of messages for inlining, which increases the code size
+    #[arg(long, value_enum)]
+    inline: Option<Inline>,
+
+    /// Rust edition of the crate, which includes the generated code
+    #[arg(long, value_enum, default_value_t = Edition::Edition2021)]
+    edition: Edition,
+
+    /// Layout of the doc comments of message fields
+    #[arg(long, value_enum, default_value_t = DocStyle::Terse)]
+    doc_style: DocStyle,
+
+    /// Generate code for `#![no_std]` crates, `std` collections are replaced with `heapless`
+    #[arg(long)]
+    no_std: bool,
 }
 
-fn resolve_input(paths: Vec<PathBuf>) -> anyhow::Result<Vec<PathBuf>> {
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ListFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Collections {
+    /// `Vec`, `String` and `Cow` from `std`
+    Std,
+    /// The same collections from `alloc` for `no_std` crates
+    Alloc,
+    /// `heapless` collections with bounded capacity
+    Heapless,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Inline {
+    /// `#[inline]`
+    Hint,
+    /// `#[inline(always)]`
+    Always,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum DocStyle {
+    /// Only the description
+    Terse,
+    /// The description followed by the units, the range and other metadata
+    Full,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Edition {
+    #[value(name = "2018")]
+    Edition2018,
+    #[value(name = "2021")]
+    Edition2021,
+}
+
+impl From<Inline> for mavgen::codegen::rust::Inline {
+    fn from(inline: Inline) -> Self {
+        match inline {
+            Inline::Hint => Self::Hint,
+            Inline::Always => Self::Always,
+        }
+    }
+}
+
+impl From<DocStyle> for mavgen::codegen::rust::DocStyle {
+    fn from(doc_style: DocStyle) -> Self {
+        match doc_style {
+            DocStyle::Terse => Self::Terse,
+            DocStyle::Full => Self::Full,
+        }
+    }
+}
+
+impl From<Edition> for mavgen::codegen::rust::Edition {
+    fn from(edition: Edition) -> Self {
+        match edition {
+            Edition::Edition2018 => Self::Edition2018,
+            Edition::Edition2021 => Self::Edition2021,
+        }
+    }
+}
+
+impl From<Collections> for mavgen::codegen::rust::Collections {
+    fn from(collections: Collections) -> Self {
+        match collections {
+            Collections::Std => Self::Std,
+            Collections::Alloc => Self::Alloc,
+            Collections::Heapless => Self::Heapless,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print what the definition contains without generating code
+    Inspect {
+        /// Path to the definition file
+        input: PathBuf,
+    },
+    /// List the messages, enums and includes of a definition
+    List {
+        /// Path to the definition file
+        input: PathBuf,
+
+        /// Format of the listing
+        #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+        format: ListFormat,
+    },
+    /// Print how the generated API changes between two versions of a definition
+    ///
+    /// Only the options below change the compared API, the others are assumed
+    /// to be the same for both versions.
+    Diff {
+        /// Path to the old version of the definition file
+        old: PathBuf,
+
+        /// Path to the new version of the definition file
+        new: PathBuf,
+
+        /// The code is generated with `--open-enums`
+        #[arg(long)]
+        open_enums: bool,
+
+        /// The code is generated with `--non-exhaustive-enums`
+        #[arg(long)]
+        non_exhaustive_enums: bool,
+
+        /// The code is generated with `--compact-single-entry-enums`
+        #[arg(long)]
+        compact_single_entry_enums: bool,
+
+        /// The code is generated with `--preserve-names`
+        #[arg(long)]
+        preserve_names: bool,
+    },
+}
+
+fn parse_mav_message_name(name: &str) -> Result<String, String> {
+    let example = name.replace(mavgen::codegen::rust::MODULE_PLACEHOLDER, "Module");
+    let mut chars = example.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if valid {
+        Ok(name.to_owned())
+    } else {
+        Err(format!("{name:?} is not a valid Rust type name"))
+    }
+}
+
+enum Input {
+    Stdin(String),
+    Files(Vec<PathBuf>),
+}
+
+fn resolve_input(paths: Vec<PathBuf>) -> anyhow::Result<Input> {
+    if paths.iter().any(|path| path.as_os_str() == "-") {
+        if paths.len() != 1 {
+            anyhow::bail!("stdin can't be combined with other inputs");
+        }
+
+        let mut xml = String::new();
+        std::io::stdin()
+            .read_to_string(&mut xml)
+            .context("reading stdin")?;
+        return Ok(Input::Stdin(xml));
+    }
+
     let mut result = Vec::with_capacity(paths.len());
     for path in paths {
-        let meta = path
-            .metadata()
-            .with_context(|| format!("accessing {}", path.display()))?;
-
-        if meta.is_dir() {
-            let ls = std::fs::read_dir(&path)
-                .with_context(|| format!("reading directory {}", path.display()))?;
-            for entry in ls {
-                let path = entry?.path();
-                if path.is_file() {
-                    result.push(path);
+        if !path.exists() && is_glob(&path) {
+            let pattern = path.to_string_lossy();
+            let matches = glob::glob(&pattern)
+                .with_context(|| format!("invalid glob pattern {pattern}"))?
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| format!("expanding glob pattern {pattern}"))?;
+
+            let found = result.len();
+            for path in matches {
+                if path.is_dir() || is_definition(&path) {
+                    collect_definitions(path, &mut result)?;
                 }
             }
+            if result.len() == found {
+                anyhow::bail!("glob pattern {pattern} doesn't match any definition");
+            }
         } else {
+            collect_definitions(path, &mut result)?;
+        }
+    }
+
+    // The same file can be matched by several inputs
+    let mut seen = HashSet::new();
+    result.retain(|path| seen.insert(path.clone()));
+
+    if result.is_empty() {
+        anyhow::bail!("no input definitions");
+    }
+
+    Ok(Input::Files(result))
+}
+
+fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+fn is_definition(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("xml"))
+}
+
+/// Adds the `.xml` file or all `.xml` files from the directory and its
+/// subdirectories in a stable order. Other files are skipped with a warning.
+fn collect_definitions(path: PathBuf, result: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let meta = path
+        .metadata()
+        .with_context(|| format!("accessing {}", path.display()))?;
+
+    if !meta.is_dir() {
+        if is_definition(&path) {
             result.push(path);
+        } else {
+            eprintln!(
+                "warning: {} isn't an .xml file, skipping it",
+                path.display()
+            );
         }
+        return Ok(());
     }
 
-    Ok(result)
+    let mut entries = std::fs::read_dir(&path)
+        .with_context(|| format!("reading directory {}", path.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("reading directory {}", path.display()))?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() {
+            collect_definitions(entry, result)?;
+        } else if is_definition(&entry) {
+            result.push(entry);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the errors as a list, the errors after `max_errors` are only counted.
+fn print_errors<T: std::fmt::Display>(errors: &[T], max_errors: Option<usize>) {
+    let shown = max_errors.map_or(errors.len(), |max| max.min(errors.len()));
+    for error in &errors[..shown] {
+        eprintln!("- {error:#}");
+    }
+    if shown < errors.len() {
+        eprintln!("- ... and {} more", errors.len() - shown);
+    }
 }
 
-fn print_and_format_mavgen_error(error: mavgen::Error) -> anyhow::Error {
+fn print_and_format_mavgen_error(error: mavgen::Error, max_errors: Option<usize>) -> anyhow::Error {
     match error {
         mavgen::Error::CreateDir(error, path_buf) => anyhow::anyhow!(
             "failed to create directory {}: {}",
@@ -48,54 +457,279 @@ fn print_and_format_mavgen_error(error: mavgen::Error) -> anyhow::Error {
         ),
         mavgen::Error::ParseXml(errors) => {
             eprintln!("Errors occured during xml parsing:");
-            for error in errors {
-                eprintln!("- {error:#}");
-            }
+            print_errors(&errors, max_errors);
             anyhow::anyhow!("failed to parse XML")
         }
         mavgen::Error::NormalisePath(error, path_buf) => {
             anyhow::anyhow!("failed to normalise path {}: {}", path_buf.display(), error)
         }
-        mavgen::Error::Flattening(error) => anyhow::anyhow!("failed to flatten a module: {error}"),
+        mavgen::Error::Flattening(errors, path_buf) => {
+            eprintln!("Errors occured during flattening of {}", path_buf.display());
+            print_errors(&errors, max_errors);
+            anyhow::anyhow!("failed to flatten a module")
+        }
         mavgen::Error::Normalisation(errors, path_buf) => {
             eprintln!(
                 "Errors occured during model normalisation in {}",
                 path_buf.display()
             );
-
-            for error in errors {
-                eprintln!("- {error:#}");
-            }
+            print_errors(&errors, max_errors);
             anyhow::anyhow!("failed to normalise mavlink model")
         }
-        mavgen::Error::InvalidFilename(os_string) => {
-            anyhow::anyhow!("unsupported filename: {:?}", os_string)
-        }
+        mavgen::Error::InvalidFilename(os_string, path_buf) => anyhow::anyhow!(
+            "unsupported filename {:?} of {}",
+            os_string,
+            path_buf.display()
+        ),
         mavgen::Error::WritingToFile(error, path_buf) => anyhow::anyhow!(
             "failed to write to a file {}: {}",
             path_buf.display(),
             error
         ),
+        mavgen::Error::Collisions(collisions, path_buf) => {
+            eprintln!("Generated names collide in {}", path_buf.display());
+            print_errors(&collisions, max_errors);
+            anyhow::anyhow!("failed to generate code without name collisions")
+        }
+        mavgen::Error::InvalidDerives(derives) => {
+            eprintln!("Extra derives can't be added:");
+            print_errors(&derives, max_errors);
+            anyhow::anyhow!("invalid extra derives")
+        }
+        mavgen::Error::InvalidModuleName(name) => {
+            anyhow::anyhow!("module name {name:?} isn't a valid identifier or is a keyword")
+        }
+        mavgen::Error::InvalidMavMessageName(name, path) => anyhow::anyhow!(
+            "message enum name {name:?} of {} isn't a valid identifier or is a keyword",
+            path.display()
+        ),
+    }
+}
+
+/// Prints the warnings collected by the generation so far, see
+/// [`mavgen::Options::warnings`].
+fn print_warnings(options: &mavgen::Options) {
+    for warning in options.warnings.take() {
+        eprintln!("warning: {warning}");
+    }
+}
+
+fn generate_to_stdout(
+    input: Input,
+    options: &mavgen::Options,
+    max_errors: Option<usize>,
+) -> anyhow::Result<()> {
+    if options.nested {
+        anyhow::bail!("--stdout can't be combined with --nested, which generates multiple modules");
+    }
+
+    let code = match input {
+        Input::Stdin(xml) => mavgen::load_module_from_str(&xml)
+            .and_then(|module| mavgen::generate_module_to_string(&module, options)),
+        Input::Files(input) if input.len() == 1 => {
+            mavgen::generate_one_to_string_with_options(&input[0], options)
+        }
+        Input::Files(input) => anyhow::bail!(
+            "--stdout requires exactly one input definition, but got {}",
+            input.len()
+        ),
+    };
+    print_warnings(options);
+    let code = code.map_err(|err| print_and_format_mavgen_error(err, max_errors))?;
+
+    std::io::stdout()
+        .lock()
+        .write_all(code.as_bytes())
+        .context("writing to stdout")
+}
+
+/// What is generated from the input definitions.
+enum Generation {
+    Stdin(String),
+    One(PathBuf),
+    Combined(Vec<PathBuf>),
+    Dir(Vec<PathBuf>),
+}
+
+impl Generation {
+    fn write(self, output: &Path, options: &mavgen::Options) -> Result<(), mavgen::Error> {
+        match self {
+            Generation::Stdin(xml) => mavgen::generate_from_str_with_options(&xml, output, options),
+            Generation::One(input) => mavgen::generate_one_with_options(&input, output, options),
+            Generation::Combined(input) => {
+                mavgen::generate_combined_with_options(&input, output, options)
+            }
+            Generation::Dir(input) => mavgen::generate_dir_with_options(&input, output, options),
+        }
+    }
+
+    fn files(
+        self,
+        output: &Path,
+        options: &mavgen::Options,
+    ) -> Result<Vec<mavgen::GeneratedFile>, mavgen::Error> {
+        match self {
+            Generation::Stdin(xml) => mavgen::generate_from_str_to_files(&xml, output, options),
+            Generation::One(input) => mavgen::generate_one_to_files(&input, output, options),
+            Generation::Combined(input) => {
+                mavgen::generate_combined_to_files(&input, output, options)
+            }
+            Generation::Dir(input) => {
+                mavgen::generate_with_source_to_files(mavgen::FsSource, &input, output, options)
+            }
+        }
     }
 }
 
+fn print_dry_run(files: &[mavgen::GeneratedFile], out: &mut impl Write) -> std::io::Result<()> {
+    for file in files {
+        writeln!(
+            out,
+            "would write {} ({} bytes)",
+            file.path.display(),
+            file.contents.len()
+        )?;
+    }
+    Ok(())
+}
+
+fn inspect(input: PathBuf) -> anyhow::Result<()> {
+    let module =
+        mavgen::load_module(&input).map_err(|err| print_and_format_mavgen_error(err, None))?;
+    inspect::print_module(&module, &mut std::io::stdout().lock()).context("writing to stdout")?;
+    Ok(())
+}
+
+fn list(input: PathBuf, format: ListFormat) -> anyhow::Result<()> {
+    let (module, includes) = mavgen::load_module_with_includes(&input)
+        .map_err(|err| print_and_format_mavgen_error(err, None))?;
+    let mut out = std::io::stdout().lock();
+    match format {
+        ListFormat::Text => list::print_text(&module, &includes, &mut out),
+        ListFormat::Json => list::print_json(&module, &includes, &mut out),
+    }
+    .context("writing to stdout")
+}
+
+fn diff(old: PathBuf, new: PathBuf, codegen: mavgen::codegen::rust::Codegen) -> anyhow::Result<()> {
+    let old = mavgen::load_module(&old).map_err(|err| print_and_format_mavgen_error(err, None))?;
+    let new = mavgen::load_module(&new).map_err(|err| print_and_format_mavgen_error(err, None))?;
+    let changes = codegen.api_changes(&old, &new);
+    diff::print_changes(&changes, &mut std::io::stdout().lock()).context("writing to stdout")?;
+    Ok(())
+}
+
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
-    let input_is_one_file = args.input.len() == 1 && args.input[0].is_file();
+
+    match args.command {
+        Some(Command::Inspect { input }) => return inspect(input),
+        Some(Command::List { input, format }) => return list(input, format),
+        Some(Command::Diff {
+            old,
+            new,
+            open_enums,
+            non_exhaustive_enums,
+            compact_single_entry_enums,
+            preserve_names,
+        }) => {
+            let codegen = mavgen::codegen::rust::Codegen {
+                open_enums,
+                non_exhaustive_enums,
+                compact_single_entry_enums,
+                preserve_names,
+                ..Default::default()
+            };
+            return diff(old, new, codegen);
+        }
+        None => {}
+    }
+
+    let mut options = mavgen::Options::default();
+    options.codegen.reflection = args.reflection;
+    options.codegen.emit_framing = args.emit_framing;
+    options.codegen.dyn_messages = args.dyn_messages;
+    options.codegen.mav_message_name = args.mav_message_name;
+    options.codegen.open_enums = args.open_enums;
+    options.codegen.non_exhaustive_enums = args.non_exhaustive_enums;
+    options.codegen.compact_single_entry_enums = args.compact_single_entry_enums;
+    options.codegen.serde_snake_case_enums = args.serde_snake_case_enums;
+    options.codegen.serde_skip = args.serde_skip;
+    options.codegen.defmt = args.defmt;
+    options.codegen.arbitrary = args.arbitrary;
+    options.codegen.telemetry_formatter = args.telemetry_formatter;
+    options.codegen.clone_size_threshold = args.clone_size_threshold;
+    options.codegen.borrowed_messages = args.borrowed_messages;
+    options.codegen.log_severity_enum = args.log_severity_enum;
+    options.codegen.field_metadata = args.field_metadata;
+    options.codegen.validation = args.validation;
+    options.codegen.enum_variants = args.enum_variants;
+    options.codegen.enum_strings = args.enum_strings;
+    options.codegen.message_builders = args.message_builders;
+    options.codegen.strict_wip = args.strict_wip;
+    options.codegen.preserve_names = args.preserve_names;
+    options.codegen.per_message_features = args.per_message_features;
+    options.codegen.extra_derives = args.extra_derives;
+    options.codegen.extra_message_derives = args.extra_message_derives;
+    options.codegen.extra_enum_derives = args.extra_enum_derives;
+    options.codegen.collections = args.collections.into();
+    options.codegen.inline = args.inline.map(Into::into);
+    options.codegen.edition = args.edition.into();
+    options.codegen.doc_style = args.doc_style.into();
+    options.codegen.no_std = args.no_std;
+    options.nested = args.nested;
+    options.deduplicate = args.deduplicate;
+    options.prune_unused_enums = args.prune_unused_enums;
+    options.strict = args.strict;
+    options.allow_missing_includes = args.allow_missing_includes;
+    options.module_name = args.output_module_name;
+    if !args.only_messages.is_empty() {
+        options.message_filter = Some(mavgen::model::MessageFilter::Allow(
+            args.only_messages.into_iter().collect(),
+        ));
+    } else if !args.exclude_messages.is_empty() {
+        options.message_filter = Some(mavgen::model::MessageFilter::Deny(
+            args.exclude_messages.into_iter().collect(),
+        ));
+    }
+
+    if args.stdout {
+        return generate_to_stdout(resolve_input(args.input)?, &options, args.max_errors);
+    }
+
+    let output = args.output.expect("required by clap");
+    let input_is_file = args.input.len() == 1 && args.input[0].is_file();
     let input = resolve_input(args.input)?;
+    // Decided on the resolved input, as files, which aren't definitions, are skipped
+    let input_is_one_file =
+        input_is_file && matches!(&input, Input::Files(files) if files.len() == 1);
+    let single_module = !options.nested && (input_is_one_file || matches!(input, Input::Stdin(_)));
+    if options.module_name.is_some() && !single_module {
+        anyhow::bail!("--output-module-name requires exactly one input definition");
+    }
 
-    let result = if input_is_one_file {
-        mavgen::generate_one(&input[0], &args.output)
-    } else if args.output.is_file() {
-        anyhow::bail!("for multiple input definitions the output should point to a directory to generate a tree of modules");
-    } else {
-        mavgen::generate_dir(&input, &args.output)
+    let generation = match input {
+        Input::Stdin(xml) => Generation::Stdin(xml),
+        Input::Files(mut input) if input_is_one_file && !options.nested => {
+            Generation::One(input.remove(0))
+        }
+        Input::Files(_) if output.is_file() && (options.nested || options.deduplicate) => {
+            anyhow::bail!("for multiple input definitions the output should point to a directory to generate a tree of modules");
+        }
+        Input::Files(input) if output.is_file() || output.extension() == Some("rs".as_ref()) => {
+            Generation::Combined(input)
+        }
+        Input::Files(input) => Generation::Dir(input),
     };
 
-    if let Err(err) = result {
-        let err = print_and_format_mavgen_error(err);
-        Err(err)
-    } else {
-        Ok(())
+    if args.dry_run {
+        let files = generation.files(&output, &options);
+        print_warnings(&options);
+        let files = files.map_err(|err| print_and_format_mavgen_error(err, args.max_errors))?;
+        return print_dry_run(&files, &mut std::io::stdout().lock()).context("writing to stdout");
     }
+
+    let result = generation.write(&output, &options);
+    print_warnings(&options);
+    result.map_err(|err| print_and_format_mavgen_error(err, args.max_errors))
 }
@@ -0,0 +1,64 @@
+use std::{path::PathBuf, process::Command};
+
+const OLD: &str = r#"<?xml version="1.0"?>
+<mavlink>
+    <messages>
+        <message id="0" name="HEARTBEAT">
+            <field type="uint32_t" name="custom_mode">A bitfield for use for autopilot-specific flags</field>
+        </message>
+    </messages>
+</mavlink>
+"#;
+
+/// Creates a directory per test, as the tests run in parallel.
+fn definitions_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("mavgen-cli-diff-{}", std::process::id()))
+        .join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run_diff(name: &str, old: &str, new: &str) -> String {
+    let dir = definitions_dir(name);
+    let old_path = dir.join("old.xml");
+    let new_path = dir.join("new.xml");
+    std::fs::write(&old_path, old).unwrap();
+    std::fs::write(&new_path, new).unwrap();
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg("diff")
+        .arg(&old_path)
+        .arg(&new_path)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    String::from_utf8(result.stdout).unwrap()
+}
+
+#[test]
+fn test_diff_added_field() {
+    let new = OLD.replace(
+        "</field>",
+        r#"</field>
+            <field type="uint8_t" name="base_mode">System mode bitmap</field>"#,
+    );
+
+    let stdout = run_diff("added_field", OLD, &new);
+    assert!(stdout.contains("Semver impact: major"), "stdout: {stdout}");
+    assert!(
+        stdout.contains("major: added field `Heartbeat::base_mode: u8`"),
+        "stdout: {stdout}"
+    );
+}
+
+#[test]
+fn test_diff_without_changes() {
+    let stdout = run_diff("without_changes", OLD, OLD);
+    assert_eq!(stdout, "No API changes\n");
+}
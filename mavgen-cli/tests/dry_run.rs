@@ -0,0 +1,77 @@
+use std::process::Command;
+
+const MINIMAL: &str = r#"<?xml version="1.0"?>
+<mavlink>
+    <version>3</version>
+    <messages>
+        <message id="0" name="HEARTBEAT">
+            <field type="uint32_t" name="custom_mode">A bitfield for use for autopilot-specific flags</field>
+            <field type="uint8_t_mavlink_version" name="mavlink_version">MAVLink version</field>
+        </message>
+    </messages>
+</mavlink>
+"#;
+
+#[test]
+fn test_dry_run() {
+    let dir = std::env::temp_dir().join(format!("mavgen-cli-dry-run-{}", std::process::id()));
+    let definitions = dir.join("definitions");
+    std::fs::create_dir_all(&definitions).unwrap();
+    for name in ["first.xml", "second.xml"] {
+        std::fs::write(definitions.join(name), MINIMAL).unwrap();
+    }
+    let out_dir = dir.join("out");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg("--dry-run")
+        .arg(&definitions)
+        .arg("-o")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    assert!(!out_dir.exists());
+
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    let paths = stdout
+        .lines()
+        .map(|line| {
+            let path = line.strip_prefix("would write ").unwrap();
+            let (path, size) = path.rsplit_once(" (").unwrap();
+            assert!(size.ends_with(" bytes)"), "line: {line}");
+            path.to_owned()
+        })
+        .collect::<Vec<_>>();
+    let expected =
+        ["first.rs", "second.rs", "mod.rs"].map(|name| out_dir.join(name).display().to_string());
+    assert_eq!(paths, expected);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_dry_run_reports_errors() {
+    let dir =
+        std::env::temp_dir().join(format!("mavgen-cli-dry-run-errors-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let definition = dir.join("broken.xml");
+    std::fs::write(&definition, MINIMAL.replace("uint32_t", "uint33_t")).unwrap();
+    let out_dir = dir.join("out");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg("--dry-run")
+        .arg(&definition)
+        .arg("-o")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(result.stdout.is_empty());
+    assert!(!out_dir.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
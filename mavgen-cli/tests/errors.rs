@@ -0,0 +1,73 @@
+use std::{path::PathBuf, process::Command};
+
+/// Every message has an invalid field type, so there are three
+/// normalisation errors.
+const BROKEN: &str = r#"<?xml version="1.0"?>
+<mavlink>
+    <messages>
+        <message id="1" name="FIRST">
+            <field type="turbo_fish" name="value">Value</field>
+        </message>
+        <message id="2" name="SECOND">
+            <field type="turbo_fish" name="value">Value</field>
+        </message>
+        <message id="3" name="THIRD">
+            <field type="turbo_fish" name="value">Value</field>
+        </message>
+    </messages>
+</mavlink>
+"#;
+
+fn write_definition(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mavgen-cli-errors-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{name}.xml"));
+    std::fs::write(&path, BROKEN).unwrap();
+    path
+}
+
+fn listed_errors(stderr: &str) -> Vec<&str> {
+    stderr
+        .lines()
+        .filter(|line| line.starts_with("- "))
+        .collect()
+}
+
+#[test]
+fn test_all_errors_by_default() {
+    let definition = write_definition("all");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg("--stdout")
+        .arg(&definition)
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    let errors = listed_errors(&stderr);
+    assert_eq!(errors.len(), 3, "stderr: {stderr}");
+    assert!(
+        errors[0].contains("message FIRST field value: unknown type 'turbo_fish'"),
+        "stderr: {stderr}"
+    );
+    assert!(!stderr.contains("more"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_max_errors() {
+    let definition = write_definition("max");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .args(["--stdout", "--max-errors", "1"])
+        .arg(&definition)
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    let errors = listed_errors(&stderr);
+    assert_eq!(errors.len(), 2, "stderr: {stderr}");
+    assert!(errors[0].contains("FIRST"), "stderr: {stderr}");
+    assert_eq!(errors[1], "- ... and 2 more");
+}
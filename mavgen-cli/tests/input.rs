@@ -0,0 +1,180 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const MINIMAL: &str = r#"<?xml version="1.0"?>
+<mavlink>
+    <version>3</version>
+    <messages>
+        <message id="0" name="HEARTBEAT">
+            <field type="uint32_t" name="custom_mode">A bitfield for use for autopilot-specific flags</field>
+            <field type="uint8_t_mavlink_version" name="mavlink_version">MAVLink version</field>
+        </message>
+    </messages>
+</mavlink>
+"#;
+
+/// Creates a tree of definitions with files, which aren't definitions.
+fn definitions_tree(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("mavgen-cli-input-{}", std::process::id()))
+        .join(name);
+    std::fs::create_dir_all(dir.join("defs/nested/deeper")).unwrap();
+
+    for path in [
+        "defs/first.xml",
+        "defs/nested/second.xml",
+        "defs/nested/deeper/third.xml",
+    ] {
+        std::fs::write(dir.join(path), MINIMAL).unwrap();
+    }
+    std::fs::write(dir.join("defs/README.md"), "# Definitions").unwrap();
+    std::fs::write(dir.join("defs/nested/LICENSE"), "MIT").unwrap();
+    std::fs::write(dir.join("defs/.DS_Store"), [0x00, 0x01]).unwrap();
+
+    dir
+}
+
+fn generated_files(out_dir: &Path) -> Vec<String> {
+    let mut files = std::fs::read_dir(out_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+#[test]
+fn test_nested_directory() {
+    let dir = definitions_tree("nested");
+    let out_dir = dir.join("out");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg(dir.join("defs"))
+        .arg("-o")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    assert_eq!(
+        generated_files(&out_dir),
+        ["first.rs", "mod.rs", "second.rs", "third.rs"]
+    );
+}
+
+#[test]
+fn test_explicit_non_definition() {
+    let dir = definitions_tree("explicit");
+    let out_dir = dir.join("out");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg(dir.join("defs/first.xml"))
+        .arg(dir.join("defs/nested/second.xml"))
+        .arg(dir.join("defs/.DS_Store"))
+        .arg("-o")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(result.status.success(), "stderr: {stderr}");
+    assert!(
+        stderr.contains(".DS_Store isn't an .xml file, skipping it"),
+        "stderr: {stderr}"
+    );
+
+    assert_eq!(
+        generated_files(&out_dir),
+        ["first.rs", "mod.rs", "second.rs"]
+    );
+}
+
+#[test]
+fn test_glob_subset() {
+    let dir = definitions_tree("glob");
+    let out_dir = dir.join("out");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg(dir.join("defs/nested/**/*.xml"))
+        .arg("-o")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    assert_eq!(
+        generated_files(&out_dir),
+        ["mod.rs", "second.rs", "third.rs"]
+    );
+}
+
+#[test]
+fn test_glob_without_matches() {
+    let dir = definitions_tree("no-matches");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg(dir.join("defs/**/*.json"))
+        .arg("-o")
+        .arg(dir.join("out"))
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(
+        stderr.contains("doesn't match any definition"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_combined_file() {
+    let dir = definitions_tree("combined");
+    let output = dir.join("dialects.rs");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg(dir.join("defs"))
+        .arg("-o")
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    let code = std::fs::read_to_string(&output).unwrap();
+    for module in ["first", "second", "third"] {
+        assert!(
+            code.contains(&format!("pub mod {module} {{")),
+            "code: {code}"
+        );
+    }
+}
+
+#[test]
+fn test_no_definitions() {
+    let dir = definitions_tree("no_definitions");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg(dir.join("defs/.DS_Store"))
+        .arg("-o")
+        .arg(dir.join("out"))
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("no input definitions"), "stderr: {stderr}");
+    assert!(!stderr.contains("panicked"), "stderr: {stderr}");
+}
@@ -0,0 +1,70 @@
+use std::{path::PathBuf, process::Command};
+
+const DIALECT: &str = r#"<?xml version="1.0"?>
+<mavlink>
+    <version>3</version>
+    <enums>
+        <enum name="MAV_STATE">
+            <entry value="0" name="MAV_STATE_UNINIT"/>
+            <entry value="1" name="MAV_STATE_BOOT"/>
+        </enum>
+    </enums>
+    <messages>
+        <message id="0" name="HEARTBEAT">
+            <field type="uint8_t" name="type">Type of the system</field>
+            <field type="uint8_t" name="autopilot">Autopilot type</field>
+            <field type="uint8_t" name="base_mode">System mode bitmap</field>
+            <field type="uint32_t" name="custom_mode">A bitfield for use for autopilot-specific flags</field>
+            <field type="uint8_t" name="system_status" enum="MAV_STATE">System status flag</field>
+            <field type="uint8_t_mavlink_version" name="mavlink_version">MAVLink version</field>
+        </message>
+        <message id="300" name="PROTOCOL_VERSION">
+            <field type="uint16_t" name="version">Currently active MAVLink version</field>
+            <extensions/>
+            <field type="uint16_t" name="min_version">Minimum MAVLink version supported</field>
+        </message>
+    </messages>
+</mavlink>
+"#;
+
+fn write_dialect() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mavgen-cli-inspect-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("dialect.xml");
+    std::fs::write(&path, DIALECT).unwrap();
+    path
+}
+
+#[test]
+fn test_inspect() {
+    let dialect = write_dialect();
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg("inspect")
+        .arg(&dialect)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    assert!(stdout.contains("Enums (1):"), "stdout: {stdout}");
+    assert!(
+        stdout.contains("MAV_STATE (enum, 2 entries, u8)"),
+        "stdout: {stdout}"
+    );
+    assert!(stdout.contains("Messages (2):"), "stdout: {stdout}");
+    assert!(
+        stdout.contains("0 HEARTBEAT (crc_extra: 50, payload: 9 bytes, with extensions: 9 bytes)"),
+        "stdout: {stdout}"
+    );
+    assert!(
+        stdout.contains(
+            "300 PROTOCOL_VERSION (crc_extra: 53, payload: 2 bytes, with extensions: 4 bytes)"
+        ),
+        "stdout: {stdout}"
+    );
+}
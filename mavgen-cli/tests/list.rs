@@ -0,0 +1,110 @@
+use std::{path::PathBuf, process::Command};
+
+/// `test_chain.xml` includes `test_middle.xml`, which includes
+/// `test_base.xml`, every file defines one message.
+fn write_chain() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mavgen-cli-list-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let files = [
+        ("test_base.xml", None, 0, "HEARTBEAT"),
+        ("test_middle.xml", Some("test_base.xml"), 1, "SYS_STATUS"),
+        ("test_chain.xml", Some("test_middle.xml"), 2, "SYSTEM_TIME"),
+    ];
+    for (name, include, id, message) in files {
+        let include = include
+            .map(|include| format!("<include>{include}</include>"))
+            .unwrap_or_default();
+        let enums = if include.is_empty() {
+            r#"<enums>
+                <enum name="MAV_MODE_FLAG" bitmask="true">
+                    <entry value="1" name="MAV_MODE_FLAG_CUSTOM_MODE_ENABLED"/>
+                    <entry value="2" name="MAV_MODE_FLAG_TEST_ENABLED"/>
+                </enum>
+            </enums>"#
+        } else {
+            ""
+        };
+        std::fs::write(
+            dir.join(name),
+            format!(
+                r#"<?xml version="1.0"?>
+                <mavlink>
+                    {include}
+                    {enums}
+                    <messages>
+                        <message id="{id}" name="{message}">
+                            <field type="uint32_t" name="value">Value</field>
+                        </message>
+                    </messages>
+                </mavlink>
+                "#
+            ),
+        )
+        .unwrap();
+    }
+
+    dir.join("test_chain.xml")
+}
+
+fn list(args: &[&str]) -> String {
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg("list")
+        .arg(write_chain())
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    String::from_utf8(result.stdout).unwrap()
+}
+
+#[test]
+fn test_list_text() {
+    let listing = list(&[]);
+
+    assert!(listing.contains("Messages (3):"), "listing: {listing}");
+    for line in [
+        "     0 HEARTBEAT",
+        "     1 SYS_STATUS",
+        "     2 SYSTEM_TIME",
+    ] {
+        assert!(
+            listing.lines().any(|l| l == format!("  {line}")),
+            "listing: {listing}"
+        );
+    }
+    assert!(
+        listing.contains("  MAV_MODE_FLAG (2 entries, bitmask)"),
+        "listing: {listing}"
+    );
+
+    let includes = listing
+        .lines()
+        .skip_while(|line| !line.starts_with("Includes"))
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    assert_eq!(includes.len(), 2, "listing: {listing}");
+    assert!(includes[0].ends_with("test_base.xml"));
+    assert!(includes[1].ends_with("test_middle.xml"));
+}
+
+#[test]
+fn test_list_json() {
+    let listing = list(&["--format", "json"]);
+    let listing: serde_json::Value = serde_json::from_str(&listing).unwrap();
+
+    let messages = listing["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| message["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["HEARTBEAT", "SYS_STATUS", "SYSTEM_TIME"]);
+    assert_eq!(listing["enums"][0]["bitmask"], true);
+    assert_eq!(listing["includes"].as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,82 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+const MINIMAL: &str = r#"<?xml version="1.0"?>
+<mavlink>
+    <version>3</version>
+    <enums>
+        <enum name="MAV_STATE">
+            <entry value="0" name="MAV_STATE_UNINIT"/>
+            <entry value="1" name="MAV_STATE_BOOT"/>
+        </enum>
+    </enums>
+    <messages>
+        <message id="0" name="HEARTBEAT">
+            <field type="uint32_t" name="custom_mode">A bitfield for use for autopilot-specific flags</field>
+            <field type="uint8_t" name="system_status" enum="MAV_STATE">System status flag.</field>
+            <field type="uint8_t_mavlink_version" name="mavlink_version">MAVLink version</field>
+        </message>
+    </messages>
+</mavlink>
+"#;
+
+fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("mavgen-cli-{}", std::process::id()))
+        .join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run_with_stdin(args: &[&str], stdin: &str) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_stdin_to_file() {
+    let output = output_dir("stdin_to_file").join("minimal.rs");
+
+    let result = run_with_stdin(&["-", "-o", output.to_str().unwrap()], MINIMAL);
+    assert!(
+        result.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    let generated = std::fs::read_to_string(&output).unwrap();
+    syn::parse_file(&generated).unwrap();
+    assert!(generated.contains("pub struct Heartbeat"));
+}
+
+#[test]
+fn test_stdin_with_include() {
+    let output = output_dir("stdin_with_include").join("minimal.rs");
+    let xml = MINIMAL.replace("<version>", "<include>common.xml</include><version>");
+
+    let result = run_with_stdin(&["-", "-o", output.to_str().unwrap()], &xml);
+    assert!(!result.status.success());
+
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(
+        stderr.contains("includes are supported only for files"),
+        "stderr: {stderr}"
+    );
+}
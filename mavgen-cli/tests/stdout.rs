@@ -0,0 +1,67 @@
+use std::{path::PathBuf, process::Command};
+
+const MINIMAL: &str = r#"<?xml version="1.0"?>
+<mavlink>
+    <version>3</version>
+    <messages>
+        <message id="0" name="HEARTBEAT">
+            <field type="uint32_t" name="custom_mode">A bitfield for use for autopilot-specific flags</field>
+            <field type="uint8_t_mavlink_version" name="mavlink_version">MAVLink version</field>
+        </message>
+    </messages>
+</mavlink>
+"#;
+
+fn write_definitions(name: &str, count: usize) -> Vec<PathBuf> {
+    let dir = std::env::temp_dir()
+        .join(format!("mavgen-cli-stdout-{}", std::process::id()))
+        .join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    (0..count)
+        .map(|i| {
+            let path = dir.join(format!("minimal_{i}.xml"));
+            std::fs::write(&path, MINIMAL).unwrap();
+            path
+        })
+        .collect()
+}
+
+#[test]
+fn test_stdout() {
+    let definitions = write_definitions("one", 1);
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg("--stdout")
+        .args(&definitions)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    let generated = String::from_utf8(result.stdout).unwrap();
+    syn::parse_file(&generated).unwrap();
+    assert!(generated.contains("pub enum MavMessage"));
+}
+
+#[test]
+fn test_stdout_with_many_inputs() {
+    let definitions = write_definitions("many", 2);
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .arg("--stdout")
+        .args(&definitions)
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(result.stdout.is_empty());
+
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(
+        stderr.contains("--stdout requires exactly one input definition"),
+        "stderr: {stderr}"
+    );
+}
@@ -0,0 +1,41 @@
+use std::{path::PathBuf, process::Command};
+
+fn write_definition(name: &str, xml: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mavgen-cli-warnings-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{name}.xml"));
+    std::fs::write(&path, xml).unwrap();
+    path
+}
+
+#[test]
+fn test_missing_include_warning() {
+    let definition = write_definition(
+        "missing_include",
+        r#"<?xml version="1.0"?>
+<mavlink>
+    <include>does_not_exist.xml</include>
+    <messages>
+        <message id="1" name="PING">
+            <field type="uint32_t" name="seq">Sequence</field>
+        </message>
+    </messages>
+</mavlink>
+"#,
+    );
+
+    let result = Command::new(env!("CARGO_BIN_EXE_mavgen-cli"))
+        .args(["--stdout", "--allow-missing-includes"])
+        .arg(&definition)
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    assert!(stdout.contains("pub struct Ping {"), "stdout: {stdout}");
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(
+        stderr.contains("does_not_exist.xml, which doesn't exist, skipping it"),
+        "stderr: {stderr}"
+    );
+}
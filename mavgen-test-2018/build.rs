@@ -0,0 +1,24 @@
+use std::path::Path;
+
+fn main() {
+    let definition = Path::new(env!("CARGO_MANIFEST_DIR")).join("definitions/sample.xml");
+    let out_dir = std::env::var_os("OUT_DIR").unwrap();
+
+    let mut options = mavgen::Options::default();
+    options.codegen.edition = mavgen::codegen::rust::Edition::Edition2018;
+    options.codegen.reflection = true;
+    options.codegen.dyn_messages = true;
+    options.codegen.emit_framing = true;
+    options.codegen.field_metadata = true;
+    options.codegen.telemetry_formatter = true;
+    options.codegen.borrowed_messages = true;
+
+    mavgen::generate_dir_with_options(
+        std::slice::from_ref(&definition),
+        Path::new(&out_dir),
+        &options,
+    )
+    .expect("failed to generate mavlink");
+
+    println!("cargo:rerun-if-changed={}", definition.display());
+}
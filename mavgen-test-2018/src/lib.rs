@@ -0,0 +1,6 @@
+//! Checks that the code generated for the 2018 edition compiles in a crate
+//! of this edition.
+
+pub mod messages {
+    include!(concat!(env!("OUT_DIR"), "/mod.rs"));
+}
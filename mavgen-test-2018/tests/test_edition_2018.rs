@@ -0,0 +1,43 @@
+use std::convert::TryFrom;
+
+use mavgen_test_2018::messages::sample::{MavModeFlag, MavSeverity, Statustext, StatustextRef};
+use mavlink_core::{MavlinkVersion, MessageData};
+
+#[test]
+fn test_enum_conversions() {
+    assert_eq!(
+        MavSeverity::try_from(6u8).unwrap(),
+        MavSeverity::MavSeverityInfo
+    );
+    assert!(MavSeverity::try_from(3u8).is_err());
+    assert_eq!(u8::from(MavSeverity::MavSeverityInfo), 6);
+}
+
+#[test]
+fn test_roundtrip() {
+    let mut message = Statustext {
+        severity: MavSeverity::MavSeverityInfo,
+        id: 3,
+        ..Default::default()
+    };
+    message.set_text_str("Armed");
+
+    let mut buf = [0; 64];
+    let len = message
+        .serialize_payload(MavlinkVersion::V2, &mut buf)
+        .unwrap();
+    assert_eq!(
+        Statustext::deser(MavlinkVersion::V2, &buf[..len]).unwrap(),
+        message
+    );
+
+    let borrowed = StatustextRef::deser(MavlinkVersion::V2, &buf[..len]).unwrap();
+    assert_eq!(borrowed.text_as_str(), "Armed");
+    assert_eq!(borrowed.to_owned(), message);
+}
+
+#[test]
+fn test_bitmask() {
+    let flags = MavModeFlag::MavModeFlagSafetyArmed;
+    assert_eq!(flags.bits(), 128);
+}
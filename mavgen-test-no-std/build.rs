@@ -0,0 +1,20 @@
+use std::path::Path;
+
+fn main() {
+    let definition = Path::new(env!("CARGO_MANIFEST_DIR")).join("definitions/minimal.xml");
+    let out_dir = std::env::var_os("OUT_DIR").unwrap();
+
+    let mut options = mavgen::Options::default();
+    options.codegen.no_std = true;
+    options.codegen.reflection = true;
+    options.codegen.defmt = true;
+
+    mavgen::generate_dir_with_options(
+        std::slice::from_ref(&definition),
+        Path::new(&out_dir),
+        &options,
+    )
+    .expect("failed to generate mavlink");
+
+    println!("cargo:rerun-if-changed={}", definition.display());
+}
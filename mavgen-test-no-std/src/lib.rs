@@ -0,0 +1,6 @@
+//! Checks that the code generated with `no_std` compiles without `std`.
+#![no_std]
+
+pub mod messages {
+    include!(concat!(env!("OUT_DIR"), "/mod.rs"));
+}
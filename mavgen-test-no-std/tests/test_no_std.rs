@@ -0,0 +1,49 @@
+use mavgen_test_no_std::messages::minimal::{
+    CommandLong, FieldValue, MavCmd, MavSeverity, Reflect, Statustext,
+};
+use mavlink_core::MavlinkVersion;
+
+#[test]
+fn test_string_field() {
+    let mut message = Statustext {
+        severity: MavSeverity::MavSeverityInfo,
+        ..Default::default()
+    };
+    message.set_text_str("Armed");
+    assert_eq!(message.text_as_str(), "Armed");
+
+    // Invalid UTF-8 can't be replaced without allocation
+    message.text[2] = 0xFF;
+    assert_eq!(message.text_as_str(), "Ar");
+}
+
+#[test]
+fn test_serialize_payload() {
+    let mut message = Statustext::default();
+    message.set_text_str("ok");
+
+    let mut buf = [0; 64];
+    let len = message
+        .serialize_payload(MavlinkVersion::V2, &mut buf)
+        .unwrap();
+    assert_eq!(&buf[..len], &[0, b'o', b'k']);
+}
+
+#[test]
+fn test_heapless_collections() {
+    let command = CommandLong {
+        command: MavCmd::MavCmdNavWaypoint,
+        param1: 5.0,
+        param4: 90.0,
+        ..Default::default()
+    };
+    assert_eq!(
+        command.labeled_params().as_slice(),
+        [("Hold", 5.0), ("Yaw", 90.0)]
+    );
+
+    let Some(FieldValue::U8Array(text)) = Statustext::default().get_field("text") else {
+        panic!("text should be an array");
+    };
+    assert_eq!(text.len(), 50);
+}
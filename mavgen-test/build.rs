@@ -16,9 +16,76 @@ mod build {
 
         let out_dir = std::env::var_os("OUT_DIR").unwrap();
 
-        mavgen::generate_dir(&definitions, Path::new(&out_dir))
+        let mut options = mavgen::Options::default();
+        options.codegen.emit_framing = true;
+        options.codegen.dyn_messages = true;
+        options.codegen.arbitrary = true;
+        options.codegen.telemetry_formatter = true;
+        options.codegen.field_metadata = true;
+        options.codegen.enum_variants = true;
+        options.codegen.enum_strings = true;
+        options.codegen.message_builders = true;
+        options.codegen.borrowed_messages = true;
+        options.codegen.log_severity_enum = Some("MAV_SEVERITY".to_owned());
+
+        mavgen::generate_dir_with_options(&definitions, Path::new(&out_dir), &options)
             .expect("failed to generate mavlink");
 
+        let common = definitions
+            .iter()
+            .find(|def| def.file_name().is_some_and(|name| name == "common.xml"))
+            .expect("common.xml should exist");
+        let mut open_options = mavgen::Options::default();
+        open_options.codegen.open_enums = true;
+        open_options.codegen.enum_strings = true;
+        mavgen::generate_dir_with_options(
+            std::slice::from_ref(common),
+            &Path::new(&out_dir).join("open"),
+            &open_options,
+        )
+        .expect("failed to generate mavlink with open enums");
+
+        let mut alloc_options = mavgen::Options::default();
+        alloc_options.codegen.reflection = true;
+        alloc_options.codegen.emit_framing = true;
+        alloc_options.codegen.collections = mavgen::codegen::rust::Collections::Alloc;
+        mavgen::generate_dir_with_options(
+            std::slice::from_ref(common),
+            &Path::new(&out_dir).join("alloc"),
+            &alloc_options,
+        )
+        .expect("failed to generate mavlink with alloc collections");
+
+        // Generated without the features of `mod.rs`, so that it's always
+        // available for the serde tests
+        let serde_names = Path::new(env!("CARGO_MANIFEST_DIR")).join("definitions/serde_names.xml");
+        let serde_dir = Path::new(&out_dir).join("serde_names");
+        std::fs::create_dir_all(&serde_dir).unwrap();
+        let mut serde_options = mavgen::Options::default();
+        serde_options.codegen.serde_snake_case_enums = true;
+        mavgen::generate_one_with_options(
+            &serde_names,
+            &serde_dir.join("serde_names.rs"),
+            &serde_options,
+        )
+        .expect("failed to generate serde_names.xml");
+        std::fs::write(serde_dir.join("mod.rs"), "pub mod serde_names;\n").unwrap();
+        println!("cargo:rerun-if-changed={}", serde_names.display());
+
+        let validation = Path::new(env!("CARGO_MANIFEST_DIR")).join("definitions/validation.xml");
+        let validation_dir = Path::new(&out_dir).join("validation");
+        std::fs::create_dir_all(&validation_dir).unwrap();
+        let mut validation_options = mavgen::Options::default();
+        validation_options.codegen.validation = true;
+        mavgen::generate_one_with_options(
+            &validation,
+            &validation_dir.join("validation.rs"),
+            &validation_options,
+        )
+        .expect("failed to generate validation.xml");
+        std::fs::write(validation_dir.join("mod.rs"), "pub mod validation;\n").unwrap();
+        println!("cargo:rerun-if-changed={}", validation.display());
+
         for def in definitions {
             println!("cargo:rerun-if-changed={}", def.display());
         }
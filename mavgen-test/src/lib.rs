@@ -1,5 +1,27 @@
 #![cfg(feature = "mavgen-test")]
 
+extern crate alloc;
+
 pub mod messages {
     include!(concat!(env!("OUT_DIR"), "/mod.rs"));
 }
+
+/// Dialects generated with open enums.
+pub mod open_messages {
+    include!(concat!(env!("OUT_DIR"), "/open/mod.rs"));
+}
+
+/// Dialects generated with collections from `alloc`.
+pub mod alloc_messages {
+    include!(concat!(env!("OUT_DIR"), "/alloc/mod.rs"));
+}
+
+/// A small dialect with names, which differ in Rust and in the definition.
+pub mod serde_messages {
+    include!(concat!(env!("OUT_DIR"), "/serde_names/mod.rs"));
+}
+
+/// A small dialect with ranges and increments of fields.
+pub mod validation_messages {
+    include!(concat!(env!("OUT_DIR"), "/validation/mod.rs"));
+}
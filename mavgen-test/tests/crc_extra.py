@@ -0,0 +1,30 @@
+import argparse
+from pathlib import Path
+
+from mavtest import extract_mavlink_messages, import_mod
+
+
+def parse_args():
+    parser = argparse.ArgumentParser(
+        description='Print the id, name and CRC_EXTRA of each message of a pymavlink dialect',
+    )
+    parser.add_argument(
+        '--dialect',
+        type=Path,
+        required=True,
+        help='path to the python module generated by pymavlink',
+    )
+    return parser.parse_args()
+
+
+def main():
+    args = parse_args()
+    dialect: Path = args.dialect
+    module = import_mod(dialect.stem, dialect)
+
+    for message_class in extract_mavlink_messages(module):
+        print(message_class.id, message_class.msgname, message_class.crc_extra)
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,61 @@
+import argparse
+from pathlib import Path
+
+from mavtest import extract_mavlink_messages, set_dialect
+
+
+def parse_args():
+    parser = argparse.ArgumentParser(
+        description='Print the MAVLink 2 payload of a message packed by pymavlink as hex',
+    )
+    parser.add_argument(
+        '--dialect',
+        type=Path,
+        required=True,
+        help='path to the python module generated by pymavlink',
+    )
+    parser.add_argument('--message', required=True, help='name of the message')
+    parser.add_argument(
+        'fields',
+        nargs='*',
+        help='integer fields as NAME=VALUE, other fields are zero',
+    )
+    return parser.parse_args()
+
+
+def zero_message(cls):
+    message = {}
+
+    for i, (name, field_type) in enumerate(zip(cls.fieldnames, cls.fieldtypes)):
+        length = cls.array_lengths[cls.orders[i]]
+
+        if field_type == 'char':
+            message[name] = b''
+        elif length > 0:
+            message[name] = [0] * length
+        else:
+            message[name] = 0
+
+    return message
+
+
+def main():
+    args = parse_args()
+    module = set_dialect(args.dialect)
+
+    cls = next(cls for cls in extract_mavlink_messages(module) if cls.msgname == args.message)
+    fields = zero_message(cls)
+    for field in args.fields:
+        name, value = field.split('=', 1)
+        fields[name] = int(value, 0)
+
+    mav = module.MAVLink(None, srcSystem=1, srcComponent=1)
+    packed = cls(**fields).pack(mav)
+
+    # MAVLink 2 header is 10 bytes with the payload length in the second one
+    payload_len = packed[1]
+    print(packed[10 : 10 + payload_len].hex())
+
+
+if __name__ == '__main__':
+    main()
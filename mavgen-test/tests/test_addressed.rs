@@ -0,0 +1,17 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::CommandLong;
+
+#[test]
+fn test_addressed() {
+    let command = CommandLong::addressed(1, 190);
+
+    assert_eq!(
+        command,
+        CommandLong {
+            target_system: 1,
+            target_component: 190,
+            ..CommandLong::DEFAULT
+        }
+    );
+}
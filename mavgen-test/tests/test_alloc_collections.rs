@@ -0,0 +1,39 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::alloc_messages::common::{framing::Frame, FieldValue, Reflect, Statustext};
+
+#[test]
+fn test_alloc_string_field() {
+    let mut message = Statustext::default();
+    message.set_text_str("Takeoff");
+
+    let text: alloc::borrow::Cow<'_, str> = message.text_as_str();
+    assert_eq!(text, "Takeoff");
+}
+
+#[test]
+fn test_alloc_reflect_array_field() {
+    let mut message = Statustext::default();
+    message
+        .set_field("text", FieldValue::U8Array(vec![b'o'; 50]))
+        .unwrap();
+
+    let Some(FieldValue::U8Array(text)) = message.get_field("text") else {
+        panic!("text should be an array");
+    };
+    let text: alloc::vec::Vec<u8> = text;
+    assert_eq!(text, [b'o'; 50]);
+}
+
+#[test]
+fn test_alloc_frame_payload() {
+    const HEARTBEAT_V1: &[u8] = &[
+        0xfe, 0x09, 0xef, 0x01, 0x01, 0x00, 0x05, 0x00, 0x00, 0x00, 0x02, 0x03, 0x59, 0x03, 0x03,
+        0xf1, 0xd7,
+    ];
+
+    let (frame, _) = Frame::parse(HEARTBEAT_V1).unwrap();
+    assert_eq!(frame.build().unwrap(), HEARTBEAT_V1);
+    let payload: alloc::vec::Vec<u8> = frame.payload;
+    assert_eq!(payload.len(), 9);
+}
@@ -0,0 +1,20 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use std::collections::HashSet;
+
+use mavgen_test::messages::common::MavModeFlag;
+
+#[test]
+fn test_bitmask_in_hash_set() {
+    let armed = MavModeFlag::MavModeFlagSafetyArmed;
+    let custom = MavModeFlag::MavModeFlagCustomModeEnabled;
+
+    let mut set = HashSet::new();
+    assert!(set.insert(armed));
+    assert!(set.insert(custom));
+    assert!(set.insert(armed | custom));
+    assert!(!set.insert(MavModeFlag::from_bits_retain(armed.bits())));
+
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(&(custom | armed)));
+}
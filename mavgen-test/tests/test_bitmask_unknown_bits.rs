@@ -0,0 +1,42 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{AutopilotVersion, MavProtocolCapability};
+use mavlink_core::{MavlinkVersion, MessageData};
+
+/// `MAV_PROTOCOL_CAPABILITY` has no entry for this bit.
+const UNKNOWN_BIT: u32 = 1 << 30;
+
+#[test]
+fn test_unknown_bits_roundtrip() {
+    let message = AutopilotVersion {
+        capabilities: MavProtocolCapability::MavProtocolCapabilityMissionFloat,
+        ..AutopilotVersion::DEFAULT
+    };
+    let mut payload = [0; AutopilotVersion::ENCODED_LEN];
+    let len = message.ser(MavlinkVersion::V1, &mut payload);
+
+    // `capabilities` is the first field on the wire
+    payload[..4].copy_from_slice(&(1 | UNKNOWN_BIT).to_le_bytes());
+
+    let decoded = AutopilotVersion::deser(MavlinkVersion::V1, &payload[..len]).unwrap();
+    assert_eq!(decoded.capabilities.unknown_bits(), UNKNOWN_BIT);
+    assert!(decoded
+        .capabilities
+        .contains(MavProtocolCapability::MavProtocolCapabilityMissionFloat));
+    assert!(!decoded
+        .capabilities
+        .contains(MavProtocolCapability::MavProtocolCapabilityParamFloat));
+
+    let mut encoded = [0; AutopilotVersion::ENCODED_LEN];
+    let encoded_len = decoded.ser(MavlinkVersion::V1, &mut encoded);
+    assert_eq!(encoded[..encoded_len], payload[..len]);
+}
+
+#[test]
+fn test_known_bits_have_no_unknown_bits() {
+    assert_eq!(MavProtocolCapability::all().unknown_bits(), 0);
+    assert_eq!(
+        MavProtocolCapability::from_bits_retain(UNKNOWN_BIT).unknown_bits(),
+        UNKNOWN_BIT
+    );
+}
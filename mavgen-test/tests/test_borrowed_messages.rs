@@ -0,0 +1,51 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{MavSeverity, Statustext, StatustextRef};
+use mavlink_core::{MavlinkVersion, MessageData};
+
+fn statustext(text: &str, id: u16) -> Statustext {
+    let mut message = Statustext {
+        severity: MavSeverity::MavSeverityWarning,
+        id,
+        ..Statustext::default()
+    };
+    message.set_text_str(text);
+    message
+}
+
+#[test]
+fn test_borrowed_string_field() {
+    let message = statustext("PreArm: Gyros inconsistent", 7);
+    let mut buf = [0u8; 255];
+    let len = message
+        .serialize_payload(MavlinkVersion::V2, &mut buf)
+        .unwrap();
+    let payload = &buf[..len];
+
+    let borrowed = StatustextRef::deser(MavlinkVersion::V2, payload).unwrap();
+    assert_eq!(borrowed.severity, MavSeverity::MavSeverityWarning);
+    assert_eq!(borrowed.id, 7);
+    assert_eq!(borrowed.text.len(), message.text.len());
+    assert_eq!(borrowed.text_as_str(), "PreArm: Gyros inconsistent");
+
+    // The field points into the payload instead of a copy
+    let payload_range = payload.as_ptr_range();
+    assert!(payload_range.contains(&borrowed.text.as_ptr()));
+
+    assert_eq!(borrowed.to_owned(), message);
+}
+
+#[test]
+fn test_borrowed_truncated_payload() {
+    let message = statustext("short", 0);
+    let mut buf = [0u8; 255];
+    let len = message
+        .serialize_payload(MavlinkVersion::V2, &mut buf)
+        .unwrap();
+    assert!(len < Statustext::ENCODED_LEN);
+
+    let borrowed = StatustextRef::deser(MavlinkVersion::V2, &buf[..len]).unwrap();
+    assert_eq!(borrowed.text, b"short");
+    assert_eq!(borrowed.text_as_str(), "short");
+    assert_eq!(borrowed.to_owned(), message);
+}
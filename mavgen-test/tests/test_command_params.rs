@@ -0,0 +1,43 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{CommandLong, MavCmd};
+
+#[test]
+fn test_waypoint_labeled_params() {
+    let command = CommandLong {
+        command: MavCmd::MavCmdNavWaypoint,
+        param1: 5.0,
+        param2: 2.0,
+        param3: 0.0,
+        param4: 90.0,
+        param5: 47.39,
+        param6: 8.54,
+        param7: 500.0,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        command.labeled_params(),
+        [
+            ("Hold", 5.0),
+            ("Accept Radius", 2.0),
+            ("Pass Radius", 0.0),
+            ("Yaw", 90.0),
+            ("Latitude", 47.39),
+            ("Longitude", 8.54),
+            ("Altitude", 500.0),
+        ]
+    );
+}
+
+#[test]
+fn test_command_without_params() {
+    assert_eq!(MavCmd::MavCmdNavReturnToLaunch.param_labels(), [None; 7]);
+
+    let command = CommandLong {
+        command: MavCmd::MavCmdNavReturnToLaunch,
+        param1: 1.0,
+        ..Default::default()
+    };
+    assert!(command.labeled_params().is_empty());
+}
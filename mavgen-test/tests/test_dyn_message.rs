@@ -0,0 +1,56 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{DynMessage, Heartbeat, MavMessage, Statustext};
+use mavlink_core::{MavlinkVersion, Message, MessageData};
+
+#[test]
+fn test_serialize_boxed_messages() {
+    let mut statustext = Statustext::default();
+    statustext.set_text_str("ok");
+    let heartbeat = Heartbeat {
+        custom_mode: 5,
+        ..Default::default()
+    };
+
+    let messages: Vec<Box<dyn DynMessage>> = vec![Box::new(heartbeat), Box::new(statustext)];
+
+    let ids = messages
+        .iter()
+        .map(|message| message.id())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [Heartbeat::ID, Statustext::ID]);
+    let names = messages
+        .iter()
+        .map(|message| message.name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["HEARTBEAT", "STATUSTEXT"]);
+
+    let mut buf = Vec::new();
+    for message in &messages {
+        message.serialize(MavlinkVersion::V1, &mut buf);
+    }
+    assert_eq!(buf.len(), Heartbeat::ENCODED_LEN + Statustext::ENCODED_LEN);
+
+    let mut expected = vec![0; Heartbeat::ENCODED_LEN];
+    let len = heartbeat.ser(MavlinkVersion::V1, &mut expected);
+    assert_eq!(&buf[..len], &expected[..len]);
+}
+
+#[test]
+fn test_serialize_boxed_mav_message() {
+    let heartbeat = Heartbeat {
+        custom_mode: 5,
+        ..Default::default()
+    };
+    let message: Box<dyn DynMessage> = Box::new(MavMessage::Heartbeat(heartbeat));
+    assert_eq!(message.id(), Heartbeat::ID);
+    assert_eq!(message.extra_crc(), Heartbeat::EXTRA_CRC);
+
+    let mut buf = vec![0xAA];
+    message.serialize(MavlinkVersion::V2, &mut buf);
+
+    let mut expected = [0; Heartbeat::ENCODED_LEN];
+    let len = MavMessage::Heartbeat(heartbeat).ser(MavlinkVersion::V2, &mut expected);
+    assert_eq!(buf[0], 0xAA);
+    assert_eq!(&buf[1..], &expected[..len]);
+}
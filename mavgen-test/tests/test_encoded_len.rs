@@ -0,0 +1,25 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{CommandAck, Heartbeat, MAX_PAYLOAD_LEN, MESSAGE_INFO};
+use mavlink_core::MessageData;
+
+#[test]
+fn test_encoded_len() {
+    assert_eq!(Heartbeat::ENCODED_LEN, 9);
+    assert_eq!(Heartbeat::ENCODED_LEN_V1, 9);
+
+    // The extension fields are only in MAVLink 2 payloads
+    assert_eq!(CommandAck::ENCODED_LEN_V1, 3);
+    assert!(CommandAck::ENCODED_LEN > CommandAck::ENCODED_LEN_V1);
+}
+
+#[test]
+fn test_max_payload_len() {
+    let max = MESSAGE_INFO
+        .iter()
+        .map(|info| info.max_payload_len)
+        .max()
+        .unwrap();
+    assert_eq!(MAX_PAYLOAD_LEN, max);
+    assert!(MAX_PAYLOAD_LEN <= 255);
+}
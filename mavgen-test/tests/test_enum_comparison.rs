@@ -0,0 +1,16 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{Heartbeat, MavState};
+
+#[test]
+fn test_compare_enum_with_raw_value() {
+    let heartbeat = Heartbeat {
+        system_status: MavState::MavStateActive,
+        ..Default::default()
+    };
+
+    assert!(heartbeat.system_status == MavState::MavStateActive as u8);
+    assert!(heartbeat.system_status == 4u8);
+    assert!(4u8 == heartbeat.system_status);
+    assert!(heartbeat.system_status != MavState::MavStateStandby as u8);
+}
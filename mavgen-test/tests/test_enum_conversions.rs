@@ -0,0 +1,27 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{InvalidEnumValue, MavCmd, MavState};
+
+#[test]
+fn test_roundtrip() {
+    let state = MavState::try_from(4u8).unwrap();
+    assert_eq!(state, MavState::MavStateActive);
+    assert_eq!(u8::from(state), 4);
+
+    let command = MavCmd::try_from(400u16).unwrap();
+    assert_eq!(command, MavCmd::MavCmdComponentArmDisarm);
+    assert_eq!(u16::from(command), 400);
+}
+
+#[test]
+fn test_undefined_value() {
+    let err = MavState::try_from(200u8).unwrap_err();
+    assert_eq!(
+        err,
+        InvalidEnumValue {
+            enum_name: "MAV_STATE",
+            value: 200,
+        }
+    );
+    assert_eq!(err.to_string(), "200 is not a value of enum MAV_STATE");
+}
@@ -0,0 +1,44 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{MavState, MavType, UnknownEnumName};
+
+#[test]
+fn test_round_trip_by_name() {
+    for (name, state) in [
+        ("MAV_STATE_ACTIVE", MavState::MavStateActive),
+        ("MAV_STATE_STANDBY", MavState::MavStateStandby),
+    ] {
+        assert_eq!(name.parse::<MavState>().unwrap(), state);
+        assert_eq!(state.to_string(), name);
+    }
+
+    let mav_type = MavType::MavTypeQuadrotor;
+    assert_eq!(mav_type.to_string().parse::<MavType>().unwrap(), mav_type);
+}
+
+#[test]
+fn test_unknown_name() {
+    let err = "MAV_STATE_DANCING".parse::<MavState>().unwrap_err();
+    assert_eq!(
+        err,
+        UnknownEnumName {
+            enum_name: "MAV_STATE",
+            name: "MAV_STATE_DANCING".to_owned(),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "MAV_STATE_DANCING is not an entry of enum MAV_STATE"
+    );
+}
+
+#[test]
+fn test_open_enum_without_entry() {
+    use mavgen_test::open_messages::common::MavType;
+
+    assert_eq!(MavType(200).to_string(), "200");
+    assert_eq!(
+        "MAV_TYPE_QUADROTOR".parse::<MavType>().unwrap(),
+        MavType::MavTypeQuadrotor
+    );
+}
@@ -0,0 +1,38 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{MavModeFlag, MavSeverity};
+
+#[test]
+fn test_all_variants() {
+    let names = [
+        "MAV_SEVERITY_EMERGENCY",
+        "MAV_SEVERITY_ALERT",
+        "MAV_SEVERITY_CRITICAL",
+        "MAV_SEVERITY_ERROR",
+        "MAV_SEVERITY_WARNING",
+        "MAV_SEVERITY_NOTICE",
+        "MAV_SEVERITY_INFO",
+        "MAV_SEVERITY_DEBUG",
+    ];
+
+    let variants = MavSeverity::all_variants();
+    assert_eq!(variants.len(), names.len());
+    assert_eq!(variants[0], MavSeverity::MavSeverityEmergency);
+
+    for (variant, name) in variants.iter().zip(names) {
+        assert_eq!(variant.name(), name);
+    }
+}
+
+#[test]
+fn test_bitmask_variants() {
+    let variants = MavModeFlag::all_variants();
+    assert_eq!(variants.len(), 8);
+    assert!(variants.iter().all(|flag| flag.bits().count_ones() == 1));
+
+    assert_eq!(variants[0], MavModeFlag::MavModeFlagSafetyArmed);
+    assert_eq!(variants[0].name(), "MAV_MODE_FLAG_SAFETY_ARMED");
+
+    let combined = MavModeFlag::MavModeFlagSafetyArmed | MavModeFlag::MavModeFlagTestEnabled;
+    assert_eq!(combined.name(), "");
+}
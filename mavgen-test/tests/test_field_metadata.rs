@@ -0,0 +1,22 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{FieldMeta, SystemTime};
+
+#[test]
+fn test_units() {
+    let meta = SystemTime::field_metadata("time_boot_ms").unwrap();
+    assert_eq!(meta.units, Some("ms"));
+    assert_eq!(meta.multiplier, None);
+
+    assert_eq!(
+        SystemTime::field_metadata("time_unix_usec").and_then(|meta: FieldMeta| meta.units),
+        Some("us")
+    );
+}
+
+#[test]
+fn test_unknown_field() {
+    assert_eq!(SystemTime::field_metadata("time_boot"), None);
+    // Field names are the names from the definition
+    assert_eq!(SystemTime::field_metadata("TIME_BOOT_MS"), None);
+}
@@ -0,0 +1,228 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{
+    framing::{
+        extra_crc, Frame, FrameError, FrameLayout, FrameReader, ResyncPolicy, Version,
+        MAVLINK_IFLAG_SIGNED, SIGNATURE_LEN,
+    },
+    Heartbeat,
+};
+use mavlink_core::{MavlinkVersion, MessageData};
+use sha2::Digest;
+
+const HEARTBEAT_V1: &[u8] = &[
+    0xfe, 0x09, 0xef, 0x01, 0x01, 0x00, 0x05, 0x00, 0x00, 0x00, 0x02, 0x03, 0x59, 0x03, 0x03, 0xf1,
+    0xd7,
+];
+
+const HEARTBEAT_V2: &[u8] = &[
+    0xfd, 0x09, 0x00, 0x00, 0xef, 0x01, 0x01, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x02, 0x03,
+    0x59, 0x03, 0x03, 0x10, 0xf0,
+];
+
+#[test]
+fn test_heartbeat_v1_roundtrip() {
+    let (frame, len) = Frame::parse(HEARTBEAT_V1).unwrap();
+    assert_eq!(len, HEARTBEAT_V1.len());
+    assert_eq!(frame.version, Version::V1);
+    assert_eq!(frame.sequence, 0xef);
+    assert_eq!(frame.system_id, 1);
+    assert_eq!(frame.component_id, 1);
+    assert_eq!(frame.message_id, Heartbeat::ID);
+    assert_eq!(frame.signature, None);
+
+    let heartbeat = Heartbeat::deser(MavlinkVersion::V1, &frame.payload).unwrap();
+    assert_eq!(heartbeat.custom_mode, 5);
+    assert_eq!(heartbeat.mavlink_version, 3);
+
+    assert_eq!(frame.build().unwrap(), HEARTBEAT_V1);
+}
+
+#[test]
+fn test_heartbeat_v2_roundtrip() {
+    let (frame, len) = Frame::parse(HEARTBEAT_V2).unwrap();
+    assert_eq!(len, HEARTBEAT_V2.len());
+    assert_eq!(frame.version, Version::V2);
+    assert_eq!(frame.message_id, Heartbeat::ID);
+
+    assert_eq!(frame.build().unwrap(), HEARTBEAT_V2);
+}
+
+#[test]
+fn test_signature_passthrough() {
+    let (mut frame, _) = Frame::parse(HEARTBEAT_V2).unwrap();
+    frame.signature = Some([0xAA; 13]);
+
+    let bytes = frame.build().unwrap();
+    assert_eq!(bytes.len(), HEARTBEAT_V2.len() + 13);
+
+    let (parsed, len) = Frame::parse(&bytes).unwrap();
+    assert_eq!(len, bytes.len());
+    assert_eq!(parsed.signature, Some([0xAA; 13]));
+    assert_eq!(parsed.payload, frame.payload);
+}
+
+#[test]
+fn test_layout_unsigned_v2() {
+    let layout = FrameLayout::locate(HEARTBEAT_V2).unwrap();
+    assert_eq!(layout.version, Version::V2);
+    assert_eq!(layout.header, 0..10);
+    assert_eq!(layout.payload, 10..19);
+    assert_eq!(layout.checksum, 19..21);
+    assert_eq!(layout.signature, None);
+    assert_eq!(layout.frame_len(), HEARTBEAT_V2.len());
+}
+
+#[test]
+fn test_layout_signed_v2() {
+    let (mut frame, _) = Frame::parse(HEARTBEAT_V2).unwrap();
+    frame.signature = Some([0xAA; SIGNATURE_LEN]);
+    let bytes = frame.build().unwrap();
+    assert_ne!(bytes[2] & MAVLINK_IFLAG_SIGNED, 0);
+
+    // Only the header is needed to locate the parts
+    let layout = FrameLayout::locate(&bytes[..10]).unwrap();
+    assert_eq!(layout.payload, 10..19);
+    assert_eq!(layout.checksum, 19..21);
+    assert_eq!(layout.signature, Some(21..34));
+    assert_eq!(layout.frame_len(), bytes.len());
+    assert_eq!(&bytes[layout.signature.unwrap()], &[0xAA; SIGNATURE_LEN]);
+}
+
+#[test]
+fn test_layout_v1() {
+    let layout = FrameLayout::locate(HEARTBEAT_V1).unwrap();
+    assert_eq!(layout.version, Version::V1);
+    assert_eq!(layout.payload, 6..15);
+    assert_eq!(layout.signature, None);
+    assert_eq!(layout.frame_len(), HEARTBEAT_V1.len());
+
+    assert_eq!(
+        FrameLayout::locate(&HEARTBEAT_V2[..5]),
+        Err(FrameError::Incomplete)
+    );
+}
+
+#[test]
+fn test_invalid_checksum() {
+    let mut bytes = HEARTBEAT_V1.to_vec();
+    bytes[16] ^= 0xFF;
+
+    let err = Frame::parse(&bytes).unwrap_err();
+    assert!(matches!(err, FrameError::InvalidChecksum { .. }));
+}
+
+#[test]
+fn test_incomplete() {
+    let err = Frame::parse(&HEARTBEAT_V2[..10]).unwrap_err();
+    assert_eq!(err, FrameError::Incomplete);
+}
+
+#[test]
+fn test_reader_skips_garbage() {
+    let mut reader = FrameReader::new();
+    reader.push(&[0x00, 0x11, 0x22]);
+    assert_eq!(reader.next_frame(), None);
+    assert_eq!(reader.discarded(), 3);
+
+    // A frame split between two reads
+    reader.push(&HEARTBEAT_V2[..5]);
+    assert_eq!(reader.next_frame(), None);
+    reader.push(&HEARTBEAT_V2[5..]);
+    reader.push(HEARTBEAT_V1);
+
+    let frame = reader.next_frame().unwrap().unwrap();
+    assert_eq!(frame.version, Version::V2);
+    let frame = reader.next_frame().unwrap().unwrap();
+    assert_eq!(frame.version, Version::V1);
+    assert_eq!(reader.next_frame(), None);
+    assert_eq!(reader.discarded(), 3);
+}
+
+#[test]
+fn test_reader_recovers_after_corrupted_frame() {
+    let mut corrupted = HEARTBEAT_V2.to_vec();
+    *corrupted.last_mut().unwrap() ^= 0xff;
+
+    let mut reader = FrameReader::new();
+    reader.push(&[0xaa, 0xbb]);
+    reader.push(&corrupted);
+    reader.push(HEARTBEAT_V2);
+
+    assert!(matches!(
+        reader.next_frame(),
+        Some(Err(FrameError::InvalidChecksum { .. }))
+    ));
+
+    let (expected, _) = Frame::parse(HEARTBEAT_V2).unwrap();
+    assert_eq!(reader.next_frame(), Some(Ok(expected)));
+    assert_eq!(reader.next_frame(), None);
+    assert_eq!(reader.discarded(), 2 + corrupted.len());
+}
+
+#[test]
+fn test_reader_scan_window() {
+    let mut reader = FrameReader::with_policy(ResyncPolicy { max_scan: Some(4) });
+    reader.push(&[0x00; 6]);
+    reader.push(HEARTBEAT_V1);
+
+    assert_eq!(reader.next_frame(), Some(Err(FrameError::ResyncLimit(4))));
+    assert_eq!(reader.discarded(), 4);
+
+    let frame = reader.next_frame().unwrap().unwrap();
+    assert_eq!(frame.message_id, Heartbeat::ID);
+    assert_eq!(reader.discarded(), 6);
+}
+
+#[test]
+fn test_framing_extra_crc() {
+    const HEARTBEAT_EXTRA_CRC: Option<u8> = extra_crc(0);
+
+    assert_eq!(HEARTBEAT_EXTRA_CRC, Some(Heartbeat::EXTRA_CRC));
+    assert_eq!(extra_crc(253), Some(83));
+    assert_eq!(extra_crc(u32::MAX), None);
+}
+
+fn sha256(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = sha2::Sha256::new();
+    parts.iter().for_each(|part| hasher.update(part));
+    hasher.finalize().into()
+}
+
+const SECRET_KEY: [u8; 32] = [0x42; 32];
+
+#[test]
+fn test_sign_and_verify() {
+    let (mut frame, _) = Frame::parse(HEARTBEAT_V2).unwrap();
+    frame.sign(&SECRET_KEY, 7, 0x01_0203_0405, sha256).unwrap();
+
+    assert_eq!(
+        frame.signature,
+        Some([0x07, 0x05, 0x04, 0x03, 0x02, 0x01, 0x00, 0x5e, 0x77, 0xd2, 0x08, 0xa9, 0x83])
+    );
+    assert_eq!(frame.signature_link(), Some((7, 0x01_0203_0405)));
+    assert_eq!(frame.verify(&SECRET_KEY, sha256), Ok(true));
+    assert_eq!(frame.verify(&[0x24; 32], sha256), Ok(false));
+
+    // The signature survives the wire
+    let bytes = frame.build().unwrap();
+    let (parsed, _) = Frame::parse(&bytes).unwrap();
+    assert_eq!(parsed.verify(&SECRET_KEY, sha256), Ok(true));
+
+    let mut tampered = parsed;
+    tampered.payload[0] ^= 0x01;
+    assert_eq!(tampered.verify(&SECRET_KEY, sha256), Ok(false));
+}
+
+#[test]
+fn test_sign_unsigned_and_v1() {
+    let (frame, _) = Frame::parse(HEARTBEAT_V2).unwrap();
+    assert_eq!(frame.verify(&SECRET_KEY, sha256), Ok(false));
+    assert_eq!(frame.signature_link(), None);
+
+    let (mut frame, _) = Frame::parse(HEARTBEAT_V1).unwrap();
+    assert_eq!(
+        frame.sign(&SECRET_KEY, 0, 0, sha256),
+        Err(FrameError::UnsignableVersion)
+    );
+}
@@ -0,0 +1,31 @@
+#![cfg(all(feature = "mavgen-test", feature = "common", feature = "log"))]
+
+use log::Level;
+use mavgen_test::messages::common::{MavSeverity, Statustext};
+
+#[test]
+fn test_severity_to_log_level() {
+    let levels = [
+        (MavSeverity::MavSeverityEmergency, Level::Error),
+        (MavSeverity::MavSeverityAlert, Level::Error),
+        (MavSeverity::MavSeverityCritical, Level::Error),
+        (MavSeverity::MavSeverityError, Level::Error),
+        (MavSeverity::MavSeverityWarning, Level::Warn),
+        (MavSeverity::MavSeverityNotice, Level::Info),
+        (MavSeverity::MavSeverityInfo, Level::Info),
+        (MavSeverity::MavSeverityDebug, Level::Debug),
+    ];
+
+    for (severity, level) in levels {
+        assert_eq!(Level::from(severity), level, "{severity:?}");
+    }
+}
+
+#[test]
+fn test_statustext_log_level() {
+    let message = Statustext {
+        severity: MavSeverity::MavSeverityWarning,
+        ..Statustext::default()
+    };
+    assert_eq!(Level::from(message.severity), Level::Warn);
+}
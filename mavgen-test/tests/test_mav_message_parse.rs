@@ -0,0 +1,32 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{Heartbeat, MavMessage, MavState, MAVLINK_VERSION};
+use mavlink_core::{error::ParserError, MavlinkVersion, Message, MessageData};
+
+#[test]
+fn test_parse_heartbeat() {
+    let heartbeat = Heartbeat {
+        custom_mode: 5,
+        system_status: MavState::MavStateActive,
+        ..Heartbeat::DEFAULT
+    };
+    assert_eq!(heartbeat.mavlink_version, MAVLINK_VERSION);
+
+    for version in [MavlinkVersion::V1, MavlinkVersion::V2] {
+        let mut payload = [0; 255];
+        let len = heartbeat.ser(version, &mut payload);
+
+        let message = MavMessage::parse(version, Heartbeat::ID, &payload[..len]).unwrap();
+        assert_eq!(message.message_id(), Heartbeat::ID);
+        assert_eq!(message, MavMessage::Heartbeat(heartbeat));
+    }
+}
+
+#[test]
+fn test_parse_unknown_id() {
+    let result = MavMessage::parse(MavlinkVersion::V2, u32::MAX, &[]);
+    assert!(
+        matches!(result, Err(ParserError::UnknownMessage { id: u32::MAX })),
+        "{result:?}"
+    );
+}
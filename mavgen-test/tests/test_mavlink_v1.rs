@@ -0,0 +1,55 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{CommandAck, MavCmd, MavResult};
+use mavlink_core::{MavlinkVersion, MessageData};
+
+fn command_ack() -> CommandAck {
+    CommandAck {
+        command: MavCmd::MavCmdComponentArmDisarm,
+        result: MavResult::MavResultDenied,
+        progress: 50,
+        result_param2: 7,
+        target_system: 1,
+        target_component: 2,
+    }
+}
+
+#[test]
+fn test_v1_omits_extension_fields() {
+    let message = command_ack();
+
+    let mut v1 = [0; CommandAck::ENCODED_LEN];
+    let v1_len = message.ser(MavlinkVersion::V1, &mut v1);
+    // `command` and `result` only
+    assert_eq!(v1_len, 3);
+
+    let mut v2 = [0; CommandAck::ENCODED_LEN];
+    let v2_len = message.ser(MavlinkVersion::V2, &mut v2);
+    assert_eq!(v2_len, CommandAck::ENCODED_LEN);
+    assert_eq!(v1[..v1_len], v2[..v1_len]);
+
+    let parsed = CommandAck::deser(MavlinkVersion::V1, &v1[..v1_len]).unwrap();
+    assert_eq!(
+        parsed,
+        CommandAck {
+            command: message.command,
+            result: message.result,
+            ..CommandAck::DEFAULT
+        }
+    );
+
+    let parsed = CommandAck::deser(MavlinkVersion::V2, &v2[..v2_len]).unwrap();
+    assert_eq!(parsed, message);
+}
+
+#[test]
+fn test_v1_ignores_trailing_bytes() {
+    let message = command_ack();
+
+    // A v2 payload parsed as v1 still ends before the extension fields
+    let mut v2 = [0; CommandAck::ENCODED_LEN];
+    let v2_len = message.ser(MavlinkVersion::V2, &mut v2);
+    let parsed = CommandAck::deser(MavlinkVersion::V1, &v2[..v2_len]).unwrap();
+    assert_eq!(parsed.progress, CommandAck::DEFAULT.progress);
+    assert_eq!(parsed.target_system, CommandAck::DEFAULT.target_system);
+}
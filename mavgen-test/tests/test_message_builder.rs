@@ -0,0 +1,22 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::Heartbeat;
+
+#[test]
+fn test_builder_keeps_defaults() {
+    let heartbeat = Heartbeat::builder().with_custom_mode(5).build();
+
+    assert_eq!(heartbeat.custom_mode, 5);
+    assert_eq!(
+        heartbeat,
+        Heartbeat {
+            custom_mode: 5,
+            ..Heartbeat::DEFAULT
+        }
+    );
+}
+
+#[test]
+fn test_builder_without_fields_is_default() {
+    assert_eq!(Heartbeat::builder().build(), Heartbeat::default());
+}
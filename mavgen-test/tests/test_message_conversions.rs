@@ -0,0 +1,31 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{Heartbeat, MavMessage, MavState};
+
+fn message_name(message: impl Into<MavMessage>) -> &'static str {
+    match message.into() {
+        MavMessage::Heartbeat(_) => "HEARTBEAT",
+        _ => "OTHER",
+    }
+}
+
+#[test]
+fn test_owned_and_borrowed_conversions() {
+    let heartbeat = Heartbeat {
+        system_status: MavState::MavStateActive,
+        ..Heartbeat::DEFAULT
+    };
+
+    let borrowed = MavMessage::from(&heartbeat);
+    assert_eq!(borrowed, MavMessage::Heartbeat(heartbeat));
+
+    let owned: MavMessage = heartbeat.into();
+    assert_eq!(owned, borrowed);
+
+    assert_eq!(message_name(heartbeat), "HEARTBEAT");
+
+    let heartbeats = [heartbeat, Heartbeat::DEFAULT];
+    let messages: Vec<MavMessage> = heartbeats.iter().map(MavMessage::from).collect();
+    assert_eq!(messages[0], owned);
+    assert_eq!(messages[1], MavMessage::Heartbeat(Heartbeat::DEFAULT));
+}
@@ -0,0 +1,109 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use std::collections::{HashMap, HashSet};
+
+use mavgen_test::messages::common::{Heartbeat, MavMessage, MESSAGE_INFO};
+use mavlink_core::{Message, MessageData};
+
+type Handler = Box<dyn Fn(&MavMessage) -> &'static str>;
+
+#[test]
+fn test_message_info_matches_dialect() {
+    let ids = MESSAGE_INFO
+        .iter()
+        .map(|info| info.id)
+        .collect::<HashSet<_>>();
+    assert_eq!(ids.len(), MESSAGE_INFO.len());
+
+    for info in MESSAGE_INFO {
+        assert_eq!(MavMessage::message_id_from_name(info.name), Ok(info.id));
+        assert_eq!(MavMessage::id_from_name(info.name), Some(info.id));
+        assert_eq!(MavMessage::name_from_id(info.id), Some(info.name));
+        assert_eq!(MavMessage::try_extra_crc(info.id), Some(info.extra_crc));
+
+        let message = MavMessage::default_message_from_id(info.id).unwrap();
+        assert_eq!(message.message_name(), info.name);
+    }
+}
+
+#[test]
+fn test_register_handlers() {
+    let handlers: HashMap<u32, Handler> = MESSAGE_INFO
+        .iter()
+        .map(|info| {
+            let name = info.name;
+            let handler: Handler = Box::new(move |_| name);
+            (info.id, handler)
+        })
+        .collect();
+
+    assert_eq!(handlers.len(), MESSAGE_INFO.len());
+
+    let message = MavMessage::Heartbeat(Heartbeat::default());
+    assert_eq!(handlers[&message.message_id()](&message), Heartbeat::NAME);
+}
+
+#[test]
+fn test_heartbeat_name_id_roundtrip() {
+    assert_eq!(Heartbeat::ID, 0);
+    assert_eq!(MavMessage::id_from_name("HEARTBEAT"), Some(0));
+    assert_eq!(MavMessage::name_from_id(0), Some("HEARTBEAT"));
+
+    assert_eq!(MavMessage::id_from_name("NOT_A_MESSAGE"), None);
+    assert_eq!(MavMessage::name_from_id(u32::MAX), None);
+}
+
+#[test]
+fn test_ids_by_extra_crc() {
+    assert!(MavMessage::ids_by_extra_crc(Heartbeat::EXTRA_CRC).contains(&Heartbeat::ID));
+
+    for info in MESSAGE_INFO {
+        let ids = MavMessage::ids_by_extra_crc(info.extra_crc);
+        assert!(ids.contains(&info.id), "{} not in {ids:?}", info.name);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    let total = (0..=u8::MAX)
+        .map(|extra_crc| MavMessage::ids_by_extra_crc(extra_crc).len())
+        .sum::<usize>();
+    assert_eq!(total, MESSAGE_INFO.len());
+}
+
+#[test]
+fn test_extra_crc_in_const_context() {
+    const HEARTBEAT_EXTRA_CRC: Option<u8> = MavMessage::try_extra_crc(0);
+    const UNKNOWN_EXTRA_CRC: Option<u8> = MavMessage::try_extra_crc(u32::MAX);
+    const HEARTBEAT_IDS: &[u32] = MavMessage::ids_by_extra_crc(50);
+
+    assert_eq!(HEARTBEAT_EXTRA_CRC, Some(50));
+    assert_eq!(UNKNOWN_EXTRA_CRC, None);
+    assert!(HEARTBEAT_IDS.contains(&0));
+
+    // Well-known values from the MAVLink specification
+    for (id, extra_crc) in [(1, 124), (30, 39), (76, 152), (253, 83)] {
+        assert_eq!(
+            MavMessage::try_extra_crc(id),
+            Some(extra_crc),
+            "message {id}"
+        );
+    }
+}
+
+#[test]
+fn test_all_message_ids() {
+    assert!(MavMessage::ALL_MESSAGE_IDS
+        .windows(2)
+        .all(|pair| pair[0] < pair[1]));
+    assert_eq!(MavMessage::ALL_MESSAGE_IDS.len(), MESSAGE_INFO.len());
+    assert_eq!(
+        MavMessage::ALL_MESSAGE_NAMES.len(),
+        MavMessage::ALL_MESSAGE_IDS.len()
+    );
+
+    for (id, name) in MavMessage::ALL_MESSAGE_IDS
+        .iter()
+        .zip(MavMessage::ALL_MESSAGE_NAMES)
+    {
+        assert_eq!(MavMessage::name_from_id(*id), Some(*name));
+    }
+}
@@ -0,0 +1,62 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use std::fmt::Debug;
+
+use mavgen_test::messages::common::{CommandAck, Heartbeat, MavMessage, MavResult};
+use mavlink_core::{error::ParserError, MavlinkVersion, Message, MessageData};
+
+/// Exercises the trait only through `M`, the way generic code over
+/// dialects uses the message enum.
+fn check_message<M: Message + PartialEq + Debug>(message: &M) {
+    let id = message.message_id();
+    let name = message.message_name();
+
+    assert_eq!(M::message_id_from_name(name), Ok(id));
+    assert!(M::default_message_from_id(id).is_ok());
+
+    for version in [MavlinkVersion::V1, MavlinkVersion::V2] {
+        let mut bytes = [0; 255];
+        let len = message.ser(version, &mut bytes);
+        let parsed = M::parse(version, id, &bytes[..len]).unwrap();
+        if version == MavlinkVersion::V2 {
+            assert_eq!(&parsed, message);
+        }
+    }
+}
+
+#[test]
+fn test_message_trait() {
+    let heartbeat = MavMessage::Heartbeat(Heartbeat {
+        custom_mode: 5,
+        ..Heartbeat::DEFAULT
+    });
+    check_message(&heartbeat);
+    assert_eq!(heartbeat.message_id(), Heartbeat::ID);
+    assert_eq!(heartbeat.message_name(), "HEARTBEAT");
+    assert_eq!(
+        <MavMessage as Message>::extra_crc(Heartbeat::ID),
+        Heartbeat::EXTRA_CRC
+    );
+
+    let command_ack = MavMessage::CommandAck(CommandAck {
+        result: MavResult::MavResultDenied,
+        progress: 50,
+        ..CommandAck::DEFAULT
+    });
+    check_message(&command_ack);
+    assert_eq!(
+        <MavMessage as Message>::extra_crc(CommandAck::ID),
+        CommandAck::EXTRA_CRC
+    );
+}
+
+#[test]
+fn test_message_trait_unknown() {
+    assert!(matches!(
+        <MavMessage as Message>::parse(MavlinkVersion::V2, u32::MAX, &[]),
+        Err(ParserError::UnknownMessage { id: u32::MAX })
+    ));
+    assert!(<MavMessage as Message>::message_id_from_name("NOT_A_MESSAGE").is_err());
+    assert!(<MavMessage as Message>::default_message_from_id(u32::MAX).is_err());
+    assert_eq!(<MavMessage as Message>::extra_crc(u32::MAX), 0);
+}
@@ -0,0 +1,33 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::{
+    messages::common as closed,
+    open_messages::common::{Heartbeat, MavAutopilot, MavState, MavType},
+};
+use mavlink_core::{MavlinkVersion, MessageData};
+
+/// HEARTBEAT payload with an unknown `MAV_TYPE` of 200.
+const PAYLOAD: &[u8] = &[0x05, 0x00, 0x00, 0x00, 200, 0x03, 0x59, 0x03, 0x03];
+
+#[test]
+fn test_unknown_value_round_trip() {
+    assert!(closed::Heartbeat::deser(MavlinkVersion::V2, PAYLOAD).is_err());
+
+    let heartbeat = Heartbeat::deser(MavlinkVersion::V2, PAYLOAD).unwrap();
+    assert_eq!(heartbeat.r#type, MavType(200));
+
+    let mut buf = [0; Heartbeat::ENCODED_LEN];
+    let len = heartbeat.ser(MavlinkVersion::V2, &mut buf);
+    assert_eq!(&buf[..len], PAYLOAD);
+}
+
+#[test]
+fn test_known_constants() {
+    let heartbeat = Heartbeat::deser(MavlinkVersion::V2, PAYLOAD).unwrap();
+
+    assert_eq!(heartbeat.autopilot, MavAutopilot::MavAutopilotArdupilotmega);
+    assert_eq!(heartbeat.system_status, MavState::MavStateStandby);
+    assert_eq!(MavState::MavStateStandby, MavState(3));
+    assert!(heartbeat.system_status == 3u8);
+    assert_eq!(MavState::default(), MavState::MavStateUninit);
+}
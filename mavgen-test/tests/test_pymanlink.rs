@@ -1,6 +1,7 @@
 #![cfg(feature = "mavgen-test")]
 
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
     println,
     process::Command,
@@ -98,6 +99,152 @@ fn test_dialect(dialect_xml: &str) {
     assert!(status.success());
 }
 
+/// Checks `CRC_EXTRA` of each generated message against the one computed by
+/// pymavlink for the same dialect.
+#[allow(unused)]
+fn test_crc_extra(dialect_xml: &str, infos: impl IntoIterator<Item = (u32, &'static str, u8)>) {
+    init_python();
+
+    let dialect_module = compile_mavlink(&definitions().join(dialect_xml));
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let output = Command::new(python())
+        .arg(test_dir.join("tests/crc_extra.py"))
+        .arg("--dialect")
+        .arg(dialect_module)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let expected: HashMap<u32, (&str, u8)> = stdout
+        .lines()
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            let id = parts.next().unwrap().parse().unwrap();
+            let name = parts.next().unwrap();
+            let crc_extra = parts.next().unwrap().parse().unwrap();
+            (id, (name, crc_extra))
+        })
+        .collect();
+
+    let mut checked = 0;
+    for (id, name, extra_crc) in infos {
+        let expected = expected
+            .get(&id)
+            .unwrap_or_else(|| panic!("{name} ({id}) is not generated by pymavlink"));
+        assert_eq!(*expected, (name, extra_crc), "message {id}");
+        checked += 1;
+    }
+
+    assert_eq!(checked, expected.len());
+}
+
+/// Returns the MAVLink 2 payload of the message packed by pymavlink, fields
+/// other than `fields` are zero.
+#[allow(unused)]
+fn pack_payload(dialect_xml: &str, message: &str, fields: &[(&str, u64)]) -> Vec<u8> {
+    init_python();
+
+    let dialect_module = compile_mavlink(&definitions().join(dialect_xml));
+    let test_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let output = Command::new(python())
+        .arg(test_dir.join("tests/pack_payload.py"))
+        .arg("--dialect")
+        .arg(dialect_module)
+        .arg("--message")
+        .arg(message)
+        .args(fields.iter().map(|(name, value)| format!("{name}={value}")))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    let hex = String::from_utf8(output.stdout).unwrap();
+    let hex = hex.trim();
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+#[cfg(feature = "common")]
+fn test_common_v2_truncation() {
+    use mavgen_test::messages::common::SystemTime;
+    use mavlink_core::{MavlinkVersion, MessageData};
+
+    let cases = [
+        SystemTime {
+            time_unix_usec: 0,
+            time_boot_ms: 0x1234,
+        },
+        // The first byte is never truncated
+        SystemTime {
+            time_unix_usec: 0,
+            time_boot_ms: 0,
+        },
+    ];
+
+    for message in cases {
+        let mut payload = [0; SystemTime::ENCODED_LEN];
+        let len = message.ser(MavlinkVersion::V2, &mut payload);
+
+        let expected = pack_payload(
+            "common.xml",
+            SystemTime::NAME,
+            &[("time_boot_ms", u64::from(message.time_boot_ms))],
+        );
+        assert_eq!(payload[..len], expected, "{message:?}");
+
+        let parsed = SystemTime::deser(MavlinkVersion::V2, &payload[..len]).unwrap();
+        assert_eq!(parsed, message);
+    }
+}
+
+macro_rules! test_crc_extra {
+    ($test:ident, $dialect:ident, $xml:literal) => {
+        #[test]
+        fn $test() {
+            let infos = mavgen_test::messages::$dialect::MESSAGE_INFO
+                .iter()
+                .map(|info| (info.id, info.name, info.extra_crc));
+            test_crc_extra($xml, infos);
+        }
+    };
+}
+
+#[cfg(feature = "all")]
+test_crc_extra!(test_all_crc_extra, all, "all.xml");
+#[cfg(feature = "ardupilotmega")]
+test_crc_extra!(
+    test_ardupilotmega_crc_extra,
+    ardupilotmega,
+    "ardupilotmega.xml"
+);
+#[cfg(feature = "asluav")]
+test_crc_extra!(test_asluav_crc_extra, asluav, "ASLUAV.xml");
+#[cfg(feature = "avssuas")]
+test_crc_extra!(test_avssuas_crc_extra, avssuas, "AVSSUAS.xml");
+#[cfg(feature = "common")]
+test_crc_extra!(test_common_crc_extra, common, "common.xml");
+#[cfg(feature = "cubepilot")]
+test_crc_extra!(test_cubepilot_crc_extra, cubepilot, "cubepilot.xml");
+#[cfg(feature = "development")]
+test_crc_extra!(test_development_crc_extra, development, "development.xml");
+#[cfg(feature = "matrixpilot")]
+test_crc_extra!(test_matrixpilot_crc_extra, matrixpilot, "matrixpilot.xml");
+#[cfg(feature = "paparazzi")]
+test_crc_extra!(test_paparazzi_crc_extra, paparazzi, "paparazzi.xml");
+#[cfg(feature = "storm32")]
+test_crc_extra!(test_storm32_crc_extra, storm32, "storm32.xml");
+#[cfg(feature = "u_avionix")]
+test_crc_extra!(test_u_avionix_crc_extra, u_avionix, "uAvionix.xml");
+#[cfg(feature = "ualberta")]
+test_crc_extra!(test_ualberta_crc_extra, ualberta, "ualberta.xml");
+
 #[test]
 #[cfg(feature = "all")]
 fn test_all() {
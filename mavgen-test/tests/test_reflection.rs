@@ -0,0 +1,75 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::alloc_messages::common::{
+    FieldValue, Heartbeat, MavMessage, MavType, Reflect, ReflectError,
+};
+
+#[test]
+fn test_reflect_heartbeat() {
+    let mut message = Heartbeat {
+        custom_mode: 7,
+        ..Heartbeat::DEFAULT
+    };
+
+    assert_eq!(
+        message.field_names(),
+        [
+            "type",
+            "autopilot",
+            "base_mode",
+            "custom_mode",
+            "system_status",
+            "mavlink_version"
+        ]
+    );
+    assert_eq!(message.get_field("custom_mode"), Some(FieldValue::U32(7)));
+
+    message
+        .set_field("custom_mode", FieldValue::U32(42))
+        .unwrap();
+    assert_eq!(message.custom_mode, 42);
+
+    // Enum fields are represented with their underlying integer
+    message
+        .set_field("type", FieldValue::U8(MavType::MavTypeQuadrotor.bits()))
+        .unwrap();
+    assert_eq!(message.r#type, MavType::MavTypeQuadrotor);
+    assert_eq!(
+        message.get_field("type"),
+        Some(FieldValue::U8(MavType::MavTypeQuadrotor.bits()))
+    );
+}
+
+#[test]
+fn test_reflect_errors() {
+    let mut message = Heartbeat::DEFAULT;
+
+    assert_eq!(message.get_field("no_such_field"), None);
+    assert_eq!(
+        message.set_field("no_such_field", FieldValue::U32(1)),
+        Err(ReflectError::UnknownField)
+    );
+    assert_eq!(
+        message.set_field("custom_mode", FieldValue::F32(1.0)),
+        Err(ReflectError::TypeMismatch)
+    );
+    assert_eq!(
+        message.set_field("type", FieldValue::U8(u8::MAX)),
+        Err(ReflectError::InvalidValue)
+    );
+    assert_eq!(message, Heartbeat::DEFAULT);
+}
+
+#[test]
+fn test_reflect_mav_message() {
+    let mut message = MavMessage::Heartbeat(Heartbeat::DEFAULT);
+
+    message
+        .set_field("custom_mode", FieldValue::U32(5))
+        .unwrap();
+    assert_eq!(message.get_field("custom_mode"), Some(FieldValue::U32(5)));
+    assert_eq!(
+        message.set_field("custom_mode", FieldValue::U8(5)),
+        Err(ReflectError::TypeMismatch)
+    );
+}
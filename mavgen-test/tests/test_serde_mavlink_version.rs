@@ -0,0 +1,30 @@
+#![cfg(all(feature = "mavgen-test", feature = "common", feature = "serde"))]
+
+use mavgen_test::messages::common::Heartbeat;
+
+#[test]
+fn test_mavlink_version_is_serialized() {
+    let heartbeat = Heartbeat {
+        custom_mode: 5,
+        mavlink_version: 3,
+        ..Default::default()
+    };
+
+    let json = serde_json::to_value(heartbeat).unwrap();
+    assert_eq!(json["mavlink_version"], 3);
+    assert_eq!(json["custom_mode"], 5);
+}
+
+#[test]
+fn test_mavlink_version_is_ignored_on_deserialize() {
+    let mut json = serde_json::to_value(Heartbeat::default()).unwrap();
+
+    // Set to the version of the dialect instead of the JSON value
+    json["mavlink_version"] = 1.into();
+    let parsed: Heartbeat = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(parsed.mavlink_version, 3);
+
+    json.as_object_mut().unwrap().remove("mavlink_version");
+    let parsed: Heartbeat = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.mavlink_version, 3);
+}
@@ -0,0 +1,26 @@
+#![cfg(all(feature = "mavgen-test", feature = "serde"))]
+
+use mavgen_test::serde_messages::serde_names::{GpsFixType, GpsRaw, MavMessage};
+
+#[test]
+fn test_fields_serialize_under_definition_names() {
+    let message = MavMessage::GpsRaw(GpsRaw {
+        time_usec: 42,
+        fix_type: GpsFixType::GpsFixTypeRtkFixed,
+        satellites_visible: 7,
+    });
+
+    let json = serde_json::to_value(message.clone()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "mavpackettype": "GPS_RAW",
+            "timeUsec": 42,
+            "fixType": "gps_fix_type_rtk_fixed",
+            "satellites_visible": 7,
+        })
+    );
+
+    let parsed: MavMessage = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, message);
+}
@@ -0,0 +1,54 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{BufferTooSmall, Heartbeat, MavMessage, MavState};
+use mavlink_core::{MavlinkVersion, MessageData};
+
+#[test]
+fn test_serialize_into_stack_buffer() {
+    let heartbeat = Heartbeat {
+        custom_mode: 5,
+        system_status: MavState::MavStateActive,
+        ..Heartbeat::DEFAULT
+    };
+    let mut buf = [0u8; 255];
+
+    let len = heartbeat
+        .serialize_payload(MavlinkVersion::V1, &mut buf)
+        .unwrap();
+    assert_eq!(len, Heartbeat::ENCODED_LEN);
+
+    // The trailing `mavlink_version` is zero, so it's truncated
+    let len = heartbeat
+        .serialize_payload(MavlinkVersion::V2, &mut buf)
+        .unwrap();
+    assert!(len < Heartbeat::ENCODED_LEN);
+    assert_eq!(
+        Heartbeat::deser(MavlinkVersion::V2, &buf[..len]).unwrap(),
+        heartbeat
+    );
+
+    let message = MavMessage::Heartbeat(heartbeat);
+    let mut message_buf = [0u8; 255];
+    let message_len = message
+        .serialize_payload(MavlinkVersion::V2, &mut message_buf)
+        .unwrap();
+    assert_eq!(message_buf[..message_len], buf[..len]);
+}
+
+#[test]
+fn test_serialize_into_small_buffer() {
+    let mut buf = [0u8; 4];
+    let expected = BufferTooSmall {
+        required: Heartbeat::ENCODED_LEN,
+        available: 4,
+    };
+
+    assert_eq!(
+        Heartbeat::DEFAULT.serialize_payload(MavlinkVersion::V2, &mut buf),
+        Err(expected)
+    );
+    assert_eq!(
+        MavMessage::Heartbeat(Heartbeat::DEFAULT).serialize_payload(MavlinkVersion::V2, &mut buf),
+        Err(expected)
+    );
+}
@@ -0,0 +1,38 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::Statustext;
+
+#[test]
+fn test_padded_string_field() {
+    let mut message = Statustext::default();
+    message.set_text_str("PreArm: Gyros inconsistent");
+
+    assert_eq!(&message.text[..26], b"PreArm: Gyros inconsistent");
+    assert!(message.text[26..].iter().all(|c| *c == 0));
+    assert_eq!(message.text_as_str(), "PreArm: Gyros inconsistent");
+
+    message.set_text_str("short");
+    assert_eq!(message.text_as_str(), "short");
+    assert!(message.text[5..].iter().all(|c| *c == 0));
+}
+
+#[test]
+fn test_full_string_field() {
+    let mut message = Statustext::default();
+    let text = "x".repeat(message.text.len());
+    message.set_text_str(&text);
+    assert_eq!(message.text_as_str(), text);
+
+    // Longer values are truncated
+    message.set_text_str(&format!("{text}yz"));
+    assert_eq!(message.text_as_str(), text);
+}
+
+#[test]
+fn test_invalid_utf8_string_field() {
+    let mut message = Statustext::default();
+    message.text[..4].copy_from_slice(&[b'o', 0xFF, b'k', 0]);
+    message.text[5] = b'!';
+
+    assert_eq!(message.text_as_str(), "o\u{FFFD}k");
+}
@@ -0,0 +1,37 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{Heartbeat, MavState, MavType, Statustext, SystemTime};
+
+#[test]
+fn test_fmt_heartbeat() {
+    let heartbeat = Heartbeat {
+        custom_mode: 5,
+        r#type: MavType::MavTypeQuadrotor,
+        system_status: MavState::MavStateActive,
+        mavlink_version: 3,
+        ..Default::default()
+    };
+
+    let output = heartbeat.fmt_telemetry();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&"custom_mode: 5"));
+    assert!(lines.contains(&"type: MavTypeQuadrotor"));
+    assert!(lines.contains(&"system_status: MavStateActive"));
+    assert!(lines.contains(&"mavlink_version: 3"));
+}
+
+#[test]
+fn test_fmt_units_and_strings() {
+    let system_time = SystemTime {
+        time_unix_usec: 1,
+        time_boot_ms: 2,
+    };
+    assert_eq!(
+        system_time.fmt_telemetry(),
+        "time_unix_usec: 1 us\ntime_boot_ms: 2 ms\n"
+    );
+
+    let mut statustext = Statustext::default();
+    statustext.set_text_str("armed");
+    assert!(statustext.fmt_telemetry().contains("text: armed\n"));
+}
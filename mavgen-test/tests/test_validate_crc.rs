@@ -0,0 +1,33 @@
+#![cfg(all(feature = "mavgen-test", feature = "common"))]
+
+use mavgen_test::messages::common::{Heartbeat, MavMessage};
+use mavlink_core::MessageData;
+
+/// v1 `HEARTBEAT` of a quadrotor with the ArduPilot autopilot.
+const HEARTBEAT_FRAME: [u8; 17] = [
+    0xFE, 0x09, 0x4E, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x51, 0x04, 0x03, 0x1C,
+    0x7F,
+];
+
+fn split(frame: &[u8]) -> (&[u8], u16) {
+    let (data, crc) = frame[1..].split_at(frame.len() - 3);
+    (data, u16::from_le_bytes([crc[0], crc[1]]))
+}
+
+#[test]
+fn test_validate_crc() {
+    let (data, crc) = split(&HEARTBEAT_FRAME);
+    assert!(MavMessage::validate_crc(Heartbeat::ID, data, crc));
+
+    assert!(!MavMessage::validate_crc(Heartbeat::ID, data, crc ^ 1));
+    assert!(!MavMessage::validate_crc(u32::MAX, data, crc));
+}
+
+#[test]
+fn test_validate_crc_corrupted() {
+    let mut frame = HEARTBEAT_FRAME;
+    frame[10] ^= 0x01;
+
+    let (data, crc) = split(&frame);
+    assert!(!MavMessage::validate_crc(Heartbeat::ID, data, crc));
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "mavgen-test")]
+
+use mavgen_test::validation_messages::validation::{FieldError, FieldErrorKind, SetThrottle};
+
+#[test]
+fn test_validate_in_range() {
+    let message = SetThrottle {
+        throttle: 1000,
+        gains: [0.5, -1.5],
+        trim: -10,
+        count: u8::MAX,
+    };
+    assert_eq!(message.validate(), Ok(()));
+}
+
+#[test]
+fn test_validate_out_of_range() {
+    let message = SetThrottle {
+        throttle: 1001,
+        gains: [1.0, 0.25],
+        trim: -11,
+        count: 0,
+    };
+
+    assert_eq!(
+        message.validate(),
+        Err(vec![
+            FieldError {
+                field: "throttle",
+                index: None,
+                value: 1001.0,
+                kind: FieldErrorKind::AboveMax(1000.0),
+            },
+            FieldError {
+                field: "gains",
+                index: Some(1),
+                value: 0.25,
+                kind: FieldErrorKind::OffIncrement(0.5),
+            },
+            FieldError {
+                field: "trim",
+                index: None,
+                value: -11.0,
+                kind: FieldErrorKind::BelowMin(-10.0),
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_check_decimal_increment() {
+    assert_eq!(FieldErrorKind::check(0.3, None, None, Some(0.1)), None);
+    assert_eq!(
+        FieldErrorKind::check(0.35, None, None, Some(0.1)),
+        Some(FieldErrorKind::OffIncrement(0.1))
+    );
+}
@@ -0,0 +1,505 @@
+use std::collections::HashMap;
+
+use super::{naming, Codegen, PrimitiveTypeAsRust};
+use crate::model::{self, FieldType};
+
+/// How a change affects users of the generated code in terms of semver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Impact {
+    /// Only additions, existing code keeps compiling.
+    Minor,
+    /// Existing code may stop compiling.
+    Major,
+}
+
+impl std::fmt::Display for Impact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Impact::Minor => write!(f, "minor"),
+            Impact::Major => write!(f, "major"),
+        }
+    }
+}
+
+/// A change of the generated API, items are referred to by their Rust names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    AddedEnum(String),
+    RemovedEnum(String),
+    /// An enum became a bitmask or vice versa, or stopped being emitted as
+    /// a unit struct with [`Codegen::compact_single_entry_enums`].
+    ChangedEnumKind(String),
+    AddedEntry {
+        r#enum: String,
+        entry: String,
+    },
+    RemovedEntry {
+        r#enum: String,
+        entry: String,
+    },
+    ChangedEntryValue {
+        r#enum: String,
+        entry: String,
+        old: u64,
+        new: u64,
+    },
+    AddedMessage(String),
+    RemovedMessage(String),
+    AddedField {
+        message: String,
+        field: String,
+        r#type: String,
+    },
+    RemovedField {
+        message: String,
+        field: String,
+    },
+    ChangedFieldType {
+        message: String,
+        field: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::AddedEnum(name) => write!(f, "added enum `{name}`"),
+            ChangeKind::RemovedEnum(name) => write!(f, "removed enum `{name}`"),
+            ChangeKind::ChangedEnumKind(name) => {
+                write!(f, "enum `{name}` changed the kind of generated type")
+            }
+            ChangeKind::AddedEntry { r#enum, entry } => {
+                write!(f, "added entry `{enum}::{entry}`")
+            }
+            ChangeKind::RemovedEntry { r#enum, entry } => {
+                write!(f, "removed entry `{enum}::{entry}`")
+            }
+            ChangeKind::ChangedEntryValue {
+                r#enum,
+                entry,
+                old,
+                new,
+            } => write!(f, "changed value of `{enum}::{entry}` from {old} to {new}"),
+            ChangeKind::AddedMessage(name) => write!(f, "added message `{name}`"),
+            ChangeKind::RemovedMessage(name) => write!(f, "removed message `{name}`"),
+            ChangeKind::AddedField {
+                message,
+                field,
+                r#type,
+            } => write!(f, "added field `{message}::{field}: {type}`"),
+            ChangeKind::RemovedField { message, field } => {
+                write!(f, "removed field `{message}::{field}`")
+            }
+            ChangeKind::ChangedFieldType {
+                message,
+                field,
+                old,
+                new,
+            } => write!(
+                f,
+                "changed type of `{message}::{field}` from `{old}` to `{new}`"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiChange {
+    pub impact: Impact,
+    pub kind: ChangeKind,
+}
+
+impl std::fmt::Display for ApiChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.impact, self.kind)
+    }
+}
+
+/// Returns the largest impact of the changes, `None` if there are no changes.
+pub fn total_impact(changes: &[ApiChange]) -> Option<Impact> {
+    changes.iter().map(|change| change.impact).max()
+}
+
+impl Codegen {
+    /// Compares the code, which would be generated for two versions of the
+    /// same module.
+    ///
+    /// The impact depends on the options, e.g. new entries of
+    /// `#[non_exhaustive]` enums are not breaking.
+    pub fn api_changes(
+        &self,
+        old: &model::MavlinkModule,
+        new: &model::MavlinkModule,
+    ) -> Vec<ApiChange> {
+        let mut changes = Vec::new();
+        self.enum_changes(&old.enums, &new.enums, &mut changes);
+        self.message_changes(&old.messages, &new.messages, &mut changes);
+        changes
+    }
+
+    fn enum_changes(&self, old: &[model::Enum], new: &[model::Enum], changes: &mut Vec<ApiChange>) {
+        let old_enums: HashMap<_, _> = old.iter().map(|r#enum| (&r#enum.name, r#enum)).collect();
+
+        for new_enum in new {
+            let name = naming::pascal_case(new_enum.name.as_ref());
+            let Some(old_enum) = old_enums.get(&new_enum.name) else {
+                changes.push(minor(ChangeKind::AddedEnum(name)));
+                continue;
+            };
+
+            if old_enum.bitmask != new_enum.bitmask
+                || self.is_compact_enum(old_enum) != self.is_compact_enum(new_enum)
+            {
+                changes.push(major(ChangeKind::ChangedEnumKind(name)));
+                continue;
+            }
+
+            // Bitmasks and open enums have a constant per entry, so new ones
+            // don't break exhaustive matches
+            let added_entry_impact =
+                if new_enum.bitmask || self.open_enums || self.non_exhaustive_enums {
+                    Impact::Minor
+                } else {
+                    Impact::Major
+                };
+
+            let old_entries: HashMap<_, _> = old_enum
+                .entries
+                .iter()
+                .map(|entry| (&entry.name, entry))
+                .collect();
+            for new_entry in &new_enum.entries {
+                let entry = self.entry_ident(&new_entry.name).to_string();
+                match old_entries.get(&new_entry.name) {
+                    None => changes.push(ApiChange {
+                        impact: added_entry_impact,
+                        kind: ChangeKind::AddedEntry {
+                            r#enum: name.clone(),
+                            entry,
+                        },
+                    }),
+                    Some(old_entry) if old_entry.value != new_entry.value => {
+                        changes.push(major(ChangeKind::ChangedEntryValue {
+                            r#enum: name.clone(),
+                            entry,
+                            old: old_entry.value,
+                            new: new_entry.value,
+                        }))
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            for old_entry in &old_enum.entries {
+                if !new_enum
+                    .entries
+                    .iter()
+                    .any(|entry| entry.name == old_entry.name)
+                {
+                    changes.push(major(ChangeKind::RemovedEntry {
+                        r#enum: name.clone(),
+                        entry: self.entry_ident(&old_entry.name).to_string(),
+                    }));
+                }
+            }
+        }
+
+        for old_enum in old {
+            if !new.iter().any(|r#enum| r#enum.name == old_enum.name) {
+                let name = naming::pascal_case(old_enum.name.as_ref());
+                changes.push(major(ChangeKind::RemovedEnum(name)));
+            }
+        }
+    }
+
+    fn message_changes(
+        &self,
+        old: &[model::Message],
+        new: &[model::Message],
+        changes: &mut Vec<ApiChange>,
+    ) {
+        let old_messages: HashMap<_, _> =
+            old.iter().map(|message| (&message.name, message)).collect();
+
+        for new_message in new {
+            let name = naming::pascal_case(new_message.name.as_ref());
+            let Some(old_message) = old_messages.get(&new_message.name) else {
+                // Every message is a variant of `MavMessage`
+                let impact = if self.non_exhaustive_enums {
+                    Impact::Minor
+                } else {
+                    Impact::Major
+                };
+                changes.push(ApiChange {
+                    impact,
+                    kind: ChangeKind::AddedMessage(name),
+                });
+                continue;
+            };
+
+            let old_fields: HashMap<_, _> = old_message
+                .all_fields()
+                .map(|(field, _)| (&field.name, field))
+                .collect();
+
+            // Fields are public, so even a new extension field breaks struct
+            // literals
+            for (new_field, _) in new_message.all_fields() {
+                let field = self.field_ident(&new_field.name).to_string();
+                let new_type = field_type(new_field);
+                match old_fields.get(&new_field.name) {
+                    None => changes.push(major(ChangeKind::AddedField {
+                        message: name.clone(),
+                        field,
+                        r#type: new_type,
+                    })),
+                    Some(old_field) => {
+                        let old_type = field_type(old_field);
+                        if old_type != new_type {
+                            changes.push(major(ChangeKind::ChangedFieldType {
+                                message: name.clone(),
+                                field,
+                                old: old_type,
+                                new: new_type,
+                            }));
+                        }
+                    }
+                }
+            }
+
+            for (old_field, _) in old_message.all_fields() {
+                if !new_message
+                    .all_fields()
+                    .any(|(field, _)| field.name == old_field.name)
+                {
+                    changes.push(major(ChangeKind::RemovedField {
+                        message: name.clone(),
+                        field: self.field_ident(&old_field.name).to_string(),
+                    }));
+                }
+            }
+        }
+
+        for old_message in old {
+            if !new.iter().any(|message| message.name == old_message.name) {
+                let name = naming::pascal_case(old_message.name.as_ref());
+                changes.push(major(ChangeKind::RemovedMessage(name)));
+            }
+        }
+    }
+}
+
+fn minor(kind: ChangeKind) -> ApiChange {
+    ApiChange {
+        impact: Impact::Minor,
+        kind,
+    }
+}
+
+fn major(kind: ChangeKind) -> ApiChange {
+    ApiChange {
+        impact: Impact::Major,
+        kind,
+    }
+}
+
+/// Rust type of the field as it appears in the generated struct.
+fn field_type(field: &model::Field) -> String {
+    let element = match &field.r#enum {
+        Some(r#enum) => naming::pascal_case(r#enum.as_ref()),
+        None => PrimitiveTypeAsRust(field.r#type.primitive_type()).to_string(),
+    };
+
+    match field.r#type {
+        FieldType::Primitive(_) => element,
+        FieldType::Array(_, size) => format!("[{element}; {size}]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::model::{Entry, Enum, Field, Message, PrimitiveType};
+
+    fn field(name: &str, r#type: FieldType) -> Field {
+        Field {
+            name: name.parse().unwrap(),
+            r#type,
+            print_format: None,
+            r#enum: None,
+            display: None,
+            units: None,
+            increment: None,
+            min_value: None,
+            max_value: None,
+            multiplier: None,
+            default: None,
+            instance: None,
+            invalid: None,
+            description: None,
+        }
+    }
+
+    fn module(enums: Vec<Enum>, messages: Vec<Message>) -> model::MavlinkModule {
+        model::MavlinkModule {
+            path: "common.xml".into(),
+            version: None,
+            dialect: None,
+            enums,
+            messages,
+            reexports: vec![],
+        }
+    }
+
+    fn heartbeat(fields: Vec<Field>) -> Message {
+        Message {
+            name: "HEARTBEAT".parse().unwrap(),
+            id: 0,
+            dev_status: None,
+            description: None,
+            fields,
+            extension_fields: vec![],
+        }
+    }
+
+    fn mav_state(entries: &[(&str, u64)]) -> Enum {
+        Enum {
+            name: "MAV_STATE".parse().unwrap(),
+            bitmask: false,
+            description: None,
+            dev_status: None,
+            entries: entries
+                .iter()
+                .map(|(name, value)| Entry {
+                    name: name.parse().unwrap(),
+                    description: None,
+                    dev_status: None,
+                    value: *value,
+                    params: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_no_changes() {
+        let module = module(
+            vec![mav_state(&[("MAV_STATE_UNINIT", 0)])],
+            vec![heartbeat(vec![field(
+                "custom_mode",
+                FieldType::Primitive(PrimitiveType::Uint32),
+            )])],
+        );
+
+        let changes = Codegen::default().api_changes(&module, &module);
+        assert_eq!(changes, []);
+        assert_eq!(total_impact(&changes), None);
+    }
+
+    #[test]
+    fn test_added_field() {
+        let custom_mode = field("custom_mode", FieldType::Primitive(PrimitiveType::Uint32));
+        let old = module(vec![], vec![heartbeat(vec![custom_mode.clone()])]);
+        let new = module(
+            vec![],
+            vec![heartbeat(vec![
+                custom_mode,
+                field("systemStatus", FieldType::Array(PrimitiveType::Uint8, 4)),
+            ])],
+        );
+
+        let changes = Codegen::default().api_changes(&old, &new);
+        assert_eq!(
+            changes,
+            [ApiChange {
+                impact: Impact::Major,
+                kind: ChangeKind::AddedField {
+                    message: "Heartbeat".into(),
+                    field: "system_status".into(),
+                    r#type: "[u8; 4]".into(),
+                },
+            }]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "major: added field `Heartbeat::system_status: [u8; 4]`"
+        );
+    }
+
+    #[test]
+    fn test_changed_and_removed_items() {
+        let old = module(
+            vec![mav_state(&[("MAV_STATE_UNINIT", 0), ("MAV_STATE_BOOT", 1)])],
+            vec![heartbeat(vec![
+                field("custom_mode", FieldType::Primitive(PrimitiveType::Uint32)),
+                field("base_mode", FieldType::Primitive(PrimitiveType::Uint8)),
+            ])],
+        );
+        let new = module(
+            vec![mav_state(&[
+                ("MAV_STATE_UNINIT", 0),
+                ("MAV_STATE_ACTIVE", 4),
+            ])],
+            vec![heartbeat(vec![field(
+                "custom_mode",
+                FieldType::Primitive(PrimitiveType::Uint64),
+            )])],
+        );
+
+        let changes = Codegen::default().api_changes(&old, &new);
+        let changes: Vec<_> = changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            [
+                "major: added entry `MavState::MavStateActive`",
+                "major: removed entry `MavState::MavStateBoot`",
+                "major: changed type of `Heartbeat::custom_mode` from `u32` to `u64`",
+                "major: removed field `Heartbeat::base_mode`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_additions_with_non_exhaustive_enums() {
+        let old = module(vec![mav_state(&[("MAV_STATE_UNINIT", 0)])], vec![]);
+        let new = module(
+            vec![mav_state(&[("MAV_STATE_UNINIT", 0), ("MAV_STATE_BOOT", 1)])],
+            vec![heartbeat(vec![field(
+                "custom_mode",
+                FieldType::Primitive(PrimitiveType::Uint32),
+            )])],
+        );
+
+        let codegen = Codegen {
+            non_exhaustive_enums: true,
+            ..Codegen::default()
+        };
+        let changes = codegen.api_changes(&old, &new);
+        assert_eq!(total_impact(&changes), Some(Impact::Minor));
+        assert_eq!(changes.len(), 2);
+    }
+
+    #[test]
+    fn test_compact_enum_gaining_entry() {
+        let old = module(vec![mav_state(&[("MAV_STATE_UNINIT", 0)])], vec![]);
+        let new = module(
+            vec![mav_state(&[("MAV_STATE_UNINIT", 0), ("MAV_STATE_BOOT", 1)])],
+            vec![],
+        );
+
+        let codegen = Codegen {
+            non_exhaustive_enums: true,
+            compact_single_entry_enums: true,
+            ..Codegen::default()
+        };
+        let changes = codegen.api_changes(&old, &new);
+        assert_eq!(
+            changes,
+            [major(ChangeKind::ChangedEnumKind("MavState".into()))]
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::HashSet;
+
+use super::{naming::IdentExt, Codegen};
+use crate::model;
+
+/// Types imported by the prelude of every generated module.
+const IMPORTED_TYPES: &[&str] = &[
+    "Bytes",
+    "BytesMut",
+    "Deserialize",
+    "FromPrimitive",
+    "MavlinkVersion",
+    "Message",
+    "MessageData",
+    "ParserError",
+    "Serialize",
+    "ToPrimitive",
+];
+
+/// A generated item, which has the same name as another item in its scope,
+/// so the generated code wouldn't compile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Collision {
+    /// A message or an enum has the same Rust name as a type emitted or
+    /// imported by the generator.
+    Type { definition: String, name: String },
+    /// Two inherent methods of a message have the same name, e.g. string
+    /// accessors of two fields.
+    Method { message: String, name: String },
+}
+
+impl std::fmt::Display for Collision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Collision::Type { definition, name } => write!(
+                f,
+                "`{definition}` is generated as `{name}`, which is already used by the generated code"
+            ),
+            Collision::Method { message, name } => {
+                write!(f, "method `{name}` is generated twice for message `{message}`")
+            }
+        }
+    }
+}
+
+impl Codegen {
+    /// Finds names in the module, which collide with the items emitted by
+    /// [`Codegen::emit_module`].
+    pub fn find_collisions(&self, module: &model::MavlinkModule) -> Vec<Collision> {
+        let mut collisions = Vec::new();
+        self.type_collisions(module, &mut collisions);
+        for message in &module.messages {
+            self.method_collisions(message, &mut collisions);
+        }
+        collisions
+    }
+
+    fn type_collisions(&self, module: &model::MavlinkModule, collisions: &mut Vec<Collision>) {
+        let mut generated = vec![
+            self.mav_message_name(module).to_string(),
+            "MessageInfo".to_owned(),
+            "BufferTooSmall".to_owned(),
+            "InvalidEnumValue".to_owned(),
+        ];
+        if self.reflection {
+            generated.extend(["FieldValue", "ReflectError", "Reflect"].map(str::to_owned));
+        }
+        if self.dyn_messages {
+            generated.push("DynMessage".to_owned());
+        }
+        if self.field_metadata {
+            generated.push("FieldMeta".to_owned());
+        }
+        if self.enum_strings {
+            generated.push("UnknownEnumName".to_owned());
+        }
+        if self.borrowed_messages {
+            generated.extend(
+                module
+                    .messages
+                    .iter()
+                    .filter(|message| super::string_fields(message).next().is_some())
+                    .map(|message| format!("{}Ref", message.name.pascal_case())),
+            );
+        }
+        generated.extend(IMPORTED_TYPES.iter().map(|name| (*name).to_owned()));
+
+        let definitions = module
+            .enums
+            .iter()
+            .map(|r#enum| &r#enum.name)
+            .chain(module.messages.iter().map(|message| &message.name));
+
+        for definition in definitions {
+            let name = definition.pascal_case().to_string();
+            if generated.contains(&name) {
+                collisions.push(Collision::Type {
+                    definition: definition.to_string(),
+                    name,
+                });
+            }
+        }
+    }
+
+    fn method_collisions(&self, message: &model::Message, collisions: &mut Vec<Collision>) {
+        let mut methods = vec!["serialize_payload".to_owned()];
+        if message.name.as_ref() == "COMMAND_LONG" {
+            methods.push("labeled_params".to_owned());
+        }
+        for field in super::string_fields(message) {
+            let (getter, setter) = super::string_accessor_names(field);
+            methods.extend([getter.to_string(), setter.to_string()]);
+        }
+        for (field, _) in message.all_fields() {
+            if field.invalid_value().is_some() {
+                let (check, setter) = super::invalid_accessor_names(field);
+                methods.extend([check.to_string(), setter.to_string()]);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for name in methods {
+            if !seen.insert(name.clone()) {
+                collisions.push(Collision::Method {
+                    message: message.name.to_string(),
+                    name,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Field, FieldType, PrimitiveType};
+
+    fn field(name: &str, r#type: FieldType) -> Field {
+        Field {
+            name: name.parse().unwrap(),
+            r#type,
+            print_format: None,
+            r#enum: None,
+            display: None,
+            units: None,
+            increment: None,
+            min_value: None,
+            max_value: None,
+            multiplier: None,
+            default: None,
+            instance: None,
+            invalid: None,
+            description: None,
+        }
+    }
+
+    fn message(name: &str, fields: Vec<Field>) -> model::Message {
+        model::Message {
+            name: name.parse().unwrap(),
+            id: 0,
+            dev_status: None,
+            description: None,
+            fields,
+            extension_fields: vec![],
+        }
+    }
+
+    fn module(messages: Vec<model::Message>) -> model::MavlinkModule {
+        model::MavlinkModule {
+            path: "test.xml".into(),
+            version: None,
+            dialect: None,
+            enums: vec![],
+            messages,
+            reexports: vec![],
+        }
+    }
+
+    #[test]
+    fn test_no_collisions() {
+        let module = module(vec![message(
+            "HEARTBEAT",
+            vec![
+                // Fields don't share the namespace with methods
+                field(
+                    "serialize_payload",
+                    FieldType::Primitive(PrimitiveType::Uint8),
+                ),
+                field("text", FieldType::Array(PrimitiveType::Char, 10)),
+            ],
+        )]);
+
+        assert!(Codegen::default().find_collisions(&module).is_empty());
+    }
+
+    #[test]
+    fn test_method_collisions() {
+        let module = module(vec![message(
+            "STATUSTEXT",
+            vec![
+                field("a_as", FieldType::Array(PrimitiveType::Char, 10)),
+                field("set_a", FieldType::Array(PrimitiveType::Char, 10)),
+            ],
+        )]);
+
+        assert_eq!(
+            Codegen::default().find_collisions(&module),
+            [Collision::Method {
+                message: "STATUSTEXT".to_owned(),
+                name: "set_a_as_str".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_type_collisions() {
+        let module = module(vec![
+            message("MESSAGE_INFO", vec![]),
+            message("FIELD_VALUE", vec![]),
+            message("MESSAGE_DATA", vec![]),
+        ]);
+
+        let collisions = Codegen::default().find_collisions(&module);
+        assert_eq!(
+            collisions,
+            [
+                Collision::Type {
+                    definition: "MESSAGE_INFO".to_owned(),
+                    name: "MessageInfo".to_owned(),
+                },
+                Collision::Type {
+                    definition: "MESSAGE_DATA".to_owned(),
+                    name: "MessageData".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            collisions[0].to_string(),
+            "`MESSAGE_INFO` is generated as `MessageInfo`, which is already used by the generated code"
+        );
+
+        let codegen = Codegen {
+            reflection: true,
+            ..Default::default()
+        };
+        assert_eq!(codegen.find_collisions(&module).len(), 3);
+    }
+
+    #[test]
+    fn test_borrowed_message_collisions() {
+        let module = module(vec![
+            message(
+                "STATUSTEXT",
+                vec![field("text", FieldType::Array(PrimitiveType::Char, 10))],
+            ),
+            message("STATUSTEXT_REF", vec![]),
+        ]);
+
+        assert!(Codegen::default().find_collisions(&module).is_empty());
+
+        let codegen = Codegen {
+            borrowed_messages: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            codegen.find_collisions(&module),
+            [Collision::Type {
+                definition: "STATUSTEXT_REF".to_owned(),
+                name: "StatustextRef".to_owned(),
+            }]
+        );
+    }
+}
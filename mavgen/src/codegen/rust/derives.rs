@@ -0,0 +1,161 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use super::Codegen;
+
+/// Derives, which the generator emits itself or leaves out on purpose, e.g.
+/// `Clone` for messages above [`Codegen::clone_size_threshold`] or `Eq` for
+/// messages with float fields.
+const GENERATOR_DERIVES: &[&str] = &[
+    "Clone",
+    "Copy",
+    "Debug",
+    "Deserialize",
+    "Eq",
+    "PartialEq",
+    "Serialize",
+];
+
+/// A derive from [`Codegen::extra_derives`] or its per-kind lists, which
+/// can't be added to the generated types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidDerive {
+    /// Not a path like `Hash` or `my_crate::Validate`.
+    Syntax { derive: String },
+    /// Derived or not by the generator itself.
+    Generated { derive: String },
+}
+
+impl std::fmt::Display for InvalidDerive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidDerive::Syntax { derive } => write!(f, "derive `{derive}` isn't a valid path"),
+            InvalidDerive::Generated { derive } => write!(
+                f,
+                "derive `{derive}` is controlled by the generator and can't be added"
+            ),
+        }
+    }
+}
+
+/// Kind of a generated type, which selects the extra derives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DeriveTarget {
+    /// Message structs and the message enum.
+    Message,
+    /// Enums of the definition, including bitmasks.
+    Enum,
+}
+
+/// Name of the derive macro without the path, e.g. `Validate` for
+/// `my_crate::Validate`.
+fn derive_name(path: &syn::Path) -> Option<String> {
+    path.segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+}
+
+impl Codegen {
+    /// Finds the extra derives, which aren't paths or conflict with the
+    /// derives of the generator.
+    pub fn invalid_derives(&self) -> Vec<InvalidDerive> {
+        let derives = self
+            .extra_derives
+            .iter()
+            .chain(&self.extra_message_derives)
+            .chain(&self.extra_enum_derives);
+
+        derives
+            .filter_map(|derive| match syn::parse_str::<syn::Path>(derive) {
+                Err(_) => Some(InvalidDerive::Syntax {
+                    derive: derive.clone(),
+                }),
+                Ok(path)
+                    if derive_name(&path)
+                        .is_some_and(|name| GENERATOR_DERIVES.contains(&name.as_str())) =>
+                {
+                    Some(InvalidDerive::Generated {
+                        derive: derive.clone(),
+                    })
+                }
+                Ok(_) => None,
+            })
+            .collect()
+    }
+
+    /// Emits the extra derives of the target, except the ones in `derived`,
+    /// which the type already derives.
+    pub(super) fn emit_extra_derives(&self, target: DeriveTarget, derived: &[&str]) -> TokenStream {
+        let specific = match target {
+            DeriveTarget::Message => &self.extra_message_derives,
+            DeriveTarget::Enum => &self.extra_enum_derives,
+        };
+
+        let paths = self
+            .extra_derives
+            .iter()
+            .chain(specific)
+            .filter_map(|derive| syn::parse_str::<syn::Path>(derive).ok())
+            .filter(|path| derive_name(path).map_or(true, |name| !derived.contains(&name.as_str())))
+            .collect::<Vec<_>>();
+
+        if paths.is_empty() {
+            return TokenStream::new();
+        }
+
+        quote! { #[derive(#(#paths),*)] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_derives() {
+        let codegen = Codegen {
+            extra_derives: vec!["Hash".to_owned(), "my_crate::Validate".to_owned()],
+            extra_message_derives: vec!["not a path".to_owned()],
+            extra_enum_derives: vec!["core::clone::Clone".to_owned()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            codegen.invalid_derives(),
+            [
+                InvalidDerive::Syntax {
+                    derive: "not a path".to_owned()
+                },
+                InvalidDerive::Generated {
+                    derive: "core::clone::Clone".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_emit_extra_derives() {
+        let codegen = Codegen {
+            extra_derives: vec!["Hash".to_owned()],
+            extra_message_derives: vec!["my_crate::Validate".to_owned()],
+            extra_enum_derives: vec!["PartialOrd".to_owned()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            codegen
+                .emit_extra_derives(DeriveTarget::Message, &["Debug"])
+                .to_string(),
+            quote! { #[derive(Hash, my_crate::Validate)] }.to_string()
+        );
+        assert_eq!(
+            codegen
+                .emit_extra_derives(DeriveTarget::Enum, &["Hash"])
+                .to_string(),
+            quote! { #[derive(PartialOrd)] }.to_string()
+        );
+        assert!(Codegen::default()
+            .emit_extra_derives(DeriveTarget::Enum, &[])
+            .is_empty());
+    }
+}
@@ -0,0 +1,473 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use super::Codegen;
+use crate::model;
+
+impl Codegen {
+    /// Emits a self-contained `framing` module, which can parse and build
+    /// MAVLink v1/v2 frames without `mavlink-core`.
+    pub(super) fn emit_framing(&self, messages: &[model::Message]) -> TokenStream {
+        let extra_crc = self.emit_framing_extra_crc(messages);
+        let vec = self.collections().growable_vec();
+        let error = self.error_trait();
+
+        quote! {
+            /// Minimal MAVLink framing without external dependencies.
+            ///
+            /// The signature of v2 frames is passed through as is by parsing
+            /// and building, [`Frame::sign`] and [`Frame::verify`] handle it
+            /// with a user-provided [`Sha256`]. [`FrameLayout`] locates the
+            /// signed parts of a frame for other implementations of signing.
+            pub mod framing {
+                use core::ops::Range;
+
+                pub const MAV_STX_V1: u8 = 0xFE;
+                pub const MAV_STX_V2: u8 = 0xFD;
+                /// Incompatibility flag of v2 frames, which are followed by a signature.
+                pub const MAVLINK_IFLAG_SIGNED: u8 = 0x01;
+                /// Length of the signature: link ID, 48-bit timestamp and
+                /// 48-bit truncated SHA-256 hash.
+                pub const SIGNATURE_LEN: usize = 13;
+
+                const HEADER_LEN_V1: usize = 6;
+                const HEADER_LEN_V2: usize = 10;
+                const CHECKSUM_LEN: usize = 2;
+
+                #extra_crc
+
+                /// Accumulates one byte into the X.25 (CRC-16/MCRF4XX) checksum.
+                pub fn crc_accumulate(crc: u16, byte: u8) -> u16 {
+                    let tmp = byte ^ (crc as u8);
+                    let tmp = tmp ^ (tmp << 4);
+                    let tmp = u16::from(tmp);
+                    (crc >> 8) ^ (tmp << 8) ^ (tmp << 3) ^ (tmp >> 4)
+                }
+
+                /// Calculates the X.25 (CRC-16/MCRF4XX) checksum of the data.
+                pub fn crc_calculate(data: &[u8]) -> u16 {
+                    data.iter().fold(0xFFFF, |crc, byte| crc_accumulate(crc, *byte))
+                }
+
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                pub enum Version {
+                    V1,
+                    V2,
+                }
+
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                pub enum FrameError {
+                    /// Not enough bytes for the whole frame.
+                    Incomplete,
+                    InvalidMagic(u8),
+                    UnknownMessage(u32),
+                    /// The message ID doesn't fit into a v1 frame.
+                    InvalidMessageId(u32),
+                    PayloadTooLarge(usize),
+                    InvalidChecksum { expected: u16, received: u16 },
+                    /// No magic byte within the scan window of [`ResyncPolicy`],
+                    /// the scanned bytes were discarded.
+                    ResyncLimit(usize),
+                    /// Only v2 frames can be signed.
+                    UnsignableVersion,
+                }
+
+                impl core::fmt::Display for FrameError {
+                    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        match self {
+                            Self::Incomplete => write!(f, "incomplete frame"),
+                            Self::InvalidMagic(magic) => write!(f, "invalid magic byte {magic:#04x}"),
+                            Self::UnknownMessage(id) => write!(f, "unknown message {id}"),
+                            Self::InvalidMessageId(id) => {
+                                write!(f, "message ID {id} doesn't fit into a v1 frame")
+                            }
+                            Self::PayloadTooLarge(len) => write!(f, "payload of {len} bytes is too large"),
+                            Self::InvalidChecksum { expected, received } => write!(
+                                f,
+                                "invalid checksum {received:#06x}, expected {expected:#06x}",
+                            ),
+                            Self::ResyncLimit(len) => write!(f, "no magic byte in {len} bytes"),
+                            Self::UnsignableVersion => write!(f, "only v2 frames can be signed"),
+                        }
+                    }
+                }
+
+                impl #error for FrameError {}
+
+                /// SHA-256 of the signature. The framing doesn't depend on a
+                /// hash implementation, so it's provided by the user, e.g.
+                /// with the `sha2` crate:
+                ///
+                /// ```ignore
+                /// let sha256 = |parts: &[&[u8]]| {
+                ///     let mut hasher = sha2::Sha256::new();
+                ///     parts.iter().for_each(|part| hasher.update(part));
+                ///     hasher.finalize().into()
+                /// };
+                /// ```
+                pub trait Sha256 {
+                    /// Returns the hash of the concatenated parts.
+                    fn sha256(&self, parts: &[&[u8]]) -> [u8; 32];
+                }
+
+                impl<F: Fn(&[&[u8]]) -> [u8; 32]> Sha256 for F {
+                    fn sha256(&self, parts: &[&[u8]]) -> [u8; 32] {
+                        self(parts)
+                    }
+                }
+
+                /// Byte ranges of the parts of a frame.
+                #[derive(Debug, Clone, PartialEq, Eq)]
+                pub struct FrameLayout {
+                    pub version: Version,
+                    pub header: Range<usize>,
+                    pub payload: Range<usize>,
+                    pub checksum: Range<usize>,
+                    /// Range of the signature for signed v2 frames. The hash
+                    /// of the signature covers all bytes before the hash,
+                    /// including the link ID and the timestamp.
+                    pub signature: Option<Range<usize>>,
+                }
+
+                impl FrameLayout {
+                    /// Locates the parts of the frame at the beginning of the
+                    /// buffer, only the header has to be in the buffer.
+                    pub fn locate(buf: &[u8]) -> Result<Self, FrameError> {
+                        let magic = *buf.first().ok_or(FrameError::Incomplete)?;
+                        let (version, header_len) = match magic {
+                            MAV_STX_V1 => (Version::V1, HEADER_LEN_V1),
+                            MAV_STX_V2 => (Version::V2, HEADER_LEN_V2),
+                            magic => return Err(FrameError::InvalidMagic(magic)),
+                        };
+                        if buf.len() < header_len {
+                            return Err(FrameError::Incomplete);
+                        }
+
+                        let payload_end = header_len + usize::from(buf[1]);
+                        let checksum_end = payload_end + CHECKSUM_LEN;
+                        let signed = version == Version::V2 && buf[2] & MAVLINK_IFLAG_SIGNED != 0;
+
+                        Ok(Self {
+                            version,
+                            header: 0..header_len,
+                            payload: header_len..payload_end,
+                            checksum: payload_end..checksum_end,
+                            signature: signed.then(|| checksum_end..checksum_end + SIGNATURE_LEN),
+                        })
+                    }
+
+                    /// Length of the whole frame.
+                    pub fn frame_len(&self) -> usize {
+                        match &self.signature {
+                            Some(signature) => signature.end,
+                            None => self.checksum.end,
+                        }
+                    }
+                }
+
+                #[derive(Debug, Clone, PartialEq, Eq)]
+                pub struct Frame {
+                    pub version: Version,
+                    pub incompat_flags: u8,
+                    pub compat_flags: u8,
+                    pub sequence: u8,
+                    pub system_id: u8,
+                    pub component_id: u8,
+                    pub message_id: u32,
+                    pub payload: #vec<u8>,
+                    pub signature: Option<[u8; SIGNATURE_LEN]>,
+                }
+
+                impl Frame {
+                    /// Parses a frame from the beginning of the buffer, returns
+                    /// the frame and the number of consumed bytes.
+                    pub fn parse(buf: &[u8]) -> Result<(Self, usize), FrameError> {
+                        let layout = FrameLayout::locate(buf)?;
+                        let version = layout.version;
+                        let (incompat_flags, compat_flags, sequence, system_id, component_id, message_id) =
+                            match version {
+                                Version::V1 => (0, 0, buf[2], buf[3], buf[4], u32::from(buf[5])),
+                                Version::V2 => (
+                                    buf[2],
+                                    buf[3],
+                                    buf[4],
+                                    buf[5],
+                                    buf[6],
+                                    u32::from_le_bytes([buf[7], buf[8], buf[9], 0]),
+                                ),
+                            };
+
+                        let frame_len = layout.frame_len();
+                        if buf.len() < frame_len {
+                            return Err(FrameError::Incomplete);
+                        }
+
+                        let extra_crc =
+                            extra_crc(message_id).ok_or(FrameError::UnknownMessage(message_id))?;
+                        let expected =
+                            crc_accumulate(crc_calculate(&buf[1..layout.payload.end]), extra_crc);
+                        let checksum_at = layout.checksum.start;
+                        let received = u16::from_le_bytes([buf[checksum_at], buf[checksum_at + 1]]);
+                        if expected != received {
+                            return Err(FrameError::InvalidChecksum { expected, received });
+                        }
+
+                        let signature = layout.signature.map(|range| {
+                            let mut signature = [0; SIGNATURE_LEN];
+                            signature.copy_from_slice(&buf[range]);
+                            signature
+                        });
+
+                        let frame = Self {
+                            version,
+                            incompat_flags,
+                            compat_flags,
+                            sequence,
+                            system_id,
+                            component_id,
+                            message_id,
+                            payload: buf[layout.payload].to_vec(),
+                            signature,
+                        };
+
+                        Ok((frame, frame_len))
+                    }
+
+                    /// Serialises the frame, calculating the checksum.
+                    ///
+                    /// The signed flag is set for v2 frames with a signature.
+                    pub fn build(&self) -> Result<#vec<u8>, FrameError> {
+                        let extra_crc =
+                            extra_crc(self.message_id).ok_or(FrameError::UnknownMessage(self.message_id))?;
+                        let payload_len = u8::try_from(self.payload.len())
+                            .map_err(|_| FrameError::PayloadTooLarge(self.payload.len()))?;
+
+                        let mut buf = #vec::with_capacity(
+                            HEADER_LEN_V2 + self.payload.len() + CHECKSUM_LEN + SIGNATURE_LEN,
+                        );
+
+                        match self.version {
+                            Version::V1 => {
+                                let message_id = u8::try_from(self.message_id)
+                                    .map_err(|_| FrameError::InvalidMessageId(self.message_id))?;
+                                buf.extend_from_slice(&[
+                                    MAV_STX_V1,
+                                    payload_len,
+                                    self.sequence,
+                                    self.system_id,
+                                    self.component_id,
+                                    message_id,
+                                ]);
+                            }
+                            Version::V2 => {
+                                if self.message_id > 0xFF_FFFF {
+                                    return Err(FrameError::InvalidMessageId(self.message_id));
+                                }
+                                let incompat_flags = if self.signature.is_some() {
+                                    self.incompat_flags | MAVLINK_IFLAG_SIGNED
+                                } else {
+                                    self.incompat_flags & !MAVLINK_IFLAG_SIGNED
+                                };
+                                let message_id = self.message_id.to_le_bytes();
+                                buf.extend_from_slice(&[
+                                    MAV_STX_V2,
+                                    payload_len,
+                                    incompat_flags,
+                                    self.compat_flags,
+                                    self.sequence,
+                                    self.system_id,
+                                    self.component_id,
+                                    message_id[0],
+                                    message_id[1],
+                                    message_id[2],
+                                ]);
+                            }
+                        }
+
+                        buf.extend_from_slice(&self.payload);
+                        let checksum = crc_accumulate(crc_calculate(&buf[1..]), extra_crc);
+                        buf.extend_from_slice(&checksum.to_le_bytes());
+
+                        if let (Version::V2, Some(signature)) = (self.version, &self.signature) {
+                            buf.extend_from_slice(signature);
+                        }
+
+                        Ok(buf)
+                    }
+
+                    /// Signs the v2 frame with the secret key for the link.
+                    /// `timestamp` is in 10 µs units since 2015-01-01 and has
+                    /// to increase for every frame of the link.
+                    pub fn sign(
+                        &mut self,
+                        secret_key: &[u8; 32],
+                        link_id: u8,
+                        timestamp: u64,
+                        sha256: impl Sha256,
+                    ) -> Result<(), FrameError> {
+                        if self.version != Version::V2 {
+                            return Err(FrameError::UnsignableVersion);
+                        }
+
+                        let mut signature = [0; SIGNATURE_LEN];
+                        signature[0] = link_id;
+                        signature[1..7].copy_from_slice(&timestamp.to_le_bytes()[..6]);
+                        self.signature = Some(signature);
+
+                        let hash = self.signature_hash(secret_key, sha256)?;
+                        signature[7..].copy_from_slice(&hash);
+                        self.signature = Some(signature);
+                        Ok(())
+                    }
+
+                    /// Verifies the signature of the frame with the secret key,
+                    /// unsigned frames are never valid. Checking the timestamp
+                    /// against replays is up to the caller.
+                    pub fn verify(
+                        &self,
+                        secret_key: &[u8; 32],
+                        sha256: impl Sha256,
+                    ) -> Result<bool, FrameError> {
+                        let Some(signature) = &self.signature else {
+                            return Ok(false);
+                        };
+                        if self.version != Version::V2 {
+                            return Ok(false);
+                        }
+
+                        let hash = self.signature_hash(secret_key, sha256)?;
+                        Ok(signature[7..] == hash)
+                    }
+
+                    /// Link ID and timestamp of the signature.
+                    pub fn signature_link(&self) -> Option<(u8, u64)> {
+                        self.signature.map(|signature| {
+                            let mut timestamp = [0; 8];
+                            timestamp[..6].copy_from_slice(&signature[1..7]);
+                            (signature[0], u64::from_le_bytes(timestamp))
+                        })
+                    }
+
+                    /// The truncated hash over the key, the frame and the link
+                    /// ID with the timestamp of the signature.
+                    fn signature_hash(
+                        &self,
+                        secret_key: &[u8; 32],
+                        sha256: impl Sha256,
+                    ) -> Result<[u8; 6], FrameError> {
+                        let buf = self.build()?;
+                        let signed = &buf[..buf.len() - 6];
+                        let hash = sha256.sha256(&[&secret_key[..], signed]);
+
+                        let mut truncated = [0; 6];
+                        truncated.copy_from_slice(&hash[..6]);
+                        Ok(truncated)
+                    }
+                }
+
+                /// How [`FrameReader`] recovers from bytes, which don't start a frame.
+                #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+                pub struct ResyncPolicy {
+                    /// Maximum number of bytes scanned for a magic byte in one
+                    /// [`FrameReader::next_frame`] call, `None` for no limit.
+                    pub max_scan: Option<usize>,
+                }
+
+                /// Reads frames from a stream of bytes, e.g. a serial line.
+                ///
+                /// Bytes, which don't start a valid frame, are discarded until
+                /// the next magic byte, see [`ResyncPolicy`].
+                #[derive(Debug, Clone, Default)]
+                pub struct FrameReader {
+                    buf: #vec<u8>,
+                    policy: ResyncPolicy,
+                    discarded: usize,
+                }
+
+                impl FrameReader {
+                    pub fn new() -> Self {
+                        Self::default()
+                    }
+
+                    pub fn with_policy(policy: ResyncPolicy) -> Self {
+                        Self {
+                            policy,
+                            ..Self::default()
+                        }
+                    }
+
+                    /// Appends received bytes to the internal buffer.
+                    pub fn push(&mut self, data: &[u8]) {
+                        self.buf.extend_from_slice(data);
+                    }
+
+                    /// Total number of bytes discarded while looking for frames.
+                    pub fn discarded(&self) -> usize {
+                        self.discarded
+                    }
+
+                    /// Returns the next frame, or `None` if more bytes are needed.
+                    ///
+                    /// After an error the offending bytes are already skipped,
+                    /// so the reading can continue.
+                    pub fn next_frame(&mut self) -> Option<Result<Frame, FrameError>> {
+                        let window = self.policy.max_scan.map_or(self.buf.len(), |max_scan| {
+                            max_scan.min(self.buf.len())
+                        });
+                        let start = self.buf[..window]
+                            .iter()
+                            .position(|byte| matches!(*byte, MAV_STX_V1 | MAV_STX_V2));
+
+                        let Some(start) = start else {
+                            self.discard(window);
+                            if window != 0 && self.policy.max_scan == Some(window) {
+                                return Some(Err(FrameError::ResyncLimit(window)));
+                            }
+                            return None;
+                        };
+                        self.discard(start);
+
+                        match Frame::parse(&self.buf) {
+                            Ok((frame, len)) => {
+                                self.buf.drain(..len);
+                                Some(Ok(frame))
+                            }
+                            Err(FrameError::Incomplete) => None,
+                            Err(err) => {
+                                // Either a corrupted frame or a magic byte inside
+                                // the noise, continue from the next byte
+                                self.discard(1);
+                                Some(Err(err))
+                            }
+                        }
+                    }
+
+                    fn discard(&mut self, len: usize) {
+                        self.buf.drain(..len);
+                        self.discarded += len;
+                    }
+                }
+            }
+        }
+    }
+
+    pub(super) fn emit_framing_extra_crc(&self, messages: &[model::Message]) -> TokenStream {
+        let messages = messages.iter().map(|message| {
+            let id = message.id;
+            let extra_crc = message.extra_crc();
+            quote! {
+                #id => Some(#extra_crc)
+            }
+        });
+
+        quote! {
+            /// Returns CRC_EXTRA of the message with the given ID.
+            pub const fn extra_crc(id: u32) -> Option<u8> {
+                match id {
+                    #(#messages,)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
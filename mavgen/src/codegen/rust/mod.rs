@@ -1,11 +1,26 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
 use proc_macro2::{Literal, TokenStream};
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, ToTokens};
 
 use crate::model::{self, FieldType, PrimitiveType};
 use naming::IdentExt;
 
+pub use collisions::Collision;
+pub use derives::InvalidDerive;
+
+use derives::DeriveTarget;
+
+pub mod api_diff;
+mod collisions;
+mod derives;
+mod framing;
 pub mod naming;
 
+/// Rust type of a primitive type.
+///
+/// `char` is a single byte on the wire, so it's `u8` both as a scalar and as
+/// an element of an array, strings are accessed with the `_as_str` helpers.
 struct PrimitiveTypeAsRust(PrimitiveType);
 
 impl std::fmt::Display for PrimitiveTypeAsRust {
@@ -41,6 +56,67 @@ fn field_type_as_rust(field_type: model::FieldType) -> TokenStream {
     }
 }
 
+/// Type of the field in the message struct, taking its enum into account.
+fn field_as_rust(field: &model::Field) -> TokenStream {
+    match (&field.r#enum, field.r#type) {
+        (Some(r#enum), FieldType::Array(_, size)) => {
+            let typ = r#enum.pascal_case();
+            let size = usize::from(size);
+            quote! { [#typ; #size] }
+        }
+        (Some(r#enum), FieldType::Primitive(_)) => {
+            let typ = r#enum.pascal_case();
+            quote! { #typ }
+        }
+        (None, typ) => field_type_as_rust(typ),
+    }
+}
+
+/// Converts the `default` attribute of a field into a value of one element.
+fn default_value_as_rust(
+    default: &model::DefaultValue,
+    typ: PrimitiveType,
+    r#enum: Option<&model::Ident>,
+    preserve_names: bool,
+) -> TokenStream {
+    match default {
+        model::DefaultValue::Int(value) => {
+            let value = Literal::i128_unsuffixed(*value);
+            match r#enum {
+                // Only bitmasks have integer defaults, which combine the flags
+                Some(r#enum) => {
+                    let enm = r#enum.pascal_case();
+                    quote!(#enm::from_bits_retain(#value))
+                }
+                None => quote!(#value),
+            }
+        }
+        model::DefaultValue::Float(value) => {
+            let float = match typ {
+                PrimitiveType::Float => quote!(f32),
+                _ => quote!(f64),
+            };
+            if value.is_nan() {
+                quote!(#float::NAN)
+            } else if *value == f64::INFINITY {
+                quote!(#float::INFINITY)
+            } else if *value == f64::NEG_INFINITY {
+                quote!(#float::NEG_INFINITY)
+            } else {
+                let value = Literal::f64_unsuffixed(*value);
+                quote!(#value)
+            }
+        }
+        model::DefaultValue::Entry(entry) => {
+            let enm = r#enum
+                .expect("entry defaults are only allowed for enum fields")
+                .pascal_case();
+            let entry = naming::entry_ident(entry, preserve_names);
+            quote!(#enm::#entry)
+        }
+    }
+}
+
 fn field_type_default_value(field_type: FieldType) -> TokenStream {
     fn default_value(typ: PrimitiveType) -> TokenStream {
         match typ {
@@ -79,37 +155,667 @@ fn rust_size_type(typ: model::RustSizeType) -> syn::Ident {
     syn::Ident::new(literal, proc_macro2::Span::call_site())
 }
 
-fn remove_line_leading_whitespaces(s: &str) -> String {
-    let mut output = String::with_capacity(s.len());
-    for line in s.lines() {
-        let line = line.trim_start();
-        output.push_str(line);
-        output.push('\n');
+/// Width, after which lines of doc comments are wrapped.
+const DOC_LINE_WIDTH: usize = 100;
+
+/// Turns a description into the lines of a doc comment.
+///
+/// Whitespace is collapsed, as Markdown treats lines indented by 4 spaces,
+/// which are common in formatted XML, as code blocks. Brackets, which aren't
+/// links, are escaped, so rustdoc doesn't try to resolve them, and so is an
+/// unpaired backtick. Long lines are wrapped, except for tables.
+fn doc_lines(desc: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in escape_markdown(desc).lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        let previous_is_blank = lines.last().map(String::is_empty).unwrap_or(true);
+        if line.is_empty() && previous_is_blank {
+            continue;
+        }
+
+        if line.len() <= DOC_LINE_WIDTH || line.starts_with('|') {
+            lines.push(line);
+            continue;
+        }
+
+        let mut wrapped = String::new();
+        for word in line.split(' ') {
+            if !wrapped.is_empty() && wrapped.len() + 1 + word.len() > DOC_LINE_WIDTH {
+                lines.push(std::mem::take(&mut wrapped));
+            }
+            if !wrapped.is_empty() {
+                wrapped.push(' ');
+            }
+            wrapped.push_str(word);
+        }
+        lines.push(wrapped);
+    }
+
+    if lines.last().is_some_and(String::is_empty) {
+        lines.pop();
     }
-    output
+    lines
 }
 
-#[derive(Debug, Default)]
-pub struct Codegen {}
+/// Escapes `[` and `]` outside of code spans, unless they form a
+/// `[text](url)` link, and the last backtick if they aren't paired.
+fn escape_markdown(desc: &str) -> String {
+    let chars: Vec<char> = desc.chars().collect();
+    let backticks = chars.iter().filter(|c| **c == '`').count();
+    let unpaired_backtick = if backticks % 2 == 1 {
+        chars.iter().rposition(|c| *c == '`')
+    } else {
+        None
+    };
+
+    let mut escaped = String::with_capacity(desc.len());
+    let mut in_code = false;
+    let mut link_end = None;
+
+    for (i, &c) in chars.iter().enumerate() {
+        let is_escaped = i > 0 && chars[i - 1] == '\\';
+        match c {
+            '`' if Some(i) == unpaired_backtick => escaped.push_str("\\`"),
+            '`' => {
+                in_code = !in_code;
+                escaped.push(c);
+            }
+            '[' if !in_code && !is_escaped && link_end.is_none() => {
+                let close = chars[i..]
+                    .iter()
+                    .position(|c| *c == ']')
+                    .map(|offset| i + offset);
+                match close {
+                    Some(close) if chars.get(close + 1) == Some(&'(') => {
+                        link_end = Some(close);
+                        escaped.push(c);
+                    }
+                    _ => escaped.push_str("\\["),
+                }
+            }
+            ']' if Some(i) == link_end => {
+                link_end = None;
+                escaped.push(c);
+            }
+            ']' if !in_code && !is_escaped => escaped.push_str("\\]"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Collections used by the generated code for variable-sized data, like the
+/// values of [`Codegen::reflection`] or the payload of framing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Collections {
+    /// `Vec`, `String` and `Cow` from `std`.
+    #[default]
+    Std,
+    /// The same collections from `alloc`, for `no_std` crates with an
+    /// allocator. The crate has to declare `extern crate alloc`.
+    Alloc,
+    /// `heapless` collections with the capacity of the largest possible
+    /// value, the crate has to depend on `heapless`. String fields are
+    /// returned as `&str` up to the first invalid UTF-8 byte.
+    ///
+    /// The framing module needs growable buffers and uses `alloc` instead.
+    Heapless,
+}
+
+impl Collections {
+    /// Path of a vector with at most `capacity` items.
+    fn vec(self, item: TokenStream, capacity: usize) -> TokenStream {
+        match self {
+            Collections::Std => quote!(Vec<#item>),
+            Collections::Alloc => quote!(alloc::vec::Vec<#item>),
+            Collections::Heapless => quote!(heapless::Vec<#item, #capacity>),
+        }
+    }
+
+    /// Path of a string with at most `capacity` bytes.
+    fn string(self, capacity: usize) -> TokenStream {
+        match self {
+            Collections::Std => quote!(String),
+            Collections::Alloc => quote!(alloc::string::String),
+            Collections::Heapless => quote!(heapless::String<#capacity>),
+        }
+    }
+
+    /// Path of a growable vector, `alloc` is used for `heapless`.
+    fn growable_vec(self) -> TokenStream {
+        match self {
+            Collections::Std => quote!(Vec),
+            Collections::Alloc | Collections::Heapless => quote!(alloc::vec::Vec),
+        }
+    }
+}
+
+/// How the serialisation functions are marked for inlining.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inline {
+    /// `#[inline]`
+    Hint,
+    /// `#[inline(always)]`
+    Always,
+}
+
+/// How much of the field definition goes into its doc comment.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DocStyle {
+    /// Only the description.
+    #[default]
+    Terse,
+    /// The description followed by a list of the units, the range, the
+    /// increment, the multiplier, the default and the invalid values.
+    Full,
+}
+
+/// Rust edition of the crate, which includes the generated code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Edition {
+    /// Imports `TryFrom` and `TryInto`, which are only in the prelude since
+    /// 2021.
+    Edition2018,
+    #[default]
+    Edition2021,
+}
+
+/// The largest length of array fields.
+const MAX_ARRAY_LEN: usize = u8::MAX as usize;
+
+#[derive(Debug, Default, Clone)]
+pub struct Codegen {
+    /// Emit a `Reflect` trait with field access by name and implement it for
+    /// every message.
+    pub reflection: bool,
+    /// Emit `field_metadata` for every message, which returns the units,
+    /// the increment, the range and the multiplier of a field as
+    /// `FieldMeta`.
+    pub field_metadata: bool,
+    /// Emit `validate` for every message, which checks the numeric fields
+    /// against `minValue`, `maxValue` and `increment` of the definition and
+    /// returns the violations as `FieldError`s.
+    ///
+    /// Fields without any of them, enums and chars aren't checked.
+    pub validation: bool,
+    /// Emit `all_variants()` with every entry of an enum, or every named bit
+    /// of a bitmask, and `name()` with the name of the entry in the
+    /// definition.
+    pub enum_variants: bool,
+    /// Implement `FromStr` and `Display` for regular enums with the names of
+    /// the entries in the definition, e.g. `MAV_STATE_ACTIVE`.
+    pub enum_strings: bool,
+    /// Emit a `{Message}Builder` for every message with a `with_{field}`
+    /// setter per field, which starts from the default of the message.
+    pub message_builders: bool,
+    /// Keep the names of enum entries and message fields from the definition,
+    /// e.g. `MAV_STATE_ACTIVE` instead of `MavStateActive`, so that `Debug`
+    /// output matches pymavlink.
+    ///
+    /// Keywords are still escaped, e.g. `r#type`.
+    pub preserve_names: bool,
+    /// Gate messages with the `<wip>` status behind the `wip` feature of the
+    /// generated crate, so that they don't appear in stable builds.
+    ///
+    /// WIP enums are gated as well, unless a stable message uses them.
+    pub strict_wip: bool,
+    /// Gate every message behind a feature of the generated crate named
+    /// after it, e.g. `HEARTBEAT`, to build only the used messages. Enums are
+    /// gated with the messages using them.
+    ///
+    /// The features have to be declared by the crate, see
+    /// [`Self::message_features`]. [`crate::generate_dir_with_options`]
+    /// writes them to `{module}.features.toml` next to the module, unless
+    /// the modules are nested or deduplicated.
+    pub per_message_features: bool,
+    /// Paths of derives added to the messages, the message enum and the
+    /// enums, e.g. `Hash` or `my_crate::Validate`.
+    ///
+    /// Derives, which a type already has, are skipped, while the ones
+    /// controlled by the generator are rejected, see
+    /// [`Self::invalid_derives`].
+    pub extra_derives: Vec<String>,
+    /// Same as [`Self::extra_derives`], but only for the messages and the
+    /// message enum.
+    pub extra_message_derives: Vec<String>,
+    /// Same as [`Self::extra_derives`], but only for the enums.
+    pub extra_enum_derives: Vec<String>,
+    /// Emit a dependency-free `framing` module for parsing and building
+    /// MAVLink v1/v2 frames.
+    pub emit_framing: bool,
+    /// Name of the enum with all messages instead of `MavMessage`.
+    ///
+    /// `{module}` is replaced with the PascalCase name of the definition
+    /// file, e.g. `{module}Message` becomes `CommonMessage` for `common.xml`.
+    pub mav_message_name: Option<String>,
+    /// Emit regular enums as newtypes over their integer with a constant per
+    /// entry, so that unknown values are kept instead of failing to parse.
+    pub open_enums: bool,
+    /// Mark regular enums and `MavMessage` as `#[non_exhaustive]`, so that
+    /// new entries and messages are not breaking changes for users.
+    pub non_exhaustive_enums: bool,
+    /// Emit regular enums with a single entry as a unit struct with a
+    /// constant for the entry instead of a Rust enum.
+    ///
+    /// Doesn't apply to bitmasks and open enums.
+    pub compact_single_entry_enums: bool,
+    /// Emit an object-safe `DynMessage` trait implemented by all messages,
+    /// e.g. to store them as `Box<dyn DynMessage>`.
+    ///
+    /// With [`Self::no_std`] the serialisation requires `alloc`.
+    pub dyn_messages: bool,
+    /// Rename entries of regular enums with
+    /// `#[serde(rename_all = "snake_case")]`.
+    pub serde_snake_case_enums: bool,
+    /// Names of enums and messages from the definition, which don't derive
+    /// serde traits, e.g. to implement them manually with a custom
+    /// representation.
+    pub serde_skip: Vec<String>,
+    /// Derive `defmt::Format` for messages and enums behind the `defmt`
+    /// feature of the generated crate, to log them on embedded targets.
+    ///
+    /// Bitmask enums get a manual impl printing their bits, as the internal
+    /// type of `bitflags` doesn't implement `defmt::Format`.
+    pub defmt: bool,
+    /// Derive `arbitrary::Arbitrary` for messages and enums behind the
+    /// `arbitrary` feature of the generated crate, e.g. for fuzzing.
+    ///
+    /// Enums only produce their entries, so arbitrary messages can be
+    /// serialised and parsed back. Bitmask enums require the `arbitrary`
+    /// feature of `bitflags`.
+    pub arbitrary: bool,
+    /// Emit `fmt_telemetry` for every message, which renders the fields as
+    /// `name: value units` lines for human-readable dumps.
+    ///
+    /// Ignored with [`Self::no_std`], as it returns a `String`.
+    pub telemetry_formatter: bool,
+    /// Don't derive `Clone` and `Copy` for messages with a payload larger
+    /// than this number of bytes, so that they aren't copied by accident.
+    ///
+    /// The message enum derives `Clone` only if all messages do.
+    pub clone_size_threshold: Option<usize>,
+    /// Emit a `{Message}Ref<'a>` for every message with string fields, which
+    /// borrows them from the payload instead of copying, and converts into
+    /// the owned message with `to_owned`.
+    pub borrowed_messages: bool,
+    /// Name of the enum with syslog-like severities, e.g. `MAV_SEVERITY`,
+    /// which gets a conversion into `log::Level` behind the `log` feature of
+    /// the generated crate.
+    ///
+    /// Received `STATUSTEXT` messages can then be forwarded to the logger:
+    ///
+    /// ```ignore
+    /// fn handle_statustext(message: &Statustext) {
+    ///     log::log!(log::Level::from(message.severity), "{}", message.text_as_str());
+    /// }
+    /// ```
+    pub log_severity_enum: Option<String>,
+    /// Mark serialisation and deserialisation of messages for inlining,
+    /// which speeds up tight encoding loops at the cost of the code size.
+    pub inline: Option<Inline>,
+    /// Edition of the crate, which includes the generated code.
+    pub edition: Edition,
+    /// Layout of the doc comments of message fields.
+    pub doc_style: DocStyle,
+    /// Collections used by the generated code.
+    pub collections: Collections,
+    /// Emit code, which only uses `core` and can be included into a
+    /// `#![no_std]` crate. The crate itself has to declare `#![no_std]` and
+    /// depend on `mavlink-core` without default features.
+    ///
+    /// [`Collections::Std`] is replaced with [`Collections::Heapless`], so
+    /// string fields are returned as `&str`. The framing module needs
+    /// `alloc`, while `serde` support is kept as is and requires `serde`,
+    /// `serde_arrays` and `bitflags` without their `std` features.
+    pub no_std: bool,
+}
+
+/// Placeholder of the module name in [`Codegen::mav_message_name`].
+pub const MODULE_PLACEHOLDER: &str = "{module}";
 
 impl Codegen {
+    /// Collections used by the generated code, taking [`Self::no_std`] into
+    /// account.
+    pub fn collections(&self) -> Collections {
+        match self.collections {
+            Collections::Std if self.no_std => Collections::Heapless,
+            collections => collections,
+        }
+    }
+
+    /// Name of the variant or the constant of an enum entry.
+    fn entry_ident(&self, entry: &model::Ident) -> proc_macro2::Ident {
+        naming::entry_ident(entry, self.preserve_names)
+    }
+
+    /// Name of the struct field of a message field.
+    fn field_ident(&self, field: &model::Ident) -> proc_macro2::Ident {
+        naming::field_ident(field, self.preserve_names)
+    }
+
+    /// Path of the `Error` trait, which is in `core` since Rust 1.81.
+    fn error_trait(&self) -> TokenStream {
+        if self.no_std {
+            quote!(core::error::Error)
+        } else {
+            quote!(std::error::Error)
+        }
+    }
+
     pub fn emit_module(&self, module: &model::MavlinkModule) -> TokenStream {
         let mut stream = self.emit_prelude(module);
 
+        if self.reflection {
+            stream.extend(self.emit_reflect_prelude());
+        }
+
+        let mav_message = self.mav_message_name(module);
+
+        if self.dyn_messages {
+            stream.extend(self.emit_dyn_message(&mav_message));
+        }
+
+        let reexported: HashSet<&model::Ident> = module
+            .reexports
+            .iter()
+            .map(|reexport| &reexport.name)
+            .collect();
+
+        let gated = self.gated_items(module);
+
+        stream.extend(self.emit_reexports(&module.reexports, &gated));
+
         for r#enum in &module.enums {
-            stream.extend(self.emit_enum(r#enum));
+            if reexported.contains(&r#enum.name) {
+                continue;
+            }
+
+            let items = self.emit_enum(r#enum);
+            match gated.get(&r#enum.name) {
+                Some(predicate) => {
+                    stream.extend(self.emit_gated_module("enum", &r#enum.name, predicate, items));
+                }
+                None => stream.extend(items),
+            }
         }
 
         for message in &module.messages {
-            stream.extend(self.emit_message(message));
+            let mut items = if !reexported.contains(&message.name) {
+                let mut items = self.emit_message(message, &mav_message);
+                items.extend(self.emit_command_labeled_params(message, &module.enums));
+                items
+            } else if self.reflection {
+                // `Reflect` is defined per module, so the re-exported messages
+                // still need to implement it.
+                self.emit_message_reflect_impl(message)
+            } else {
+                continue;
+            };
+
+            if let Some(predicate) = gated.get(&message.name) {
+                items = self.emit_gated_module("message", &message.name, predicate, items);
+            }
+            stream.extend(items);
+        }
+
+        let version_messages = module
+            .messages
+            .iter()
+            .filter(|message| !reexported.contains(&message.name))
+            .filter(|message| message.fields.iter().any(is_mavlink_version_field))
+            .collect::<Vec<_>>();
+        if !version_messages.is_empty() {
+            let serde = version_messages
+                .iter()
+                .any(|message| !self.emit_serde_derive(&message.name).is_empty());
+            stream.extend(self.emit_mavlink_version_default(module, serde));
+        }
+
+        stream.extend(self.emit_mav_message(module, &mav_message));
+        stream.extend(self.emit_message_info(&module.messages));
+        stream.extend(self.emit_invalid_enum_value());
+        let string_enums = module
+            .enums
+            .iter()
+            .filter(|r#enum| !r#enum.bitmask && !reexported.contains(&r#enum.name))
+            .collect::<Vec<_>>();
+        if self.enum_strings && !string_enums.is_empty() {
+            stream.extend(self.emit_unknown_enum_name(&string_enums));
+        }
+        if self.field_metadata {
+            stream.extend(self.emit_field_meta());
+        }
+        if self.validation {
+            stream.extend(self.emit_field_error());
         }
 
-        stream.extend(self.emit_mav_message(module));
+        if self.emit_framing {
+            stream.extend(self.emit_framing(&module.messages));
+        }
 
         stream
     }
 
+    /// Returns the name of the enum with all messages of the module.
+    ///
+    /// Panics if the name isn't a valid identifier, see
+    /// [`Self::invalid_mav_message_name`].
+    pub fn mav_message_name(&self, module: &model::MavlinkModule) -> proc_macro2::Ident {
+        format_ident!("{}", self.expand_mav_message_name(module))
+    }
+
+    /// Returns the name of the enum with all messages of the module, if it
+    /// isn't a valid identifier or is a keyword, e.g. `1CommonMessage` for
+    /// `{module}Message` and `1_common.xml`.
+    pub fn invalid_mav_message_name(&self, module: &model::MavlinkModule) -> Option<String> {
+        let name = self.expand_mav_message_name(module);
+        match name.parse::<model::Ident>() {
+            Ok(ident) if ident.keyword_escape() == model::KeywordEscape::None => None,
+            _ => Some(name),
+        }
+    }
+
+    fn expand_mav_message_name(&self, module: &model::MavlinkModule) -> String {
+        let module_name = module
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+
+        self.expand_mav_message_name_for(&module_name)
+    }
+
+    fn expand_mav_message_name_for(&self, module_name: &str) -> String {
+        let Some(template) = &self.mav_message_name else {
+            return "MavMessage".to_owned();
+        };
+
+        // File names may contain dashes and dots, which aren't valid in
+        // identifiers
+        let module_name: String = module_name
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let module_name = naming::pascal_case(&module_name);
+
+        template.replace(MODULE_PLACEHOLDER, &module_name)
+    }
+
+    /// Returns a copy, which replaces `{module}` of [`Self::mav_message_name`]
+    /// with `module_name` instead of the name of the definition file, see
+    /// [`crate::Options::module_name`].
+    pub fn with_module_name(&self, module_name: &str) -> Self {
+        let mav_message_name = self
+            .mav_message_name
+            .as_ref()
+            .map(|_| self.expand_mav_message_name_for(module_name));
+
+        Self {
+            mav_message_name,
+            ..self.clone()
+        }
+    }
+
+    fn emit_reexports(
+        &self,
+        reexports: &[model::Reexport],
+        gated: &HashMap<&model::Ident, TokenStream>,
+    ) -> TokenStream {
+        reexports
+            .iter()
+            .map(|reexport| {
+                let module = format_ident!("{}", reexport.module);
+                let name = reexport.name.pascal_case();
+                let cfg = match gated.get(&reexport.name) {
+                    Some(predicate) => quote! { #[cfg(#predicate)] },
+                    None => TokenStream::new(),
+                };
+                quote! {
+                    #cfg
+                    pub use super::#module::#name;
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the `cfg` predicates of the messages and the enums, which are
+    /// gated behind features with [`Self::strict_wip`] or
+    /// [`Self::per_message_features`].
+    ///
+    /// Enums are only gated, if the messages using them are gated too: WIP
+    /// enums need `wip` unless a stable message uses them, and enums used by
+    /// messages need the feature of one of the messages.
+    fn gated_items<'a>(
+        &self,
+        module: &'a model::MavlinkModule,
+    ) -> HashMap<&'a model::Ident, TokenStream> {
+        let mut gated = HashMap::new();
+        if !self.strict_wip && !self.per_message_features {
+            return gated;
+        }
+
+        let mut users: HashMap<&model::Ident, Vec<&model::Message>> = HashMap::new();
+        for message in &module.messages {
+            if let Some(predicate) = self.message_cfg(message) {
+                gated.insert(&message.name, predicate);
+            }
+            for (field, _) in message.all_fields() {
+                if let Some(r#enum) = &field.r#enum {
+                    let users = users.entry(r#enum).or_default();
+                    if !users.iter().any(|user| user.name == message.name) {
+                        users.push(message);
+                    }
+                }
+            }
+        }
+
+        for r#enum in &module.enums {
+            let users = users.get(&r#enum.name).map_or(&[][..], Vec::as_slice);
+            let mut predicates = vec![];
+            if self.is_wip_gated(r#enum.dev_status.as_ref())
+                && users
+                    .iter()
+                    .all(|user| self.is_wip_gated(user.dev_status.as_ref()))
+            {
+                predicates.push(quote!(feature = "wip"));
+            }
+            if self.per_message_features && !users.is_empty() {
+                let features = users.iter().map(|user| user.name.as_ref());
+                predicates.push(quote!(any(#(feature = #features),*)));
+            }
+
+            if let Some(predicate) = all_predicate(predicates) {
+                gated.insert(&r#enum.name, predicate);
+            }
+        }
+
+        gated
+    }
+
+    fn is_wip_gated(&self, dev_status: Option<&model::DevStatus>) -> bool {
+        self.strict_wip && matches!(dev_status, Some(model::DevStatus::Wip { .. }))
+    }
+
+    /// Returns the `cfg` predicate of a message, which is gated behind
+    /// features with [`Self::strict_wip`] or [`Self::per_message_features`].
+    fn message_cfg(&self, message: &model::Message) -> Option<TokenStream> {
+        let mut predicates = vec![];
+        if self.is_wip_gated(message.dev_status.as_ref()) {
+            predicates.push(quote!(feature = "wip"));
+        }
+        if self.per_message_features {
+            let feature = message.name.as_ref();
+            predicates.push(quote!(feature = #feature));
+        }
+
+        all_predicate(predicates)
+    }
+
+    /// Emits `#[cfg(...)]` for the entries of a gated message in the message
+    /// enum and its lookups.
+    fn emit_message_cfg(&self, message: &model::Message) -> TokenStream {
+        match self.message_cfg(message) {
+            Some(predicate) => quote! { #[cfg(#predicate)] },
+            None => TokenStream::new(),
+        }
+    }
+
+    /// Wraps the items of a gated message or enum into a module behind the
+    /// `cfg` predicate, which is re-exported as is.
+    fn emit_gated_module(
+        &self,
+        kind: &str,
+        name: &model::Ident,
+        predicate: &TokenStream,
+        items: TokenStream,
+    ) -> TokenStream {
+        let module = format_ident!("gated_{}_{}", kind, naming::snake_case(name.as_ref()));
+
+        quote! {
+            #[cfg(#predicate)]
+            mod #module {
+                #[allow(unused_imports)]
+                use super::*;
+
+                #items
+            }
+
+            #[cfg(#predicate)]
+            pub use #module::*;
+        }
+    }
+
+    /// Names of the features of [`Self::per_message_features`], one per
+    /// message of the module.
+    pub fn message_features(&self, module: &model::MavlinkModule) -> Vec<String> {
+        if !self.per_message_features {
+            return vec![];
+        }
+
+        module
+            .messages
+            .iter()
+            .map(|message| message.name.as_ref().to_owned())
+            .collect()
+    }
+
     fn emit_prelude(&self, _module: &model::MavlinkModule) -> TokenStream {
+        let error = self.error_trait();
+        let edition_imports = match self.edition {
+            Edition::Edition2018 => quote! {
+                #[allow(unused_imports)]
+                use core::convert::{TryFrom, TryInto};
+            },
+            Edition::Edition2021 => TokenStream::new(),
+        };
+
+        let preserved_names = if self.preserve_names {
+            quote! { #![allow(non_camel_case_types, non_snake_case)] }
+        } else {
+            TokenStream::new()
+        };
+
         quote! {
             #![doc = "This file was automatically generated, do not edit"]
 
@@ -120,6 +826,7 @@ impl Codegen {
                 clippy::useless_conversion,
                 deprecated,
             )]
+            #preserved_names
 
             #[allow(unused_imports)]
             use bitflags::bitflags;
@@ -136,26 +843,243 @@ impl Codegen {
             use num_traits::FromPrimitive;
             #[allow(unused_imports)]
             use num_traits::ToPrimitive;
+            #edition_imports
 
             #[cfg(feature = "serde")]
             #[allow(unused_imports)]
             use serde::{Deserialize, Serialize};
+
+            /// An error returned when the buffer can't hold the payload.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct BufferTooSmall {
+                pub required: usize,
+                pub available: usize,
+            }
+
+            impl core::fmt::Display for BufferTooSmall {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    write!(
+                        f,
+                        "buffer is too small (need {} bytes, but got {})",
+                        self.required,
+                        self.available,
+                    )
+                }
+            }
+
+            impl #error for BufferTooSmall {}
         }
     }
 
     pub fn emit_enum(&self, r#enum: &model::Enum) -> TokenStream {
         let mut stream = if r#enum.bitmask {
             self.emit_bitmask_enum(r#enum)
+        } else if self.open_enums {
+            self.emit_open_enum(r#enum)
+        } else if self.is_compact_enum(r#enum) {
+            self.emit_compact_enum(r#enum)
         } else {
             self.emit_regular_enum(r#enum)
         };
 
         stream.extend(self.emit_enum_default_impl(r#enum));
         stream.extend(self.emit_enum_converters(r#enum));
+        if !r#enum.bitmask {
+            stream.extend(self.emit_enum_int_conversions(r#enum));
+        }
+        stream.extend(self.emit_enum_comparisons(r#enum));
+        stream.extend(self.emit_enum_param_labels(r#enum));
+        stream.extend(self.emit_enum_log_level(r#enum));
+        stream.extend(self.emit_enum_variants(r#enum));
+        if !r#enum.bitmask {
+            stream.extend(self.emit_enum_strings(r#enum));
+        }
 
         stream
     }
 
+    /// Emits `FromStr` and `Display` for [`Self::enum_strings`].
+    fn emit_enum_strings(&self, r#enum: &model::Enum) -> TokenStream {
+        if !self.enum_strings {
+            return TokenStream::new();
+        }
+
+        let name = r#enum.name.pascal_case();
+        let raw_name = r#enum.name.as_ref();
+        let from_str_arms = r#enum.entries.iter().map(|entry| {
+            let entry_name = entry.name.as_ref();
+            let variant = self.entry_ident(&entry.name);
+            quote! { #entry_name => Ok(Self::#variant) }
+        });
+        let display_arms = r#enum.entries.iter().map(|entry| {
+            let value = Literal::u64_unsuffixed(entry.value);
+            let entry_name = entry.name.as_ref();
+            quote! { #value => f.write_str(#entry_name) }
+        });
+
+        quote! {
+            impl core::str::FromStr for #name {
+                type Err = UnknownEnumName;
+
+                #[allow(deprecated)]
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    match s {
+                        #(#from_str_arms,)*
+                        _ => Err(UnknownEnumName::new(#raw_name, s)),
+                    }
+                }
+            }
+
+            /// Writes the name of the entry in the definition, or the value
+            /// without an entry.
+            impl core::fmt::Display for #name {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    match self.bits() {
+                        #(#display_arms,)*
+                        value => write!(f, "{value}"),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Emits the error of `FromStr` for [`Self::enum_strings`].
+    ///
+    /// With [`Collections::Heapless`] the name is truncated to the longest
+    /// entry name of the module.
+    fn emit_unknown_enum_name(&self, enums: &[&model::Enum]) -> TokenStream {
+        let error = self.error_trait();
+        let capacity = enums
+            .iter()
+            .flat_map(|r#enum| &r#enum.entries)
+            .map(|entry| entry.name.as_ref().len())
+            .max()
+            .unwrap_or(1);
+        let string = self.collections().string(capacity);
+
+        let new_name = match self.collections() {
+            Collections::Std | Collections::Alloc => quote! { name.into() },
+            Collections::Heapless => quote! {{
+                let mut truncated = heapless::String::new();
+                for c in name.chars() {
+                    if truncated.push(c).is_err() {
+                        break;
+                    }
+                }
+                truncated
+            }},
+        };
+
+        quote! {
+            /// Name without an entry in the enum, returned by `FromStr`.
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct UnknownEnumName {
+                /// Name of the enum in the definition.
+                pub enum_name: &'static str,
+                pub name: #string,
+            }
+
+            impl UnknownEnumName {
+                fn new(enum_name: &'static str, name: &str) -> Self {
+                    Self {
+                        enum_name,
+                        name: #new_name,
+                    }
+                }
+            }
+
+            impl core::fmt::Display for UnknownEnumName {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    write!(f, "{} is not an entry of enum {}", self.name, self.enum_name)
+                }
+            }
+
+            impl #error for UnknownEnumName {}
+        }
+    }
+
+    /// Emits `all_variants` and `name` for [`Self::enum_variants`].
+    fn emit_enum_variants(&self, r#enum: &model::Enum) -> TokenStream {
+        if !self.enum_variants {
+            return TokenStream::new();
+        }
+
+        let name = r#enum.name.pascal_case();
+        let variants = r#enum
+            .entries
+            .iter()
+            .map(|entry| self.entry_ident(&entry.name));
+        let arms = r#enum.entries.iter().map(|entry| {
+            let value = Literal::u64_unsuffixed(entry.value);
+            let name = entry.name.as_ref();
+            quote! { #value => #name }
+        });
+
+        let (all_doc, name_doc) = if r#enum.bitmask {
+            (
+                "Every named bit of the bitmask in the order of the definition.",
+                "Name of the bit in the definition, empty if none or several bits are set.",
+            )
+        } else if self.open_enums {
+            (
+                "All entries of the enum in the order of the definition.",
+                "Name of the entry in the definition, empty for unknown values.",
+            )
+        } else {
+            (
+                "All entries of the enum in the order of the definition.",
+                "Name of the entry in the definition.",
+            )
+        };
+
+        quote! {
+            impl #name {
+                #[doc = #all_doc]
+                #[allow(deprecated)]
+                pub const fn all_variants() -> &'static [Self] {
+                    &[#(Self::#variants),*]
+                }
+
+                #[doc = #name_doc]
+                pub fn name(&self) -> &'static str {
+                    match self.bits() {
+                        #(#arms,)*
+                        _ => "",
+                    }
+                }
+            }
+        }
+    }
+
+    /// Emits a conversion into `log::Level` for [`Self::log_severity_enum`].
+    ///
+    /// Values follow the syslog severities used by `MAV_SEVERITY`.
+    fn emit_enum_log_level(&self, r#enum: &model::Enum) -> TokenStream {
+        let is_severity = self
+            .log_severity_enum
+            .as_ref()
+            .is_some_and(|name| name.as_str() == r#enum.name.as_ref());
+        if !is_severity || r#enum.bitmask {
+            return TokenStream::new();
+        }
+
+        let name = r#enum.name.pascal_case();
+
+        quote! {
+            #[cfg(feature = "log")]
+            impl From<#name> for log::Level {
+                fn from(severity: #name) -> Self {
+                    match severity.bits() {
+                        0..=3 => log::Level::Error,
+                        4 => log::Level::Warn,
+                        5 | 6 => log::Level::Info,
+                        _ => log::Level::Debug,
+                    }
+                }
+            }
+        }
+    }
+
     fn emit_doc(
         &self,
         description: Option<&str>,
@@ -165,12 +1089,13 @@ impl Codegen {
         let mut desc = String::new();
 
         if let Some(model::DevStatus::Wip { since, description }) = dev_status {
-            desc.push_str("WIP");
+            desc.push_str("**WORK IN PROGRESS**");
             if let Some(since) = since {
                 write!(desc, " since {}", since).expect("string is ok");
             }
+            desc.push_str(" — may change without notice.");
             if let Some(description) = description {
-                write!(desc, " - {}", description.trim()).expect("string is ok");
+                write!(desc, " {}", description.trim()).expect("string is ok");
             }
             desc.push_str("\n\n");
         }
@@ -181,16 +1106,8 @@ impl Codegen {
 
         let mut stream = TokenStream::new();
 
-        if !desc.is_empty() {
-            let desc = desc.replace('\t', "    ");
-            // The document is processed as markdown, where lines starting with
-            // more than 4 spaces are treated as code blocks. This can happen
-            // easily when XML is formatted for readability, leading to long
-            // indented lines. To avoid this, we'll remove the leading spaces.
-            let desc = remove_line_leading_whitespaces(&desc);
-            let doc = desc.trim();
-
-            stream.extend(quote! { #[doc = #doc] });
+        for line in doc_lines(&desc) {
+            stream.extend(quote! { #[doc = #line] });
         }
 
         if let Some(model::DevStatus::Deprecated {
@@ -211,6 +1128,110 @@ impl Codegen {
         stream
     }
 
+    /// Emits the field description, followed by the list of its metadata
+    /// with [`DocStyle::Full`].
+    fn emit_field_doc(&self, field: &model::Field) -> TokenStream {
+        use std::fmt::Write;
+
+        let mut description = field
+            .description
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .to_owned();
+        if is_mavlink_version_field(field) {
+            if !description.is_empty() {
+                description.push_str("\n\n");
+            }
+            description.push_str(
+                "Managed by the protocol: defaults to `MAVLINK_VERSION`, the version of the dialect.",
+            );
+        }
+        let description = description.as_str();
+        if self.doc_style == DocStyle::Terse {
+            return self.emit_doc(Some(description), None);
+        }
+
+        let code = |value: &String| format!("`{}`", value.trim());
+        let default = field.default.as_ref().map(|default| match default {
+            model::DefaultValue::Int(value) => value.to_string(),
+            model::DefaultValue::Float(value) => value.to_string(),
+            model::DefaultValue::Entry(entry) => format!("`{entry}`"),
+        });
+        let metadata = [
+            ("Units", field.units.as_ref().map(code)),
+            ("Minimum", field.min_value.map(|min| min.to_string())),
+            ("Maximum", field.max_value.map(|max| max.to_string())),
+            ("Increment", field.increment.map(|inc| inc.to_string())),
+            ("Multiplier", field.multiplier.as_ref().map(code)),
+            ("Default", default),
+            ("Invalid", field.invalid.as_ref().map(code)),
+        ];
+
+        let mut desc = description.to_owned();
+        for (i, (name, value)) in metadata
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value?)))
+            .enumerate()
+        {
+            if i == 0 && !desc.is_empty() {
+                desc.push_str("\n\n");
+            } else if i > 0 {
+                desc.push('\n');
+            }
+            write!(desc, "- {name}: {value}").expect("string is ok");
+        }
+
+        self.emit_doc(Some(&desc), None)
+    }
+
+    /// Emits the entry description followed by the list of its parameters.
+    fn emit_entry_doc(&self, entry: &model::Entry) -> TokenStream {
+        use std::fmt::Write;
+
+        if entry.params.is_empty() {
+            return self.emit_doc(entry.description.as_deref(), entry.dev_status.as_ref());
+        }
+
+        let mut desc = entry
+            .description
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .to_owned();
+        if !desc.is_empty() {
+            desc.push_str("\n\n");
+        }
+        desc.push_str("# Parameters\n");
+
+        for param in &entry.params {
+            write!(desc, "\n- **{}**", param.index).expect("string is ok");
+            if let Some(label) = &param.label {
+                write!(desc, " {}", label.trim()).expect("string is ok");
+            }
+
+            let details: Vec<String> = [
+                param.units.as_ref().map(|units| units.trim().to_owned()),
+                param.min_value.map(|min| format!("min: {min}")),
+                param.max_value.map(|max| format!("max: {max}")),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            if !details.is_empty() {
+                write!(desc, " ({})", details.join(", ")).expect("string is ok");
+            }
+
+            if let Some(description) = &param.description {
+                // Keep multiline descriptions inside the list item
+                let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+                write!(desc, ": {description}").expect("string is ok");
+            }
+        }
+
+        self.emit_doc(Some(&desc), entry.dev_status.as_ref())
+    }
+
     fn emit_regular_enum(&self, r#enum: &model::Enum) -> TokenStream {
         let mut stream = self.emit_doc(r#enum.description.as_deref(), r#enum.dev_status.as_ref());
 
@@ -221,9 +1242,8 @@ impl Codegen {
             .entries
             .iter()
             .map(|entry| {
-                let mut stream =
-                    self.emit_doc(entry.description.as_deref(), entry.dev_status.as_ref());
-                let name = entry.name.pascal_case();
+                let mut stream = self.emit_entry_doc(entry);
+                let name = self.entry_ident(&entry.name);
                 let value = Literal::u64_unsuffixed(entry.value);
                 stream.extend(quote! {
                     #name = #value,
@@ -232,10 +1252,26 @@ impl Codegen {
             })
             .collect();
 
+        let non_exhaustive = self.emit_non_exhaustive();
+        let defmt = self.emit_defmt_derive();
+        let arbitrary = self.emit_arbitrary_derive();
+        let mut serde = self.emit_serde_derive(&r#enum.name);
+        if self.serde_snake_case_enums && !serde.is_empty() {
+            serde.extend(quote! {
+                #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+            });
+        }
+
+        let extra = self.emit_extra_derives(DeriveTarget::Enum, &["FromPrimitive", "ToPrimitive"]);
+
         stream.extend(quote! {
             #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
-            #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+            #extra
+            #serde
+            #defmt
+            #arbitrary
             #[repr(#typ)]
+            #non_exhaustive
             pub enum #name {
                 #entries
             }
@@ -244,6 +1280,158 @@ impl Codegen {
         stream
     }
 
+    /// Whether the enum is emitted by [`Self::emit_compact_enum`].
+    pub(crate) fn is_compact_enum(&self, r#enum: &model::Enum) -> bool {
+        self.compact_single_entry_enums
+            && !r#enum.bitmask
+            && !self.open_enums
+            && r#enum.entries.len() == 1
+    }
+
+    fn emit_compact_enum(&self, r#enum: &model::Enum) -> TokenStream {
+        let mut stream = self.emit_doc(r#enum.description.as_deref(), r#enum.dev_status.as_ref());
+
+        let name = r#enum.name.pascal_case();
+        let entry = &r#enum.entries[0];
+        let entry_doc = self.emit_entry_doc(entry);
+        let entry_name = self.entry_ident(&entry.name);
+        let serde = self.emit_serde_derive(&r#enum.name);
+        let defmt = self.emit_defmt_derive();
+        let arbitrary = self.emit_arbitrary_derive();
+        let extra = self.emit_extra_derives(DeriveTarget::Enum, &[]);
+
+        stream.extend(quote! {
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            #extra
+            #serde
+            #defmt
+            #arbitrary
+            pub struct #name;
+
+            #[allow(non_upper_case_globals)]
+            impl #name {
+                #entry_doc
+                pub const #entry_name: Self = Self;
+            }
+        });
+
+        stream
+    }
+
+    /// Emits serde derives, unless the type is listed in [`Self::serde_skip`].
+    fn emit_serde_derive(&self, name: &model::Ident) -> TokenStream {
+        if self
+            .serde_skip
+            .iter()
+            .any(|skipped| skipped.as_str() == name.as_ref())
+        {
+            return TokenStream::new();
+        }
+
+        quote! { #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))] }
+    }
+
+    fn emit_defmt_derive(&self) -> TokenStream {
+        if self.defmt {
+            quote! { #[cfg_attr(feature = "defmt", derive(defmt::Format))] }
+        } else {
+            TokenStream::new()
+        }
+    }
+
+    fn emit_arbitrary_derive(&self) -> TokenStream {
+        if self.arbitrary {
+            quote! { #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))] }
+        } else {
+            TokenStream::new()
+        }
+    }
+
+    fn emit_bitmask_defmt_impl(&self, r#enum: &model::Enum) -> TokenStream {
+        if !self.defmt {
+            return TokenStream::new();
+        }
+
+        let name = r#enum.name.pascal_case();
+        let format = format!("{name}({{=?}})");
+
+        quote! {
+            #[cfg(feature = "defmt")]
+            impl defmt::Format for #name {
+                fn format(&self, f: defmt::Formatter) {
+                    defmt::write!(f, #format, self.bits())
+                }
+            }
+        }
+    }
+
+    fn emit_inline(&self) -> TokenStream {
+        match self.inline {
+            Some(Inline::Hint) => quote! { #[inline] },
+            Some(Inline::Always) => quote! { #[inline(always)] },
+            None => TokenStream::new(),
+        }
+    }
+
+    /// Whether the message is small enough for [`Self::clone_size_threshold`].
+    fn can_derive_clone(&self, message: &model::Message) -> bool {
+        match self.clone_size_threshold {
+            Some(threshold) => message.wire_size() <= threshold,
+            None => true,
+        }
+    }
+
+    fn emit_non_exhaustive(&self) -> TokenStream {
+        if self.non_exhaustive_enums {
+            quote! { #[non_exhaustive] }
+        } else {
+            TokenStream::new()
+        }
+    }
+
+    fn emit_open_enum(&self, r#enum: &model::Enum) -> TokenStream {
+        let mut stream = self.emit_doc(r#enum.description.as_deref(), r#enum.dev_status.as_ref());
+
+        let typ = rust_size_type(r#enum.min_rust_size());
+        let name = r#enum.name.pascal_case();
+
+        let entries: TokenStream = r#enum
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut stream = self.emit_entry_doc(entry);
+                let name = self.entry_ident(&entry.name);
+                let value = Literal::u64_unsuffixed(entry.value);
+                stream.extend(quote! {
+                    pub const #name: Self = Self(#value);
+                });
+                stream
+            })
+            .collect();
+
+        let serde = self.emit_serde_derive(&r#enum.name);
+        let defmt = self.emit_defmt_derive();
+        let arbitrary = self.emit_arbitrary_derive();
+        let extra = self.emit_extra_derives(DeriveTarget::Enum, &["Hash"]);
+
+        stream.extend(quote! {
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #extra
+            #serde
+            #defmt
+            #arbitrary
+            #[repr(transparent)]
+            pub struct #name(pub #typ);
+
+            #[allow(non_upper_case_globals)]
+            impl #name {
+                #entries
+            }
+        });
+
+        stream
+    }
+
     fn emit_bitmask_enum(&self, r#enum: &model::Enum) -> TokenStream {
         let name = r#enum.name.pascal_case();
         let size = rust_size_type(r#enum.min_rust_size());
@@ -252,9 +1440,8 @@ impl Codegen {
             .entries
             .iter()
             .map(|entry| {
-                let mut stream =
-                    self.emit_doc(entry.description.as_deref(), entry.dev_status.as_ref());
-                let name = entry.name.pascal_case();
+                let mut stream = self.emit_entry_doc(entry);
+                let name = self.entry_ident(&entry.name);
                 let value = Literal::u64_unsuffixed(entry.value);
                 stream.extend(quote! {
                     const #name = #value;
@@ -264,25 +1451,34 @@ impl Codegen {
             .collect();
 
         let mut stream = self.emit_doc(r#enum.description.as_deref(), r#enum.dev_status.as_ref());
+        let serde = self.emit_serde_derive(&r#enum.name);
+        let arbitrary = self.emit_arbitrary_derive();
+        let extra = self.emit_extra_derives(DeriveTarget::Enum, &["Hash"]);
 
         stream.extend(quote! {
-            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-            #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #extra
+            #serde
+            #arbitrary
             pub struct #name: #size {
                 #entries
             }
         });
 
+        let defmt = self.emit_bitmask_defmt_impl(r#enum);
+
         quote! {
             bitflags! {
                 #stream
             }
+
+            #defmt
         }
     }
 
     fn emit_enum_default_impl(&self, r#enum: &model::Enum) -> TokenStream {
         let name = r#enum.name.pascal_case();
-        let default_entry = r#enum.entries[0].name.pascal_case();
+        let default_entry = self.entry_ident(&r#enum.entries[0].name);
 
         quote! {
             impl #name {
@@ -302,14 +1498,49 @@ impl Codegen {
         let typ = rust_size_type(r#enum.min_rust_size());
         let raw_name = r#enum.name.as_ref();
 
-        let methods = if r#enum.bitmask {
+        let methods = if !r#enum.bitmask && self.open_enums {
             quote! {
                 #[allow(unused)]
-                fn try_from_bits(value: #typ) -> Result<Self, ParserError> {
-                    Self::from_bits(value).ok_or(ParserError::InvalidFlag {
-                        flag_type: #raw_name,
-                        value: value as u32,
-                    })
+                pub(crate) fn try_from_bits(value: #typ) -> Result<Self, ParserError> {
+                    Ok(Self(value))
+                }
+
+                pub fn bits(self) -> #typ {
+                    self.0
+                }
+            }
+        } else if self.is_compact_enum(r#enum) {
+            let value = Literal::u64_unsuffixed(r#enum.entries[0].value);
+
+            quote! {
+                #[allow(unused)]
+                pub(crate) fn try_from_bits(value: #typ) -> Result<Self, ParserError> {
+                    if value == #value {
+                        Ok(Self)
+                    } else {
+                        Err(ParserError::InvalidEnum {
+                            enum_type: #raw_name,
+                            value: value as u32,
+                        })
+                    }
+                }
+
+                pub fn bits(self) -> #typ {
+                    #value
+                }
+            }
+        } else if r#enum.bitmask {
+            // Bits without an entry may be flags of a newer definition, so
+            // they are kept to be serialised back
+            quote! {
+                #[allow(unused)]
+                pub(crate) fn try_from_bits(value: #typ) -> Result<Self, ParserError> {
+                    Ok(Self::from_bits_retain(value))
+                }
+
+                /// Returns the bits without an entry in the definition.
+                pub fn unknown_bits(&self) -> #typ {
+                    self.bits() & !Self::all().bits()
                 }
             }
         } else {
@@ -317,7 +1548,7 @@ impl Codegen {
 
             quote! {
                 #[allow(unused)]
-                fn try_from_bits(value: #typ) -> Result<Self, ParserError> {
+                pub(crate) fn try_from_bits(value: #typ) -> Result<Self, ParserError> {
                     FromPrimitive::#from_typ(value).ok_or(ParserError::InvalidEnum {
                         enum_type: #raw_name,
                         value: value as u32,
@@ -337,135 +1568,943 @@ impl Codegen {
         }
     }
 
-    fn emit_message(&self, message: &model::Message) -> TokenStream {
-        let mut stream = self.emit_message_def(message);
-        stream.extend(self.emit_message_default_impl(message));
-        stream.extend(self.emit_message_message_data_impl(message));
-        stream
-    }
-
-    fn emit_message_def(&self, message: &model::Message) -> TokenStream {
-        let mut stream = self.emit_doc(message.description.as_deref(), message.dev_status.as_ref());
+    /// Emits conversions from and into the integer of the enum, which fail
+    /// with `InvalidEnumValue` for values without an entry. Bitmasks keep the
+    /// unknown bits instead.
+    fn emit_enum_int_conversions(&self, r#enum: &model::Enum) -> TokenStream {
+        let name = r#enum.name.pascal_case();
+        let typ = rust_size_type(r#enum.min_rust_size());
+        let raw_name = r#enum.name.as_ref();
 
-        if can_derive_eq(message) {
-            stream.extend(quote! { #[derive(Debug, Clone, Copy, PartialEq, Eq)] });
+        let from_int = if self.open_enums {
+            quote! {
+                impl From<#typ> for #name {
+                    fn from(value: #typ) -> Self {
+                        Self(value)
+                    }
+                }
+            }
         } else {
-            stream.extend(quote! { #[derive(Debug, Clone, Copy, PartialEq)] });
-        }
-
-        let defs = message.all_fields().map(|(field, kind)| {
-            let mut stream = self.emit_doc(field.description.as_deref(), None);
-
-            if let FieldType::Array(_, _) = field.r#type {
-                stream.extend(quote! {
-                    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
-                });
+            quote! {
+                impl TryFrom<#typ> for #name {
+                    type Error = InvalidEnumValue;
+
+                    fn try_from(value: #typ) -> Result<Self, Self::Error> {
+                        Self::try_from_bits(value).map_err(|_| InvalidEnumValue {
+                            enum_name: #raw_name,
+                            value: u64::from(value),
+                        })
+                    }
+                }
             }
+        };
 
-            if let model::FieldKind::Extension = kind  {
-                // From MAVLink specification:
-                // If sent by an implementation that doesn't have the extensions fields
-                // then the recipient will see zero values for the extensions fields.
-                let attr = if field.r#enum.is_some() {
-                    quote!{ #[cfg_attr(feature = "serde", serde(default))] }
-                } else {
-                    quote!{
-                        #[cfg_attr(feature = "serde", serde(default = "mavlink_core::utils::RustDefault::rust_default"))]
-                    }
-                };
-                stream.extend(attr);
+        quote! {
+            #from_int
+
+            impl From<#name> for #typ {
+                fn from(value: #name) -> Self {
+                    value.bits()
+                }
             }
+        }
+    }
 
+    fn emit_invalid_enum_value(&self) -> TokenStream {
+        let error = self.error_trait();
 
-            let name = field.name.snake_case();
+        quote! {
+            /// Integer without an entry in the enum, returned by `TryFrom`.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct InvalidEnumValue {
+                /// Name of the enum in the definition.
+                pub enum_name: &'static str,
+                pub value: u64,
+            }
 
-            let def = match (&field.r#enum, field.r#type) {
-                (Some(r#enum), FieldType::Array(_, size)) => {
-                    let typ = r#enum.pascal_case();
-                    let size = usize::from(size);
-                    quote! { pub #name: [#typ; #size] }
-                }
-                (Some(r#enum), FieldType::Primitive(_)) => {
-                    let typ = r#enum.pascal_case();
-                    quote! { pub #name: #typ }
+            impl core::fmt::Display for InvalidEnumValue {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    write!(f, "{} is not a value of enum {}", self.value, self.enum_name)
                 }
-                (None, typ) => {
-                    let typ = field_type_as_rust(typ);
-                    quote! { pub #name: #typ }
-                }
-            };
+            }
 
-            stream.extend(def);
-            stream
-        });
+            impl #error for InvalidEnumValue {}
+        }
+    }
 
-        let name = message.name.pascal_case();
+    /// Allows comparing enums with raw values without casting, like
+    /// `MavState::MavStateActive == raw_state`.
+    fn emit_enum_comparisons(&self, r#enum: &model::Enum) -> TokenStream {
+        let name = r#enum.name.pascal_case();
+        let typ = rust_size_type(r#enum.min_rust_size());
 
-        stream.extend(quote! {
-            #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-            pub struct #name {
-                #(#defs),*
+        quote! {
+            impl PartialEq<#typ> for #name {
+                fn eq(&self, other: &#typ) -> bool {
+                    self.bits() == *other
+                }
             }
-        });
 
-        stream
+            impl PartialEq<#name> for #typ {
+                fn eq(&self, other: &#name) -> bool {
+                    *self == other.bits()
+                }
+            }
+        }
     }
 
-    fn emit_message_default_impl(&self, message: &model::Message) -> TokenStream {
-        let fields = message
-            .fields
-            .iter()
-            .chain(message.extension_fields.iter())
-            .map(|field| {
-                let name = field.name.snake_case();
+    /// Emits the labels of command parameters for enums with `<param>` tags,
+    /// like `MAV_CMD`.
+    fn emit_enum_param_labels(&self, r#enum: &model::Enum) -> TokenStream {
+        if r#enum.entries.iter().all(|entry| entry.params.is_empty()) {
+            return TokenStream::new();
+        }
 
-                match (field.r#type, &field.r#enum) {
-                    (FieldType::Array(_, size), Some(r#enum)) => {
-                        let enm = r#enum.pascal_case();
-                        let size = usize::from(size);
-                        quote! { #name: [#enm::DEFAULT; #size] }
-                    }
-                    (FieldType::Primitive(_), Some(r#enum)) => {
-                        let enm = r#enum.pascal_case();
-                        quote! { #name: #enm::DEFAULT }
-                    }
-                    (typ, None) => {
-                        let val = field_type_default_value(typ);
-                        quote! { #name: #val }
+        let name = r#enum.name.pascal_case();
+
+        let arms = r#enum
+            .entries
+            .iter()
+            .filter(|entry| !entry.params.is_empty())
+            .map(|entry| {
+                let value = Literal::u64_unsuffixed(entry.value);
+                let labels = (1..=7).map(|index| {
+                    let label = entry
+                        .params
+                        .iter()
+                        .find(|param| param.index == index)
+                        .and_then(|param| param.label.as_deref())
+                        .map(str::trim);
+                    match label {
+                        Some(label) => quote!(Some(#label)),
+                        None => quote!(None),
                     }
+                });
+                quote! {
+                    #value => [#(#labels),*]
                 }
             });
 
-        let name = message.name.pascal_case();
-
         quote! {
             impl #name {
-                pub const DEFAULT: Self = Self {
-                    #(#fields),*
-                };
-            }
-
-            impl Default for #name {
-                fn default() -> Self {
-                    Self::DEFAULT
+                /// Returns the labels of `param1` to `param7` of the command,
+                /// `None` for parameters without a label.
+                pub fn param_labels(self) -> [Option<&'static str>; 7] {
+                    match self.bits() {
+                        #(#arms),*,
+                        _ => [None; 7],
+                    }
                 }
             }
         }
     }
 
-    fn emit_message_message_data_impl(&self, message: &model::Message) -> TokenStream {
-        let name = message.name.pascal_case();
-        let id = message.id;
-        let original_name = message.name.as_ref();
-        let extra_crc = message.extra_crc();
-        let encoded_len = message.wire_size();
+    /// Emits `labeled_params` for `COMMAND_LONG`, if its command enum has
+    /// parameter labels.
+    fn emit_command_labeled_params(
+        &self,
+        message: &model::Message,
+        enums: &[model::Enum],
+    ) -> TokenStream {
+        if message.name.as_ref() != "COMMAND_LONG" {
+            return TokenStream::new();
+        }
 
-        let serialiser = self.emit_message_serialise_impl(message);
-        let deserialsier = self.emit_message_deserialise_impl(message);
+        let Some(command_enum) = message
+            .fields
+            .iter()
+            .find(|field| field.name.as_ref() == "command")
+            .and_then(|field| field.r#enum.as_ref())
+        else {
+            return TokenStream::new();
+        };
 
-        quote! {
+        let has_labels = enums.iter().any(|r#enum| {
+            &r#enum.name == command_enum
+                && r#enum.entries.iter().any(|entry| !entry.params.is_empty())
+        });
+        if !has_labels {
+            return TokenStream::new();
+        }
+
+        let params: Vec<_> = (1..=7)
+            .map(|index| {
+                message.fields.iter().find(|field| {
+                    field.name.as_ref() == format!("param{index}")
+                        && field.r#enum.is_none()
+                        && field.r#type == FieldType::Primitive(PrimitiveType::Float)
+                })
+            })
+            .collect::<Option<_>>()
+            .unwrap_or_default();
+        if params.is_empty() {
+            return TokenStream::new();
+        }
+
+        let values = params.iter().map(|field| self.field_ident(&field.name));
+        let name = message.name.pascal_case();
+        let labeled_params = self
+            .collections()
+            .vec(quote!((&'static str, f32)), params.len());
+
+        quote! {
+            impl #name {
+                /// Returns the values of the command parameters with their
+                /// labels. Parameters without a label are skipped, so unknown
+                /// commands or commands without metadata return nothing.
+                pub fn labeled_params(&self) -> #labeled_params {
+                    let values = [#(self.#values),*];
+                    self.command
+                        .param_labels()
+                        .into_iter()
+                        .zip(values)
+                        .filter_map(|(label, value)| Some((label?, value)))
+                        .collect()
+                }
+            }
+        }
+    }
+
+    fn emit_message(
+        &self,
+        message: &model::Message,
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
+        let mut stream = self.emit_message_def(message);
+        stream.extend(self.emit_message_default_impl(message));
+        if self.message_builders {
+            stream.extend(self.emit_message_builder(message));
+        }
+        stream.extend(self.emit_message_encoded_len_v1(message));
+        stream.extend(self.emit_message_addressed(message));
+        stream.extend(self.emit_message_string_accessors(message));
+        stream.extend(self.emit_message_invalid_accessors(message));
+        stream.extend(self.emit_message_fmt_telemetry(message));
+        if self.field_metadata {
+            stream.extend(self.emit_message_field_metadata(message));
+        }
+        if self.validation {
+            stream.extend(self.emit_message_validate(message));
+        }
+        stream.extend(self.emit_message_serialize_payload(message));
+        stream.extend(self.emit_message_message_data_impl(message, mav_message));
+        if self.borrowed_messages {
+            stream.extend(self.emit_message_ref(message));
+        }
+        if self.reflection {
+            stream.extend(self.emit_message_reflect_impl(message));
+        }
+        stream
+    }
+
+    fn emit_message_def(&self, message: &model::Message) -> TokenStream {
+        let mut stream = self.emit_doc(message.description.as_deref(), message.dev_status.as_ref());
+
+        let clone = if self.can_derive_clone(message) {
+            quote! { Clone, Copy, }
+        } else {
+            TokenStream::new()
+        };
+        if can_derive_eq(message) {
+            stream.extend(quote! { #[derive(Debug, #clone PartialEq, Eq)] });
+        } else {
+            stream.extend(quote! { #[derive(Debug, #clone PartialEq)] });
+        }
+        stream.extend(self.emit_extra_derives(DeriveTarget::Message, &[]));
+
+        let serde = self.emit_serde_derive(&message.name);
+
+        let defs = message.all_fields().map(|(field, kind)| {
+            let mut stream = self.emit_field_doc(field);
+            if !serde.is_empty() {
+                stream.extend(self.emit_field_serde_attrs(field, kind));
+            }
+
+            let name = self.field_ident(&field.name);
+            let typ = field_as_rust(field);
+
+            stream.extend(quote! { pub #name: #typ });
+            stream
+        });
+
+        let name = message.name.pascal_case();
+        let defmt = self.emit_defmt_derive();
+        let arbitrary = self.emit_arbitrary_derive();
+
+        stream.extend(quote! {
+            #serde
+            #defmt
+            #arbitrary
+            pub struct #name {
+                #(#defs),*
+            }
+        });
+
+        stream
+    }
+
+    fn emit_field_serde_attrs(&self, field: &model::Field, kind: model::FieldKind) -> TokenStream {
+        let mut stream = TokenStream::new();
+
+        if let FieldType::Array(_, _) = field.r#type {
+            stream.extend(quote! {
+                #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
+            });
+        }
+
+        match kind {
+            model::FieldKind::Extension => {
+                // From MAVLink specification:
+                // If sent by an implementation that doesn't have the extensions fields
+                // then the recipient will see zero values for the extensions fields.
+                let attr = if field.r#enum.is_some() {
+                    quote! { #[cfg_attr(feature = "serde", serde(default))] }
+                } else {
+                    quote! {
+                        #[cfg_attr(feature = "serde", serde(default = "mavlink_core::utils::RustDefault::rust_default"))]
+                    }
+                };
+                stream.extend(attr);
+            }
+            model::FieldKind::Regular if is_mavlink_version_field(field) => {
+                // The version is defined by the dialect, so it's read-only in JSON
+                stream.extend(quote! {
+                    #[cfg_attr(feature = "serde", serde(skip_deserializing, default = "mavlink_version"))]
+                });
+            }
+            model::FieldKind::Regular => {}
+        }
+
+        // Keep the JSON keys the same as the names from the definition
+        let raw_name = field.name.as_ref();
+        if naming::serde_key(&field.name) != raw_name {
+            stream.extend(quote! {
+                #[cfg_attr(feature = "serde", serde(rename = #raw_name))]
+            });
+        }
+
+        stream
+    }
+
+    /// Emits the value of `uint8_t_mavlink_version` fields, which is the
+    /// version of the dialect or 0 without one.
+    ///
+    /// With `serde`, it's also the value of the fields deserialised by serde.
+    fn emit_mavlink_version_default(
+        &self,
+        module: &model::MavlinkModule,
+        serde: bool,
+    ) -> TokenStream {
+        let version = module.version.unwrap_or_default();
+
+        let mut stream = quote! {
+            /// Version of the dialect, which is sent in `uint8_t_mavlink_version`
+            /// fields.
+            pub const MAVLINK_VERSION: u8 = #version;
+        };
+
+        if serde {
+            // The messages using it may be disabled by their features
+            let allow = if self.per_message_features {
+                quote! { #[allow(dead_code)] }
+            } else {
+                TokenStream::new()
+            };
+            stream.extend(quote! {
+                #[cfg(feature = "serde")]
+                #allow
+                fn mavlink_version() -> u8 {
+                    MAVLINK_VERSION
+                }
+            });
+        }
+
+        stream
+    }
+
+    /// Emits `ENCODED_LEN_V1`, while the MAVLink 2 length is
+    /// `MessageData::ENCODED_LEN`.
+    fn emit_message_encoded_len_v1(&self, message: &model::Message) -> TokenStream {
+        let name = message.name.pascal_case();
+        let encoded_len_v1: usize = message
+            .fields
+            .iter()
+            .map(|field| field.r#type.wire_size())
+            .sum();
+
+        quote! {
+            impl #name {
+                /// Length of the MAVLink 1 payload, which has no extension fields.
+                pub const ENCODED_LEN_V1: usize = #encoded_len_v1;
+            }
+        }
+    }
+
+    /// Emits `addressed` for messages with `target_system` and
+    /// `target_component`, see [`model::Message::is_addressed`].
+    fn emit_message_addressed(&self, message: &model::Message) -> TokenStream {
+        if !message.is_addressed() {
+            return TokenStream::new();
+        }
+
+        let name = message.name.pascal_case();
+
+        quote! {
+            impl #name {
+                /// Returns the default message addressed to the component of
+                /// the system.
+                pub const fn addressed(target_system: u8, target_component: u8) -> Self {
+                    Self {
+                        target_system,
+                        target_component,
+                        ..Self::DEFAULT
+                    }
+                }
+            }
+        }
+    }
+
+    /// Emits `{Message}Ref<'a>` for a message with string fields, which
+    /// references them in the payload.
+    fn emit_message_ref(&self, message: &model::Message) -> TokenStream {
+        if string_fields(message).next().is_none() {
+            return TokenStream::new();
+        }
+
+        let name = message.name.pascal_case();
+        let ref_name = format_ident!("{}Ref", name);
+        let doc = format!(
+            "Borrowed [`{name}`], which references string fields in the payload \
+             instead of copying them."
+        );
+
+        let encoded_len = message.wire_size();
+        let to_owned_doc =
+            format!("Copies the message into [`{name}`], padding string fields with NULs.");
+
+        let eq = if can_derive_eq(message) {
+            quote! { Eq, }
+        } else {
+            TokenStream::new()
+        };
+
+        let defs = message.all_fields().map(|(field, _)| {
+            let mut stream = self.emit_field_doc(field);
+            let field_name = self.field_ident(&field.name);
+
+            let def = match (&field.r#enum, field.r#type) {
+                (None, FieldType::Array(PrimitiveType::Char, _)) => {
+                    stream.extend(quote! {
+                        ///
+                        /// Bytes truncated from the end of a MAVLink 2 payload are missing.
+                    });
+                    quote! { pub #field_name: &'a [u8] }
+                }
+                (Some(r#enum), FieldType::Array(_, size)) => {
+                    let typ = r#enum.pascal_case();
+                    let size = usize::from(size);
+                    quote! { pub #field_name: [#typ; #size] }
+                }
+                (Some(r#enum), FieldType::Primitive(_)) => {
+                    let typ = r#enum.pascal_case();
+                    quote! { pub #field_name: #typ }
+                }
+                (None, typ) => {
+                    let typ = field_type_as_rust(typ);
+                    quote! { pub #field_name: #typ }
+                }
+            };
+
+            stream.extend(def);
+            stream
+        });
+
+        let mut offset = 0;
+        let deserialisers = message.wire_ordered_fields().into_iter().map(|field| {
+            let field_name = self.field_ident(&field.name);
+            let start = offset;
+            offset += field.r#type.wire_size();
+            let end = offset;
+            let size = end - start;
+
+            if is_string_field(field) {
+                quote! {
+                    #field_name: {
+                        let _: [u8; #size] = __cursor.get_array();
+                        &__input[#start.min(__avail_len)..#end.min(__avail_len)]
+                    }
+                }
+            } else {
+                let value = field_deserialiser(field);
+                quote! { #field_name: #value }
+            }
+        });
+
+        let owned = message.all_fields().map(|(field, _)| {
+            let field_name = self.field_ident(&field.name);
+            if is_string_field(field) {
+                let size = field.r#type.wire_size();
+                quote! {
+                    #field_name: {
+                        let mut value = [0; #size];
+                        value[..self.#field_name.len()].copy_from_slice(self.#field_name);
+                        value
+                    }
+                }
+            } else {
+                quote! { #field_name: self.#field_name }
+            }
+        });
+
+        let getters = string_fields(message).map(|field| {
+            let field_name = self.field_ident(&field.name);
+            let (getter, _) = string_accessor_names(field);
+            let getter_doc = format!(
+                "Returns `{field_name}` up to the first NUL, invalid UTF-8 is replaced with \
+                 `U+FFFD`."
+            );
+
+            match self.collections() {
+                Collections::Std => quote! {
+                    #[doc = #getter_doc]
+                    pub fn #getter(&self) -> std::borrow::Cow<'a, str> {
+                        let value: &'a [u8] = self.#field_name;
+                        let len = value.iter().position(|c| *c == 0).unwrap_or(value.len());
+                        String::from_utf8_lossy(&value[..len])
+                    }
+                },
+                Collections::Alloc => quote! {
+                    #[doc = #getter_doc]
+                    pub fn #getter(&self) -> alloc::borrow::Cow<'a, str> {
+                        let value: &'a [u8] = self.#field_name;
+                        let len = value.iter().position(|c| *c == 0).unwrap_or(value.len());
+                        alloc::string::String::from_utf8_lossy(&value[..len])
+                    }
+                },
+                Collections::Heapless => {
+                    let getter_doc = format!(
+                        "Returns `{field_name}` up to the first NUL or invalid UTF-8 byte."
+                    );
+                    quote! {
+                        #[doc = #getter_doc]
+                        pub fn #getter(&self) -> &'a str {
+                            let value: &'a [u8] = self.#field_name;
+                            let len = value.iter().position(|c| *c == 0).unwrap_or(value.len());
+                            match core::str::from_utf8(&value[..len]) {
+                                Ok(value) => value,
+                                Err(err) => core::str::from_utf8(&value[..err.valid_up_to()])
+                                    .expect("valid up to the error"),
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, Clone, Copy, PartialEq, #eq)]
+            pub struct #ref_name<'a> {
+                #(#defs),*
+            }
+
+            impl<'a> #ref_name<'a> {
+                /// Parses the payload of the message, string fields reference the input.
+                pub fn deser(
+                    _version: MavlinkVersion,
+                    __input: &'a [u8],
+                ) -> Result<Self, ::mavlink_core::error::ParserError> {
+                    let __avail_len = __input.len().min(#encoded_len);
+                    let mut __payload_buf = [0; #encoded_len];
+                    let mut __cursor = if __avail_len < #encoded_len {
+                        __payload_buf[0..__avail_len].copy_from_slice(__input);
+                        Bytes::new(&__payload_buf)
+                    } else {
+                        Bytes::new(__input)
+                    };
+                    Ok(Self {
+                        #(#deserialisers),*
+                    })
+                }
+
+                #[doc = #to_owned_doc]
+                ///
+                /// Panics if a string field is longer than in the definition.
+                pub fn to_owned(&self) -> #name {
+                    #name {
+                        #(#owned),*
+                    }
+                }
+
+                #(#getters)*
+            }
+        }
+    }
+
+    /// Emits helpers to access `char[N]` fields as strings.
+    fn emit_message_string_accessors(&self, message: &model::Message) -> TokenStream {
+        let accessors: Vec<TokenStream> = string_fields(message)
+            .map(|field| {
+                let name = self.field_ident(&field.name);
+                let (getter, setter) = string_accessor_names(field);
+                let getter_doc = format!(
+                    "Returns `{name}` up to the first NUL, invalid UTF-8 is replaced with `U+FFFD`."
+                );
+                let setter_doc = format!(
+                    "Sets `{name}` padded with NULs, longer values are truncated."
+                );
+
+                let getter = match self.collections() {
+                    Collections::Std => quote! {
+                        #[doc = #getter_doc]
+                        pub fn #getter(&self) -> std::borrow::Cow<'_, str> {
+                            let len = self.#name.iter().position(|c| *c == 0).unwrap_or(self.#name.len());
+                            String::from_utf8_lossy(&self.#name[..len])
+                        }
+                    },
+                    Collections::Alloc => quote! {
+                        #[doc = #getter_doc]
+                        pub fn #getter(&self) -> alloc::borrow::Cow<'_, str> {
+                            let len = self.#name.iter().position(|c| *c == 0).unwrap_or(self.#name.len());
+                            alloc::string::String::from_utf8_lossy(&self.#name[..len])
+                        }
+                    },
+                    Collections::Heapless => {
+                        let getter_doc = format!(
+                            "Returns `{name}` up to the first NUL or invalid UTF-8 byte."
+                        );
+                        quote! {
+                            #[doc = #getter_doc]
+                            pub fn #getter(&self) -> &str {
+                                let len = self.#name.iter().position(|c| *c == 0).unwrap_or(self.#name.len());
+                                match core::str::from_utf8(&self.#name[..len]) {
+                                    Ok(value) => value,
+                                    Err(err) => core::str::from_utf8(&self.#name[..err.valid_up_to()])
+                                        .expect("valid up to the error"),
+                                }
+                            }
+                        }
+                    }
+                };
+
+                quote! {
+                    #getter
+
+                    #[doc = #setter_doc]
+                    pub fn #setter(&mut self, value: &str) {
+                        let len = value.len().min(self.#name.len());
+                        self.#name.fill(0);
+                        self.#name[..len].copy_from_slice(&value.as_bytes()[..len]);
+                    }
+                }
+            })
+            .collect();
+
+        if accessors.is_empty() {
+            return TokenStream::new();
+        }
+
+        let name = message.name.pascal_case();
+
+        quote! {
+            impl #name {
+                #(#accessors)*
+            }
+        }
+    }
+
+    /// Emits `{field}_is_invalid` and `with_{field}_invalid` for fields with
+    /// the `invalid` attribute.
+    fn emit_message_invalid_accessors(&self, message: &model::Message) -> TokenStream {
+        let accessors: Vec<TokenStream> = message
+            .all_fields()
+            .filter_map(|(field, _)| Some((field, field.invalid_value()?)))
+            .map(|(field, invalid)| {
+                let name = self.field_ident(&field.name);
+                let (is_invalid, with_invalid) = invalid_accessor_names(field);
+
+                let raw_invalid = field.invalid.as_deref().unwrap_or_default();
+                let is_invalid_doc =
+                    format!("Whether `{name}` holds the invalid value `{raw_invalid}`.");
+                let with_invalid_doc = format!(
+                    "Returns the message with `{name}` set to the invalid value `{raw_invalid}`."
+                );
+
+                let typ = field.r#type.primitive_type();
+                let value = default_value_as_rust(&invalid.value, typ, None, self.preserve_names);
+                let is_nan =
+                    matches!(invalid.value, model::DefaultValue::Float(value) if value.is_nan());
+                let check = |element: TokenStream| {
+                    if is_nan {
+                        quote! { #element.is_nan() }
+                    } else {
+                        quote! { #element == #value }
+                    }
+                };
+
+                let (check, set) = match field.r#type {
+                    FieldType::Array(_, _) if invalid.first_only => (
+                        check(quote! { self.#name[0] }),
+                        quote! { self.#name[0] = #value; },
+                    ),
+                    FieldType::Array(_, size) => {
+                        let size = usize::from(size);
+                        let check = check(quote! { value });
+                        (
+                            quote! { self.#name.iter().all(|&value| #check) },
+                            quote! { self.#name = [#value; #size]; },
+                        )
+                    }
+                    FieldType::Primitive(_) => (
+                        check(quote! { self.#name }),
+                        quote! { self.#name = #value; },
+                    ),
+                };
+
+                quote! {
+                    #[doc = #is_invalid_doc]
+                    pub fn #is_invalid(&self) -> bool {
+                        #check
+                    }
+
+                    #[doc = #with_invalid_doc]
+                    pub fn #with_invalid(mut self) -> Self {
+                        #set
+                        self
+                    }
+                }
+            })
+            .collect();
+
+        if accessors.is_empty() {
+            return TokenStream::new();
+        }
+
+        let name = message.name.pascal_case();
+
+        quote! {
+            impl #name {
+                #(#accessors)*
+            }
+        }
+    }
+
+    fn emit_message_default_impl(&self, message: &model::Message) -> TokenStream {
+        let fields = message
+            .fields
+            .iter()
+            .chain(message.extension_fields.iter())
+            .map(|field| {
+                let name = self.field_ident(&field.name);
+
+                let value = match (&field.default, &field.r#enum) {
+                    (Some(default), r#enum) => default_value_as_rust(
+                        default,
+                        field.r#type.primitive_type(),
+                        r#enum.as_ref(),
+                        self.preserve_names,
+                    ),
+                    (None, Some(r#enum)) => {
+                        let enm = r#enum.pascal_case();
+                        quote! { #enm::DEFAULT }
+                    }
+                    (None, None) if is_mavlink_version_field(field) => {
+                        return quote! { #name: MAVLINK_VERSION };
+                    }
+                    (None, None) => {
+                        let value = field_type_default_value(field.r#type);
+                        return quote! { #name: #value };
+                    }
+                };
+
+                match field.r#type {
+                    FieldType::Array(_, size) => {
+                        let size = usize::from(size);
+                        quote! { #name: [#value; #size] }
+                    }
+                    FieldType::Primitive(_) => quote! { #name: #value },
+                }
+            });
+
+        let name = message.name.pascal_case();
+
+        quote! {
+            impl #name {
+                pub const DEFAULT: Self = Self {
+                    #(#fields),*
+                };
+            }
+
+            impl Default for #name {
+                fn default() -> Self {
+                    Self::DEFAULT
+                }
+            }
+        }
+    }
+
+    /// Emits `{Message}Builder` with a `with_{field}` setter per field for
+    /// [`Self::message_builders`].
+    fn emit_message_builder(&self, message: &model::Message) -> TokenStream {
+        let name = message.name.pascal_case();
+        let builder_name = format_ident!("{}Builder", name);
+        let doc = format!(
+            "Builder of [`{name}`], fields which aren't set are taken from [`{name}::DEFAULT`]."
+        );
+        let builder_doc = format!("Returns [`{builder_name}`] starting from [`Self::DEFAULT`].");
+
+        let clone = if self.can_derive_clone(message) {
+            quote! { Clone, Copy, }
+        } else {
+            TokenStream::new()
+        };
+
+        let setters = message.all_fields().map(|(field, _)| {
+            let field_name = self.field_ident(&field.name);
+            let setter = format_ident!("with_{}", field_name);
+            let typ = field_as_rust(field);
+            let setter_doc = format!("Sets `{field_name}`.");
+
+            quote! {
+                #[doc = #setter_doc]
+                pub fn #setter(mut self, value: #typ) -> Self {
+                    self.message.#field_name = value;
+                    self
+                }
+            }
+        });
+
+        quote! {
+            #[doc = #doc]
+            #[derive(Debug, #clone PartialEq)]
+            #[must_use]
+            pub struct #builder_name {
+                message: #name,
+            }
+
+            impl #builder_name {
+                #(#setters)*
+
+                /// Returns the message with the fields set so far.
+                pub fn build(self) -> #name {
+                    self.message
+                }
+            }
+
+            impl Default for #builder_name {
+                fn default() -> Self {
+                    #name::builder()
+                }
+            }
+
+            impl #name {
+                #[doc = #builder_doc]
+                pub const fn builder() -> #builder_name {
+                    #builder_name {
+                        message: Self::DEFAULT,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Emits serialisation into a preallocated buffer, which reports a small
+    /// buffer as an error instead of panicking.
+    fn emit_message_fmt_telemetry(&self, message: &model::Message) -> TokenStream {
+        if !self.telemetry_formatter || self.no_std {
+            return TokenStream::new();
+        }
+
+        let lines: Vec<TokenStream> = message
+            .fields
+            .iter()
+            .chain(&message.extension_fields)
+            .map(|field| {
+                let raw_name = field.name.as_ref();
+                let units = field
+                    .units
+                    .as_deref()
+                    .map(|units| format!(" {}", units.replace('{', "{{").replace('}', "}}")))
+                    .unwrap_or_default();
+                let name = self.field_ident(&field.name);
+
+                if is_string_field(field) {
+                    let (getter, _) = string_accessor_names(field);
+                    let format = format!("{raw_name}: {{}}{units}");
+                    quote! { let _ = writeln!(out, #format, self.#getter()); }
+                } else {
+                    let value = if is_hex_field(field) { "{:#x}" } else { "{:?}" };
+                    let format = format!("{raw_name}: {value}{units}");
+                    quote! { let _ = writeln!(out, #format, self.#name); }
+                }
+            })
+            .collect();
+
+        let name = message.name.pascal_case();
+        let body = if lines.is_empty() {
+            quote! { String::new() }
+        } else {
+            quote! {
+                use std::fmt::Write;
+
+                let mut out = String::new();
+                #(#lines)*
+                out
+            }
+        };
+
+        quote! {
+            impl #name {
+                /// Renders the fields as `name: value units` lines.
+                pub fn fmt_telemetry(&self) -> String {
+                    #body
+                }
+            }
+        }
+    }
+
+    fn emit_message_serialize_payload(&self, message: &model::Message) -> TokenStream {
+        let name = message.name.pascal_case();
+        let inline = self.emit_inline();
+
+        quote! {
+            impl #name {
+                /// Serialises the payload into the buffer and returns the number
+                /// of used bytes, trailing zeroes are truncated for v2.
+                #inline
+                pub fn serialize_payload(
+                    &self,
+                    version: MavlinkVersion,
+                    buf: &mut [u8],
+                ) -> Result<usize, BufferTooSmall> {
+                    let available = buf.len();
+                    let payload = buf.get_mut(..Self::ENCODED_LEN).ok_or(BufferTooSmall {
+                        required: Self::ENCODED_LEN,
+                        available,
+                    })?;
+                    Ok(self.ser(version, payload))
+                }
+            }
+        }
+    }
+
+    fn emit_message_message_data_impl(
+        &self,
+        message: &model::Message,
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
+        let name = message.name.pascal_case();
+        let id = message.id;
+        let original_name = message.name.as_ref();
+        let extra_crc = message.extra_crc();
+        let encoded_len = message.wire_size();
+
+        let serialiser = self.emit_message_serialise_impl(message);
+        let deserialsier = self.emit_message_deserialise_impl(message);
+
+        quote! {
             impl MessageData for #name {
-                type Message = MavMessage;
+                type Message = #mav_message;
                 const ID: u32 = #id;
                 const NAME: &'static str = #original_name;
                 const EXTRA_CRC: u8 = #extra_crc;
@@ -478,51 +2517,64 @@ impl Codegen {
     }
 
     fn emit_message_serialise_impl(&self, message: &model::Message) -> TokenStream {
-        let fields = message.sorted_fields().into_iter().chain(&message.extension_fields).map(
-            |field| -> TokenStream {
-                let name = field.name.snake_case();
+        let inline = self.emit_inline();
 
-                match (field.r#type, &field.r#enum) {
-                    (FieldType::Primitive(typ), Some(_)) => {
-                        let serialiser = primitive_type_to_serialiser(typ);
-                        quote! {
-                            __cursor.#serialiser(self.#name.bits().try_into().expect("checked"));
-                        }
+        if message.payload_is_empty() {
+            return quote! {
+                #inline
+                fn ser(&self, _version: MavlinkVersion, _bytes: &mut [u8]) -> usize {
+                    0
+                }
+            };
+        }
+
+        let serialiser = |field: &model::Field| -> TokenStream {
+            let name = self.field_ident(&field.name);
+
+            match (field.r#type, &field.r#enum) {
+                (FieldType::Primitive(typ), Some(_)) => {
+                    let serialiser = primitive_type_to_serialiser(typ);
+                    quote! {
+                        __cursor.#serialiser(self.#name.bits().try_into().expect("checked"));
                     }
-                    (FieldType::Primitive(typ), None) => {
-                        let serialiser = primitive_type_to_serialiser(typ);
-                        quote! {
-                            __cursor.#serialiser(self.#name);
-                        }
+                }
+                (FieldType::Primitive(typ), None) => {
+                    let serialiser = primitive_type_to_serialiser(typ);
+                    quote! {
+                        __cursor.#serialiser(self.#name);
                     }
-                    (FieldType::Array(PrimitiveType::Uint8 | PrimitiveType::Char, _), None) => {
-                        quote! {
-                            __cursor.put_slice(&self.#name);
-                        }
+                }
+                (FieldType::Array(PrimitiveType::Uint8 | PrimitiveType::Char, _), None) => {
+                    quote! {
+                        __cursor.put_slice(&self.#name);
                     }
-                    (FieldType::Array(typ, size), Some(_)) => {
-                        let serialiser = primitive_type_to_serialiser(typ);
-                        let size = usize::from(size);
-                        quote! {
-                            for i in 0..#size {
-                                __cursor.#serialiser(self.#name[i].bits().try_into().expect("checked"));
-                            }
+                }
+                (FieldType::Array(typ, size), Some(_)) => {
+                    let serialiser = primitive_type_to_serialiser(typ);
+                    let size = usize::from(size);
+                    quote! {
+                        for i in 0..#size {
+                            __cursor.#serialiser(self.#name[i].bits().try_into().expect("checked"));
                         }
                     }
-                    (FieldType::Array(typ, size), None) => {
-                        let serialiser = primitive_type_to_serialiser(typ);
-                        let size = usize::from(size);
-                        quote! {
-                            for i in 0..#size {
-                                __cursor.#serialiser(self.#name[i]);
-                            }
+                }
+                (FieldType::Array(typ, size), None) => {
+                    let serialiser = primitive_type_to_serialiser(typ);
+                    let size = usize::from(size);
+                    quote! {
+                        for i in 0..#size {
+                            __cursor.#serialiser(self.#name[i]);
                         }
                     }
                 }
-            },
-        );
+            }
+        };
+        let fields = message.sorted_fields().into_iter().map(serialiser);
+        // MAVLink 1 doesn't have extension fields
+        let extension_fields = message.extension_fields.iter().map(serialiser);
 
         quote! {
+            #inline
             fn ser(&self, version: MavlinkVersion, bytes: &mut [u8]) -> usize {
                 let mut __cursor = BytesMut::new(bytes);
                 if __cursor.remaining() < Self::ENCODED_LEN {
@@ -536,6 +2588,7 @@ impl Codegen {
                 #(#fields)*
 
                 if matches!(version, MavlinkVersion::V2) {
+                    #(#extension_fields)*
                     let len = __cursor.len();
                     ::mavlink_core::utils::remove_trailing_zeroes(&bytes[..len])
                 } else {
@@ -546,65 +2599,48 @@ impl Codegen {
     }
 
     fn emit_message_deserialise_impl(&self, message: &model::Message) -> TokenStream {
-        let fields = message
-            .sorted_fields()
-            .into_iter()
-            // TODO: handle extensions fields for v1
-            .chain(&message.extension_fields)
-            .map(|field| {
-                let name = field.name.snake_case();
-
-                match (field.r#type, &field.r#enum) {
-                    (FieldType::Primitive(typ), Some(r#enum)) => {
-                        let enum_name = r#enum.pascal_case();
-                        let deserialiser = primitive_type_to_deserialiser(typ);
-
-                        quote! {
-                            #name: #enum_name::try_from_bits(
-                                __cursor.#deserialiser().try_into().expect("checked")
-                            )?
-                        }
-                    }
-                    (FieldType::Primitive(typ), None) => {
-                        let deserialiser = primitive_type_to_deserialiser(typ);
-                        quote! { #name: __cursor.#deserialiser() }
-                    }
-                    (FieldType::Array(PrimitiveType::Uint8 | PrimitiveType::Char, _), None) => {
-                        quote! { #name: __cursor.get_array() }
-                    }
-                    (FieldType::Array(typ, size), Some(r#enum)) => {
-                        let enum_name = r#enum.pascal_case();
-                        let deserialiser = primitive_type_to_deserialiser(typ);
-                        let array = (0..usize::from(size)).map(|_| {
-                            quote! {
-                                #enum_name::try_from_bits(
-                                    __cursor.#deserialiser().try_into().expect("checked")
-                                )?
-                            }
-                        });
-
-                        quote! {
-                            #name: [ #(#array),*, ]
-                        }
-                    }
-                    (FieldType::Array(typ, size), None) => {
-                        let deserialiser = primitive_type_to_deserialiser(typ);
-                        let array = (0..usize::from(size)).map(|_| {
-                            quote! {
-                                __cursor.#deserialiser()
-                            }
-                        });
+        let inline = self.emit_inline();
+
+        if message.payload_is_empty() {
+            return quote! {
+                #inline
+                fn deser(
+                    _version: MavlinkVersion,
+                    _input: &[u8],
+                ) -> Result<Self, ::mavlink_core::error::ParserError> {
+                    Ok(Self {})
+                }
+            };
+        }
 
-                        quote! {
-                            #name: [ #(#array),*, ]
-                        }
-                    }
+        let fields = message.sorted_fields().into_iter().map(|field| {
+            let name = self.field_ident(&field.name);
+            let value = field_deserialiser(field);
+            quote! { #name: #value }
+        });
+        // MAVLink 1 payloads end before the extension fields, so they get
+        // their default values
+        let extension_fields = message.extension_fields.iter().map(|field| {
+            let name = self.field_ident(&field.name);
+            let value = field_deserialiser(field);
+            quote! {
+                #name: if matches!(version, MavlinkVersion::V2) {
+                    #value
+                } else {
+                    Self::DEFAULT.#name
                 }
-            });
+            }
+        });
+        let version = if message.extension_fields.is_empty() {
+            format_ident!("_version")
+        } else {
+            format_ident!("version")
+        };
 
         quote! {
+            #inline
             fn deser(
-                _version: MavlinkVersion,
+                #version: MavlinkVersion,
                 __input: &[u8],
             ) -> Result<Self, ::mavlink_core::error::ParserError> {
                 let __avail_len = __input.len();
@@ -616,39 +2652,363 @@ impl Codegen {
                     Bytes::new(__input)
                 };
                 Ok(Self{
-                    #(#fields),*
+                    #(#fields,)*
+                    #(#extension_fields),*
                 })
             }
         }
     }
 
-    fn emit_mav_message(&self, module: &model::MavlinkModule) -> TokenStream {
-        let mut stream = self.emit_mav_message_def(&module.messages);
-        stream.extend(self.emit_mav_message_impl(&module.messages));
+    fn emit_reflect_prelude(&self) -> TokenStream {
+        let [u8_vec, i8_vec, u16_vec, i16_vec, u32_vec, i32_vec, u64_vec, i64_vec, f32_vec, f64_vec] =
+            [
+                quote!(u8),
+                quote!(i8),
+                quote!(u16),
+                quote!(i16),
+                quote!(u32),
+                quote!(i32),
+                quote!(u64),
+                quote!(i64),
+                quote!(f32),
+                quote!(f64),
+            ]
+            .map(|item| self.collections().vec(item, MAX_ARRAY_LEN));
+
+        quote! {
+            /// A dynamically typed value of a message field.
+            #[derive(Debug, Clone, PartialEq)]
+            pub enum FieldValue {
+                U8(u8),
+                I8(i8),
+                U16(u16),
+                I16(i16),
+                U32(u32),
+                I32(i32),
+                U64(u64),
+                I64(i64),
+                F32(f32),
+                F64(f64),
+                U8Array(#u8_vec),
+                I8Array(#i8_vec),
+                U16Array(#u16_vec),
+                I16Array(#i16_vec),
+                U32Array(#u32_vec),
+                I32Array(#i32_vec),
+                U64Array(#u64_vec),
+                I64Array(#i64_vec),
+                F32Array(#f32_vec),
+                F64Array(#f64_vec),
+            }
+
+            /// An error returned when setting a field by name.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub enum ReflectError {
+                /// The message doesn't have a field with such name.
+                UnknownField,
+                /// The value has a different type than the field.
+                TypeMismatch,
+                /// The value doesn't fit into the field: a wrong array length
+                /// or an invalid enum value.
+                InvalidValue,
+            }
+
+            /// Access to message fields by their names from the definition.
+            ///
+            /// Enum fields are represented with their underlying integer type.
+            pub trait Reflect {
+                /// Names of all fields, including extensions.
+                fn field_names(&self) -> &'static [&'static str];
+                fn get_field(&self, name: &str) -> Option<FieldValue>;
+                fn set_field(&mut self, name: &str, value: FieldValue) -> Result<(), ReflectError>;
+            }
+        }
+    }
+
+    /// Emits the object-safe `DynMessage` trait, which is implemented for
+    /// every message through `MessageData` and for the enum of all messages.
+    fn emit_dyn_message(&self, mav_message: &proc_macro2::Ident) -> TokenStream {
+        let vec = self.collections().growable_vec();
+
+        quote! {
+            /// Object-safe view of a message, e.g. to store different
+            /// messages as `Box<dyn DynMessage>`.
+            pub trait DynMessage {
+                fn id(&self) -> u32;
+                fn name(&self) -> &'static str;
+                fn extra_crc(&self) -> u8;
+                /// Appends the payload to the buffer, trailing zeroes are
+                /// truncated for v2.
+                fn serialize(&self, version: MavlinkVersion, buf: &mut #vec<u8>);
+            }
+
+            impl<T: MessageData> DynMessage for T {
+                fn id(&self) -> u32 {
+                    T::ID
+                }
+
+                fn name(&self) -> &'static str {
+                    T::NAME
+                }
+
+                fn extra_crc(&self) -> u8 {
+                    T::EXTRA_CRC
+                }
+
+                fn serialize(&self, version: MavlinkVersion, buf: &mut #vec<u8>) {
+                    let start = buf.len();
+                    buf.resize(start + T::ENCODED_LEN, 0);
+                    let len = self.ser(version, &mut buf[start..]);
+                    buf.truncate(start + len);
+                }
+            }
+
+            impl DynMessage for #mav_message {
+                fn id(&self) -> u32 {
+                    self.message_id()
+                }
+
+                fn name(&self) -> &'static str {
+                    self.message_name()
+                }
+
+                fn extra_crc(&self) -> u8 {
+                    <Self as Message>::extra_crc(self.message_id())
+                }
+
+                fn serialize(&self, version: MavlinkVersion, buf: &mut #vec<u8>) {
+                    let start = buf.len();
+                    buf.resize(start + 255, 0);
+                    let len = self.ser(version, &mut buf[start..]);
+                    buf.truncate(start + len);
+                }
+            }
+        }
+    }
+
+    fn emit_message_reflect_impl(&self, message: &model::Message) -> TokenStream {
+        let name = message.name.pascal_case();
+
+        if message.payload_is_empty() {
+            return quote! {
+                impl Reflect for #name {
+                    fn field_names(&self) -> &'static [&'static str] {
+                        &[]
+                    }
+
+                    fn get_field(&self, _name: &str) -> Option<FieldValue> {
+                        None
+                    }
+
+                    fn set_field(&mut self, _name: &str, _value: FieldValue) -> Result<(), ReflectError> {
+                        Err(ReflectError::UnknownField)
+                    }
+                }
+            };
+        }
+
+        let field_names = message
+            .all_fields()
+            .map(|(field, _)| field.name.as_ref().to_owned());
+
+        let getters = message.all_fields().map(|(field, _)| {
+            let raw_name = field.name.as_ref();
+            let name = self.field_ident(&field.name);
+
+            let value = match (field.r#type, &field.r#enum) {
+                (FieldType::Primitive(typ), Some(_)) => {
+                    let variant = primitive_type_to_field_value(typ);
+                    quote! { FieldValue::#variant(self.#name.bits().try_into().expect("checked")) }
+                }
+                (FieldType::Primitive(typ), None) => {
+                    let variant = primitive_type_to_field_value(typ);
+                    quote! { FieldValue::#variant(self.#name) }
+                }
+                (FieldType::Array(typ, _), Some(_)) => {
+                    let variant = primitive_type_to_field_value_array(typ);
+                    quote! {
+                        FieldValue::#variant(
+                            self.#name
+                                .iter()
+                                .map(|value| value.bits().try_into().expect("checked"))
+                                .collect()
+                        )
+                    }
+                }
+                (FieldType::Array(typ, _), None) => {
+                    let variant = primitive_type_to_field_value_array(typ);
+                    if self.collections() == Collections::Heapless {
+                        quote! {
+                            FieldValue::#variant(
+                                heapless::Vec::from_slice(&self.#name).expect("array fits")
+                            )
+                        }
+                    } else {
+                        quote! { FieldValue::#variant(self.#name.to_vec()) }
+                    }
+                }
+            };
+
+            quote! {
+                #raw_name => Some(#value)
+            }
+        });
+
+        let setters = message.all_fields().map(|(field, _)| {
+            let raw_name = field.name.as_ref();
+            let name = self.field_ident(&field.name);
+
+            let assignment = match (field.r#type, &field.r#enum) {
+                (FieldType::Primitive(typ), Some(r#enum)) => {
+                    let variant = primitive_type_to_field_value(typ);
+                    let enum_name = r#enum.pascal_case();
+                    quote! {
+                        FieldValue::#variant(value) => {
+                            let value = value.try_into().map_err(|_| ReflectError::InvalidValue)?;
+                            self.#name = #enum_name::try_from_bits(value)
+                                .map_err(|_| ReflectError::InvalidValue)?;
+                        }
+                    }
+                }
+                (FieldType::Primitive(typ), None) => {
+                    let variant = primitive_type_to_field_value(typ);
+                    quote! {
+                        FieldValue::#variant(value) => self.#name = value
+                    }
+                }
+                (FieldType::Array(typ, size), Some(r#enum)) => {
+                    let variant = primitive_type_to_field_value_array(typ);
+                    let enum_name = r#enum.pascal_case();
+                    let size = usize::from(size);
+                    quote! {
+                        FieldValue::#variant(values) => {
+                            if values.len() != #size {
+                                return Err(ReflectError::InvalidValue);
+                            }
+                            let mut array = [#enum_name::DEFAULT; #size];
+                            for (item, value) in array.iter_mut().zip(values) {
+                                let value = value.try_into().map_err(|_| ReflectError::InvalidValue)?;
+                                *item = #enum_name::try_from_bits(value)
+                                    .map_err(|_| ReflectError::InvalidValue)?;
+                            }
+                            self.#name = array;
+                        }
+                    }
+                }
+                (FieldType::Array(typ, _), None) => {
+                    let variant = primitive_type_to_field_value_array(typ);
+                    // `heapless::Vec` converts into arrays only through slices
+                    let values = if self.collections() == Collections::Heapless {
+                        quote!(values.as_slice())
+                    } else {
+                        quote!(values)
+                    };
+                    quote! {
+                        FieldValue::#variant(values) => {
+                            self.#name = #values.try_into().map_err(|_| ReflectError::InvalidValue)?;
+                        }
+                    }
+                }
+            };
+
+            quote! {
+                #raw_name => match value {
+                    #assignment,
+                    _ => return Err(ReflectError::TypeMismatch),
+                }
+            }
+        });
+
+        quote! {
+            impl Reflect for #name {
+                fn field_names(&self) -> &'static [&'static str] {
+                    &[#(#field_names),*]
+                }
+
+                fn get_field(&self, name: &str) -> Option<FieldValue> {
+                    match name {
+                        #(#getters,)*
+                        _ => None,
+                    }
+                }
+
+                fn set_field(&mut self, name: &str, value: FieldValue) -> Result<(), ReflectError> {
+                    match name {
+                        #(#setters,)*
+                        _ => return Err(ReflectError::UnknownField),
+                    }
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn emit_mav_message(
+        &self,
+        module: &model::MavlinkModule,
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
+        let mut stream = self.emit_mav_message_def(&module.messages, mav_message);
+        stream.extend(self.emit_mav_message_impl(&module.messages, mav_message));
+        stream.extend(self.emit_mav_message_extra_crc_lookup(&module.messages, mav_message));
+        stream.extend(self.emit_mav_message_name_lookup(&module.messages, mav_message));
+        stream.extend(self.emit_mav_message_from_impls(&module.messages, mav_message));
+        stream.extend(self.emit_mav_message_serialize_payload(&module.messages, mav_message));
+        if self.reflection {
+            stream.extend(self.emit_mav_message_reflect_impl(&module.messages, mav_message));
+        }
         stream
     }
 
-    fn emit_mav_message_def(&self, messages: &[model::Message]) -> TokenStream {
+    fn emit_mav_message_def(
+        &self,
+        messages: &[model::Message],
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
         let entries = messages.iter().map(|message| {
             let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
             let str_name = message.name.as_ref();
             quote! {
+                #cfg
                 #[cfg_attr(feature = "serde", serde(rename = #str_name))]
                 #name(#name)
             }
         });
 
+        let non_exhaustive = self.emit_non_exhaustive();
+        let defmt = self.emit_defmt_derive();
+        let arbitrary = self.emit_arbitrary_derive();
+        let clone = if messages
+            .iter()
+            .all(|message| self.can_derive_clone(message))
+        {
+            quote! { Clone, }
+        } else {
+            TokenStream::new()
+        };
+
+        let extra = self.emit_extra_derives(DeriveTarget::Message, &[]);
+
         quote! {
-            #[derive(Debug, Clone, PartialEq)]
+            #[derive(Debug, #clone PartialEq)]
+            #extra
             #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
             #[cfg_attr(feature = "serde", serde(tag = "mavpackettype"))]
-            pub enum MavMessage {
+            #defmt
+            #arbitrary
+            #non_exhaustive
+            pub enum #mav_message {
                 #(#entries),*,
             }
         }
     }
 
-    fn emit_mav_message_impl(&self, messages: &[model::Message]) -> TokenStream {
+    fn emit_mav_message_impl(
+        &self,
+        messages: &[model::Message],
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
         let ser = self.emit_mav_message_ser(messages);
         let parse = self.emit_mav_message_parse(messages);
         let name = self.emit_mav_message_name(messages);
@@ -658,7 +3018,9 @@ impl Codegen {
         let extra_crc = self.emit_mav_message_extra_crc(messages);
 
         quote! {
-            impl Message for MavMessage {
+            /// Every message of the dialect as [`mavlink_core::Message`], which
+            /// requires `mavlink-core` 0.13 or newer.
+            impl Message for #mav_message {
                 #ser
                 #parse
                 #name
@@ -670,10 +3032,47 @@ impl Codegen {
         }
     }
 
+    fn emit_mav_message_reflect_impl(
+        &self,
+        messages: &[model::Message],
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
+        let names = messages.iter().map(|message| message.name.pascal_case());
+        let (names_1, names_2) = (names.clone(), names.clone());
+        let cfgs = messages
+            .iter()
+            .map(|message| self.emit_message_cfg(message));
+        let (cfgs_1, cfgs_2) = (cfgs.clone(), cfgs.clone());
+
+        quote! {
+            impl Reflect for #mav_message {
+                fn field_names(&self) -> &'static [&'static str] {
+                    match self {
+                        #(#cfgs Self::#names(body) => body.field_names()),*,
+                    }
+                }
+
+                fn get_field(&self, name: &str) -> Option<FieldValue> {
+                    match self {
+                        #(#cfgs_1 Self::#names_1(body) => body.get_field(name)),*,
+                    }
+                }
+
+                fn set_field(&mut self, name: &str, value: FieldValue) -> Result<(), ReflectError> {
+                    match self {
+                        #(#cfgs_2 Self::#names_2(body) => body.set_field(name, value)),*,
+                    }
+                }
+            }
+        }
+    }
+
     fn emit_mav_message_ser(&self, messages: &[model::Message]) -> TokenStream {
         let messages = messages.iter().map(|message| {
             let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
             quote! {
+                #cfg
                 Self::#name(body) => body.ser(version, bytes)
             }
         });
@@ -690,7 +3089,9 @@ impl Codegen {
     fn emit_mav_message_parse(&self, messages: &[model::Message]) -> TokenStream {
         let messages = messages.iter().map(|message| {
             let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
             quote! {
+                #cfg
                 #name::ID => {
                     #name::deser(version, payload).map(Self::#name)
                 }
@@ -715,7 +3116,9 @@ impl Codegen {
     fn emit_mav_message_name(&self, messages: &[model::Message]) -> TokenStream {
         let messages = messages.iter().map(|message| {
             let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
             quote! {
+                #cfg
                 Self::#name(..) => #name::NAME
             }
         });
@@ -732,7 +3135,9 @@ impl Codegen {
     fn emit_mav_message_id(&self, messages: &[model::Message]) -> TokenStream {
         let messages = messages.iter().map(|message| {
             let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
             quote! {
+                #cfg
                 Self::#name(..) => #name::ID
             }
         });
@@ -749,7 +3154,9 @@ impl Codegen {
     fn emit_mav_message_id_from_name(&self, messages: &[model::Message]) -> TokenStream {
         let messages = messages.iter().map(|message| {
             let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
             quote! {
+                #cfg
                 #name::NAME => Ok(#name::ID)
             }
         });
@@ -767,7 +3174,9 @@ impl Codegen {
     fn emit_mav_message_default_message_from_id(&self, messages: &[model::Message]) -> TokenStream {
         let messages = messages.iter().map(|message| {
             let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
             quote! {
+                #cfg
                 #name::ID => Ok(Self::#name(#name::default()))
             }
         });
@@ -782,10 +3191,482 @@ impl Codegen {
         }
     }
 
+    fn emit_message_info(&self, messages: &[model::Message]) -> TokenStream {
+        let infos = messages.iter().map(|message| {
+            let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
+            quote! {
+                #cfg
+                MessageInfo {
+                    id: #name::ID,
+                    name: #name::NAME,
+                    extra_crc: #name::EXTRA_CRC,
+                    max_payload_len: #name::ENCODED_LEN,
+                }
+            }
+        });
+        let max_payload_len = messages
+            .iter()
+            .map(model::Message::wire_size)
+            .max()
+            .unwrap_or(0);
+
+        quote! {
+            /// Metadata of a message type.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct MessageInfo {
+                pub id: u32,
+                pub name: &'static str,
+                pub extra_crc: u8,
+                /// Payload length including the extension fields.
+                pub max_payload_len: usize,
+            }
+
+            /// All messages of the dialect, e.g. to register handlers for
+            /// each of them.
+            pub const MESSAGE_INFO: &[MessageInfo] = &[#(#infos),*];
+
+            /// The largest payload length of the messages, e.g. to allocate
+            /// a buffer for any of them.
+            pub const MAX_PAYLOAD_LEN: usize = #max_payload_len;
+        }
+    }
+
+    fn emit_field_meta(&self) -> TokenStream {
+        quote! {
+            /// Metadata of a message field from the definition.
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub struct FieldMeta {
+                pub units: Option<&'static str>,
+                pub increment: Option<f32>,
+                pub min: Option<f32>,
+                pub max: Option<f32>,
+                pub multiplier: Option<&'static str>,
+            }
+        }
+    }
+
+    fn emit_message_field_metadata(&self, message: &model::Message) -> TokenStream {
+        let name = message.name.pascal_case();
+        let fields = message
+            .fields
+            .iter()
+            .chain(&message.extension_fields)
+            .map(|field| {
+                let raw_name = field.name.as_ref();
+                let units = option_as_rust(field.units.as_deref());
+                let increment = option_as_rust(field.increment);
+                let min = option_as_rust(field.min_value);
+                let max = option_as_rust(field.max_value);
+                let multiplier = option_as_rust(field.multiplier.as_deref());
+                quote! {
+                    #raw_name => Some(FieldMeta {
+                        units: #units,
+                        increment: #increment,
+                        min: #min,
+                        max: #max,
+                        multiplier: #multiplier,
+                    })
+                }
+            });
+
+        quote! {
+            impl #name {
+                /// Returns the metadata of the field with the name from the
+                /// definition.
+                pub fn field_metadata(name: &str) -> Option<FieldMeta> {
+                    match name {
+                        #(#fields,)*
+                        _ => None,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Emits `FieldError` returned by `validate` for [`Self::validation`].
+    fn emit_field_error(&self) -> TokenStream {
+        quote! {
+            /// How a field value violates the definition.
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub enum FieldErrorKind {
+                /// The value is below `minValue`.
+                BelowMin(f32),
+                /// The value is above `maxValue`.
+                AboveMax(f32),
+                /// The value isn't a multiple of `increment` counted from
+                /// `minValue`, or from 0 without it.
+                OffIncrement(f32),
+            }
+
+            impl FieldErrorKind {
+                /// Checks the value against the range and the increment.
+                pub fn check(
+                    value: f64,
+                    min: Option<f32>,
+                    max: Option<f32>,
+                    increment: Option<f32>,
+                ) -> Option<Self> {
+                    if let Some(min) = min.filter(|min| value < f64::from(*min)) {
+                        return Some(Self::BelowMin(min));
+                    }
+                    if let Some(max) = max.filter(|max| value > f64::from(*max)) {
+                        return Some(Self::AboveMax(max));
+                    }
+
+                    let increment = increment.filter(|increment| *increment > 0.0)?;
+                    let step = f64::from(increment);
+                    let mut offset = (value - f64::from(min.unwrap_or(0.0))) % step;
+                    if offset < 0.0 {
+                        offset += step;
+                    }
+                    // Increments like 0.1 aren't exact in binary
+                    let tolerance = step * 1e-6;
+                    if offset > tolerance && step - offset > tolerance {
+                        return Some(Self::OffIncrement(increment));
+                    }
+                    None
+                }
+            }
+
+            /// A field value, which violates the definition.
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            pub struct FieldError {
+                /// Name of the field in the definition.
+                pub field: &'static str,
+                /// Index of the element of an array field.
+                pub index: Option<usize>,
+                /// The value converted to `f64`.
+                pub value: f64,
+                pub kind: FieldErrorKind,
+            }
+        }
+    }
+
+    /// Emits `validate` for [`Self::validation`].
+    fn emit_message_validate(&self, message: &model::Message) -> TokenStream {
+        let checked = message
+            .all_fields()
+            .map(|(field, _)| field)
+            .filter(|field| {
+                field.r#enum.is_none()
+                    && field.r#type.primitive_type() != PrimitiveType::Char
+                    && !is_mavlink_version_field(field)
+                    && (field.min_value.is_some()
+                        || field.max_value.is_some()
+                        || field.increment.is_some())
+            })
+            .collect::<Vec<_>>();
+
+        let capacity = checked
+            .iter()
+            .map(|field| match field.r#type {
+                FieldType::Array(_, size) => usize::from(size),
+                FieldType::Primitive(_) => 1,
+            })
+            .sum();
+        let errors = self.collections.vec(quote!(FieldError), capacity);
+
+        let push = |error: TokenStream| match self.collections {
+            // The capacity fits an error per checked value
+            Collections::Heapless => quote! { let _ = errors.push(#error); },
+            Collections::Std | Collections::Alloc => quote! { errors.push(#error); },
+        };
+
+        let checks = checked.iter().map(|field| {
+            let raw_name = field.name.as_ref();
+            let name = self.field_ident(&field.name);
+            let min = option_as_rust(field.min_value);
+            let max = option_as_rust(field.max_value);
+            let increment = option_as_rust(field.increment);
+
+            let as_f64 = |value: TokenStream| match field.r#type.primitive_type() {
+                PrimitiveType::Double => value,
+                PrimitiveType::Int64 | PrimitiveType::Uint64 => quote! { #value as f64 },
+                _ => quote! { f64::from(#value) },
+            };
+
+            match field.r#type {
+                FieldType::Primitive(_) => {
+                    let value = as_f64(quote! { self.#name });
+                    let push = push(quote! {
+                        FieldError { field: #raw_name, index: None, value, kind }
+                    });
+                    quote! {
+                        let value = #value;
+                        if let Some(kind) = FieldErrorKind::check(value, #min, #max, #increment) {
+                            #push
+                        }
+                    }
+                }
+                FieldType::Array(_, _) => {
+                    let value = as_f64(quote! { *value });
+                    let push = push(quote! {
+                        FieldError { field: #raw_name, index: Some(index), value, kind }
+                    });
+                    quote! {
+                        for (index, value) in self.#name.iter().enumerate() {
+                            let value = #value;
+                            if let Some(kind) = FieldErrorKind::check(value, #min, #max, #increment) {
+                                #push
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        let name = message.name.pascal_case();
+        let body = if checked.is_empty() {
+            quote! { Ok(()) }
+        } else {
+            quote! {
+                let mut errors = <#errors>::new();
+                #(#checks)*
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+        };
+
+        quote! {
+            impl #name {
+                /// Checks the fields against the range and the increment from
+                /// the definition.
+                pub fn validate(&self) -> Result<(), #errors> {
+                    #body
+                }
+            }
+        }
+    }
+
+    fn emit_mav_message_extra_crc_lookup(
+        &self,
+        messages: &[model::Message],
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
+        let mut ids_by_extra_crc: BTreeMap<u8, Vec<u32>> = BTreeMap::new();
+        for message in messages {
+            ids_by_extra_crc
+                .entry(message.extra_crc())
+                .or_default()
+                .push(message.id);
+        }
+        let ids_by_extra_crc = ids_by_extra_crc.into_iter().map(|(extra_crc, mut ids)| {
+            ids.sort_unstable();
+            let extra_crc = Literal::u8_unsuffixed(extra_crc);
+            let ids = ids.into_iter().map(Literal::u32_unsuffixed);
+            quote! {
+                #extra_crc => &[#(#ids),*]
+            }
+        });
+
+        let messages = messages.iter().map(|message| {
+            let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
+            quote! {
+                #cfg
+                #name::ID => Some(#name::EXTRA_CRC)
+            }
+        });
+
+        quote! {
+            impl #mav_message {
+                /// Returns CRC_EXTRA of the message with the given ID, or
+                /// `None` if the message is unknown. It's a `const fn`, so
+                /// it can be evaluated at compile time.
+                pub const fn try_extra_crc(id: u32) -> Option<u8> {
+                    match id {
+                        #(#messages),*,
+                        _ => None,
+                    }
+                }
+
+                /// Returns IDs of the messages with the given CRC_EXTRA in
+                /// ascending order, e.g. to find the message of a frame with
+                /// an unknown ID. CRC_EXTRA isn't unique, so there may be
+                /// several of them.
+                pub const fn ids_by_extra_crc(extra_crc: u8) -> &'static [u32] {
+                    match extra_crc {
+                        #(#ids_by_extra_crc,)*
+                        _ => &[],
+                    }
+                }
+
+                /// Validates the X.25 (CRC-16/MCRF4XX) checksum of a frame of the
+                /// message with the given ID. `data` are the checksummed bytes of
+                /// the frame, i.e. the header after the magic byte followed by the
+                /// payload, CRC_EXTRA of the message is added by the function.
+                /// Frames of unknown messages are never valid.
+                pub fn validate_crc(id: u32, data: &[u8], crc: u16) -> bool {
+                    let extra_crc = match Self::try_extra_crc(id) {
+                        Some(extra_crc) => extra_crc,
+                        None => return false,
+                    };
+                    let accumulate = |crc: u16, byte: u8| {
+                        let tmp = byte ^ (crc as u8);
+                        let tmp = tmp ^ (tmp << 4);
+                        let tmp = u16::from(tmp);
+                        (crc >> 8) ^ (tmp << 8) ^ (tmp << 3) ^ (tmp >> 4)
+                    };
+                    let checksum = data.iter().fold(0xFFFF, |crc, byte| accumulate(crc, *byte));
+                    accumulate(checksum, extra_crc) == crc
+                }
+            }
+        }
+    }
+
+    /// Emits lookups between message names and IDs, which don't need an
+    /// instance of the message.
+    fn emit_mav_message_name_lookup(
+        &self,
+        messages: &[model::Message],
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
+        let ids_by_name = messages.iter().map(|message| {
+            let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
+            quote! {
+                #cfg
+                #name::NAME => Some(#name::ID)
+            }
+        });
+        let names_by_id = messages.iter().map(|message| {
+            let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
+            quote! {
+                #cfg
+                #name::ID => Some(#name::NAME)
+            }
+        });
+
+        let mut sorted = messages.iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|message| message.id);
+        let all_ids = sorted.iter().map(|message| {
+            let cfg = self.emit_message_cfg(message);
+            let id = Literal::u32_unsuffixed(message.id);
+            quote! { #cfg #id }
+        });
+        let all_names = sorted.iter().map(|message| {
+            let cfg = self.emit_message_cfg(message);
+            let name = message.name.as_ref();
+            quote! { #cfg #name }
+        });
+
+        quote! {
+            impl #mav_message {
+                /// IDs of all messages of the dialect in ascending order.
+                pub const ALL_MESSAGE_IDS: &[u32] = &[#(#all_ids),*];
+
+                /// Names of all messages of the dialect in the order of
+                /// [`Self::ALL_MESSAGE_IDS`].
+                pub const ALL_MESSAGE_NAMES: &[&str] = &[#(#all_names),*];
+
+                /// Returns the ID of the message with the given name, or
+                /// `None` if the message is unknown.
+                pub fn id_from_name(name: &str) -> Option<u32> {
+                    match name {
+                        #(#ids_by_name),*,
+                        _ => None,
+                    }
+                }
+
+                /// Returns the name of the message with the given ID, or
+                /// `None` if the message is unknown.
+                pub fn name_from_id(id: u32) -> Option<&'static str> {
+                    match id {
+                        #(#names_by_id),*,
+                        _ => None,
+                    }
+                }
+            }
+        }
+    }
+
+    fn emit_mav_message_serialize_payload(
+        &self,
+        messages: &[model::Message],
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
+        let messages = messages.iter().map(|message| {
+            let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
+            quote! {
+                #cfg
+                Self::#name(_) => #name::ENCODED_LEN
+            }
+        });
+
+        quote! {
+            impl #mav_message {
+                /// Serialises the payload into the buffer and returns the number
+                /// of used bytes, trailing zeroes are truncated for v2.
+                pub fn serialize_payload(
+                    &self,
+                    version: MavlinkVersion,
+                    buf: &mut [u8],
+                ) -> Result<usize, BufferTooSmall> {
+                    let required = match self {
+                        #(#messages),*,
+                    };
+                    let available = buf.len();
+                    let payload = buf
+                        .get_mut(..required)
+                        .ok_or(BufferTooSmall { required, available })?;
+                    Ok(self.ser(version, payload))
+                }
+            }
+        }
+    }
+
+    /// Emits conversions from owned and borrowed messages, the latter copies
+    /// the message and is skipped for messages without `Copy`.
+    fn emit_mav_message_from_impls(
+        &self,
+        messages: &[model::Message],
+        mav_message: &proc_macro2::Ident,
+    ) -> TokenStream {
+        let impls = messages.iter().map(|message| {
+            let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
+            let from_ref = if self.can_derive_clone(message) {
+                quote! {
+                    #cfg
+                    impl From<&#name> for #mav_message {
+                        fn from(message: &#name) -> Self {
+                            Self::#name(*message)
+                        }
+                    }
+                }
+            } else {
+                TokenStream::new()
+            };
+
+            quote! {
+                #cfg
+                impl From<#name> for #mav_message {
+                    fn from(message: #name) -> Self {
+                        Self::#name(message)
+                    }
+                }
+
+                #from_ref
+            }
+        });
+
+        quote! {
+            #(#impls)*
+        }
+    }
+
     fn emit_mav_message_extra_crc(&self, messages: &[model::Message]) -> TokenStream {
         let messages = messages.iter().map(|message| {
             let name = message.name.pascal_case();
+            let cfg = self.emit_message_cfg(message);
             quote! {
+                #cfg
                 #name::ID => #name::EXTRA_CRC
             }
         });
@@ -801,6 +3682,15 @@ impl Codegen {
     }
 }
 
+/// Combines `cfg` predicates with `all`, if there are several of them.
+fn all_predicate(mut predicates: Vec<TokenStream>) -> Option<TokenStream> {
+    if predicates.len() > 1 {
+        Some(quote!(all(#(#predicates),*)))
+    } else {
+        predicates.pop()
+    }
+}
+
 fn primitive_type_to_serialiser(typ: PrimitiveType) -> syn::Ident {
     let func = match typ {
         PrimitiveType::Float => "put_f32_le",
@@ -834,6 +3724,135 @@ fn primitive_type_to_deserialiser(typ: PrimitiveType) -> syn::Ident {
     syn::Ident::new(func, proc_macro2::Span::call_site())
 }
 
+fn primitive_type_to_field_value(typ: PrimitiveType) -> syn::Ident {
+    let variant = match typ {
+        PrimitiveType::Float => "F32",
+        PrimitiveType::Double => "F64",
+        PrimitiveType::Uint8 | PrimitiveType::Uint8MavlinkVersion | PrimitiveType::Char => "U8",
+        PrimitiveType::Int8 => "I8",
+        PrimitiveType::Int16 => "I16",
+        PrimitiveType::Uint16 => "U16",
+        PrimitiveType::Int32 => "I32",
+        PrimitiveType::Uint32 => "U32",
+        PrimitiveType::Int64 => "I64",
+        PrimitiveType::Uint64 => "U64",
+    };
+    syn::Ident::new(variant, proc_macro2::Span::call_site())
+}
+
+fn primitive_type_to_field_value_array(typ: PrimitiveType) -> syn::Ident {
+    format_ident!("{}Array", primitive_type_to_field_value(typ))
+}
+
+/// Fields of type `char[N]`, which get string accessors.
+fn string_fields(message: &model::Message) -> impl Iterator<Item = &model::Field> {
+    message
+        .fields
+        .iter()
+        .chain(&message.extension_fields)
+        .filter(|field| is_string_field(field))
+}
+
+/// Whether the field is a `char` array, which gets string accessors.
+/// Expression, which reads the value of `field` from `__cursor`.
+fn field_deserialiser(field: &model::Field) -> TokenStream {
+    match (field.r#type, &field.r#enum) {
+        (FieldType::Primitive(typ), Some(r#enum)) => {
+            let enum_name = r#enum.pascal_case();
+            let deserialiser = primitive_type_to_deserialiser(typ);
+
+            quote! {
+                #enum_name::try_from_bits(
+                    __cursor.#deserialiser().try_into().expect("checked")
+                )?
+            }
+        }
+        (FieldType::Primitive(typ), None) => {
+            let deserialiser = primitive_type_to_deserialiser(typ);
+            quote! { __cursor.#deserialiser() }
+        }
+        (FieldType::Array(PrimitiveType::Uint8 | PrimitiveType::Char, _), None) => {
+            quote! { __cursor.get_array() }
+        }
+        (FieldType::Array(typ, size), Some(r#enum)) => {
+            let enum_name = r#enum.pascal_case();
+            let deserialiser = primitive_type_to_deserialiser(typ);
+            let array = (0..usize::from(size)).map(|_| {
+                quote! {
+                    #enum_name::try_from_bits(
+                        __cursor.#deserialiser().try_into().expect("checked")
+                    )?
+                }
+            });
+
+            quote! {
+                [ #(#array),*, ]
+            }
+        }
+        (FieldType::Array(typ, size), None) => {
+            let deserialiser = primitive_type_to_deserialiser(typ);
+            let array = (0..usize::from(size)).map(|_| {
+                quote! {
+                    __cursor.#deserialiser()
+                }
+            });
+
+            quote! {
+                [ #(#array),*, ]
+            }
+        }
+    }
+}
+
+fn is_string_field(field: &model::Field) -> bool {
+    field.r#enum.is_none() && matches!(field.r#type, FieldType::Array(PrimitiveType::Char, _))
+}
+
+/// Names of the check and the setter of the invalid value of a field.
+fn invalid_accessor_names(field: &model::Field) -> (proc_macro2::Ident, proc_macro2::Ident) {
+    let name = field.name.snake_case();
+    (
+        format_ident!("{}_is_invalid", name),
+        format_ident!("with_{}_invalid", name),
+    )
+}
+
+/// Names of the getter and the setter of a string field.
+fn string_accessor_names(field: &model::Field) -> (proc_macro2::Ident, proc_macro2::Ident) {
+    let name = field.name.snake_case();
+    (
+        format_ident!("{}_as_str", name),
+        format_ident!("set_{}_str", name),
+    )
+}
+
+/// Whether the field is `uint8_t_mavlink_version`, which holds the version
+/// of the dialect.
+fn is_mavlink_version_field(field: &model::Field) -> bool {
+    field.r#type == FieldType::Primitive(PrimitiveType::Uint8MavlinkVersion)
+}
+
+fn option_as_rust<T: ToTokens>(value: Option<T>) -> TokenStream {
+    match value {
+        Some(value) => quote! { Some(#value) },
+        None => quote! { None },
+    }
+}
+
+/// Whether `print_format` of the field asks for a hexadecimal integer.
+fn is_hex_field(field: &model::Field) -> bool {
+    let is_integer = match field.r#type {
+        FieldType::Primitive(PrimitiveType::Float | PrimitiveType::Double) => false,
+        FieldType::Primitive(_) => field.r#enum.is_none(),
+        FieldType::Array(_, _) => false,
+    };
+    is_integer
+        && field
+            .print_format
+            .as_deref()
+            .is_some_and(|format| format.contains('x') || format.contains('X'))
+}
+
 fn can_derive_eq(message: &model::Message) -> bool {
     let has_floats = message
         .fields
@@ -864,6 +3883,17 @@ impl ModCodegen {
         })
     }
 
+    /// Adds a module, which is also enabled by the features of the modules
+    /// that include it.
+    pub fn add_included_mod(&mut self, name: &str, dependents: &[String]) {
+        let ident = format_ident!("{}", name);
+
+        self.stream.extend(quote! {
+            #[cfg(any(feature = #name #(, feature = #dependents)*))]
+            pub mod #ident;
+        })
+    }
+
     pub fn finish(self) -> TokenStream {
         self.stream
     }
@@ -38,31 +38,41 @@ pub fn snake_case(s: &str) -> String {
     transformed
 }
 
-pub struct PascalCase<'a>(&'a model::Ident);
+/// Converts the name into a valid Rust type or variant name.
+///
+/// The name is not stripped of any prefixes, so entries are named
+/// deterministically regardless of whether they repeat the enum name.
+pub fn pascal_case(s: &str) -> String {
+    let snake_case = snake_case(s);
+
+    let mut transformed = String::with_capacity(snake_case.len());
+    let mut capitalize_next = true;
+
+    for c in snake_case.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            transformed.extend(c.to_uppercase());
+            capitalize_next = false;
+        } else {
+            transformed.extend(c.to_lowercase());
+        }
+    }
 
-impl<'a> std::fmt::Display for PascalCase<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use std::fmt::Write;
+    // Leading underscores are dropped, so names like `_1ST` would produce
+    // invalid identifiers
+    if matches!(transformed.chars().next(), Some('0'..='9')) {
+        transformed.insert(0, '_');
+    }
 
-        let snake_case = snake_case(self.0.as_ref()).to_string();
+    model::KeywordEscape::of(&transformed).apply(&transformed)
+}
 
-        let mut capitalize_next = true;
+pub struct PascalCase<'a>(&'a model::Ident);
 
-        for c in snake_case.chars() {
-            if c == '_' {
-                capitalize_next = true;
-            } else if capitalize_next {
-                for upper_c in c.to_uppercase() {
-                    f.write_char(upper_c)?;
-                }
-                capitalize_next = false;
-            } else {
-                for lower_c in c.to_lowercase() {
-                    f.write_char(lower_c)?;
-                }
-            }
-        }
-        Ok(())
+impl<'a> std::fmt::Display for PascalCase<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&pascal_case(self.0.as_ref()))
     }
 }
 
@@ -71,19 +81,36 @@ pub struct SnakeCase<'a>(&'a model::Ident);
 impl<'a> std::fmt::Display for SnakeCase<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let transformed = snake_case(self.0.as_ref());
+        f.write_str(&model::KeywordEscape::of(&transformed).apply(&transformed))
+    }
+}
 
-        const RESERVED_KEYWORDS: [&str; 38] = [
-            "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn",
-            "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
-            "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
-            "unsafe", "use", "where", "while", "async", "await", "dyn",
-        ];
+/// Returns the key, which serde uses for the field generated from the name.
+pub fn serde_key(ident: &model::Ident) -> String {
+    let name = SnakeCase(ident).to_string();
+    match name.strip_prefix("r#") {
+        Some(unraw) => unraw.to_owned(),
+        None => name,
+    }
+}
 
-        if RESERVED_KEYWORDS.contains(&transformed.as_str()) {
-            write!(f, "r#{}", transformed)
-        } else {
-            write!(f, "{}", transformed)
-        }
+/// Name of the variant or the constant of an enum entry, which is either
+/// converted to PascalCase or kept as in the definition.
+pub fn entry_ident(entry: &model::Ident, preserve: bool) -> proc_macro2::Ident {
+    if preserve {
+        format_ident!("{}", entry.keyword_escape().apply(entry.as_ref()))
+    } else {
+        entry.pascal_case()
+    }
+}
+
+/// Name of the struct field of a message field, which is either converted
+/// to snake_case or kept as in the definition.
+pub fn field_ident(field: &model::Ident, preserve: bool) -> proc_macro2::Ident {
+    if preserve {
+        format_ident!("{}", field.keyword_escape().apply(field.as_ref()))
+    } else {
+        field.snake_case()
     }
 }
 
@@ -26,6 +26,94 @@ fn test_pascal_case() {
     }
 }
 
+#[test]
+fn test_pascal_case_enum_entries() {
+    let cases = [
+        // prefixed with the enum name
+        ("MAV_STATE_ACTIVE", "MavStateActive"),
+        // exactly the enum name
+        ("MAV_STATE", "MavState"),
+        // partially prefixed
+        ("MAV_ACTIVE", "MavActive"),
+        // not prefixed
+        ("ACTIVE", "Active"),
+        ("_1ST", "_1St"),
+        ("SELF", "Self_"),
+    ];
+
+    for (input, expected) in cases {
+        let ident: model::Ident = input.parse().unwrap();
+        let name = ident.pascal_case();
+
+        assert_eq!(name.to_string(), expected, "case {:?}", input);
+        syn::parse_str::<syn::Ident>(&name.to_string())
+            .unwrap_or_else(|err| panic!("case {input:?} is not a valid identifier: {err}"));
+    }
+}
+
+#[test]
+fn test_preserve_names() {
+    let enum_def = model::Enum {
+        name: "MAV_STATE".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: ["MAV_STATE_UNINIT", "MAV_STATE_ACTIVE", "self"]
+            .into_iter()
+            .zip(0..)
+            .map(|(name, value)| model::Entry {
+                name: name.parse().unwrap(),
+                description: None,
+                dev_status: None,
+                value,
+                params: vec![],
+            })
+            .collect(),
+    };
+    let variants = |codegen: &Codegen| {
+        let stream = codegen.emit_regular_enum(&enum_def);
+        syn::parse2::<syn::ItemEnum>(stream)
+            .unwrap()
+            .variants
+            .iter()
+            .map(|variant| variant.ident.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        variants(&Codegen::default()),
+        ["MavStateUninit", "MavStateActive", "Self_"]
+    );
+
+    let codegen = Codegen {
+        preserve_names: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        variants(&codegen),
+        ["MAV_STATE_UNINIT", "MAV_STATE_ACTIVE", "self_"]
+    );
+
+    let field: model::Ident = "gpsFix".parse().unwrap();
+    assert_eq!(codegen.field_ident(&field).to_string(), "gpsFix");
+    assert_eq!(
+        Codegen::default().field_ident(&field).to_string(),
+        "gps_fix"
+    );
+
+    let module = model::MavlinkModule {
+        path: "test.xml".into(),
+        version: None,
+        dialect: None,
+        enums: vec![],
+        messages: vec![],
+        reexports: vec![],
+    };
+    let prelude = codegen.emit_prelude(&module).to_string();
+    let allow = quote! { #![allow(non_camel_case_types, non_snake_case)] }.to_string();
+    assert!(prelude.contains(&allow), "prelude: {prelude}");
+}
+
 #[test]
 fn test_snake_case() {
     let cases = [
@@ -37,6 +125,9 @@ fn test_snake_case() {
         ("gpsOffsetLat", "gps_offset_lat"),
         ("sue_GPS_TYPE", "sue_gps_type"),
         ("Type", "r#type"),
+        ("match", "r#match"),
+        ("self", "self_"),
+        ("crate", "crate_"),
     ];
 
     for (input, expected) in cases {
@@ -63,7 +154,38 @@ fn test_description_only() {
 fn test_description_with_leading_newline() {
     let description = "    A test\n    description\n\talso this";
     let result = Codegen::default().emit_doc(Some(description), None);
-    let expected = quote! { #[doc = "A test\ndescription\nalso this"] };
+    let expected = quote! {
+        #[doc = "A test"]
+        #[doc = "description"]
+        #[doc = "also this"]
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_description_with_markdown() {
+    let description = "Set [foo] to `MAV_CMD[0]`, see [the guide](https://mavlink.io) and `x.";
+    let result = Codegen::default().emit_doc(Some(description), None);
+    let expected = quote! {
+        #[doc = "Set \\[foo\\] to `MAV_CMD[0]`, see [the guide](https://mavlink.io) and \\`x."]
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_long_description_is_wrapped() {
+    let description = format!("{}\n\n\n    Second   paragraph.", "word ".repeat(30));
+    let result = Codegen::default().emit_doc(Some(&description), None);
+    let first = "word ".repeat(20);
+    let first = first.trim_end();
+    let second = "word ".repeat(10);
+    let second = second.trim_end();
+    let expected = quote! {
+        #[doc = #first]
+        #[doc = #second]
+        #[doc = ""]
+        #[doc = "Second paragraph."]
+    };
     assert_eq!(result.to_string(), expected.to_string());
 }
 
@@ -74,7 +196,7 @@ fn test_wip_status_only() {
         description: Some("Use this fancy thing".to_owned()),
     };
     let result = Codegen::default().emit_doc(None, Some(&dev_status));
-    let expected = quote! { #[doc = "WIP since 2024-09-02 - Use this fancy thing"] };
+    let expected = quote! { #[doc = "**WORK IN PROGRESS** since 2024-09-02 — may change without notice. Use this fancy thing"] };
     assert_eq!(result.to_string(), expected.to_string());
 }
 
@@ -85,7 +207,8 @@ fn test_wip_status_without_since() {
         description: Some("   Use fancy pants   ".to_owned()),
     };
     let result = Codegen::default().emit_doc(None, Some(&dev_status));
-    let expected = quote! { #[doc = "WIP - Use fancy pants"] };
+    let expected =
+        quote! { #[doc = "**WORK IN PROGRESS** — may change without notice. Use fancy pants"] };
     assert_eq!(result.to_string(), expected.to_string());
 }
 
@@ -96,7 +219,8 @@ fn test_wip_status_without_description() {
         description: None,
     };
     let result = Codegen::default().emit_doc(None, Some(&dev_status));
-    let expected = quote! { #[doc = "WIP since 2024-09-02"] };
+    let expected =
+        quote! { #[doc = "**WORK IN PROGRESS** since 2024-09-02 — may change without notice."] };
     assert_eq!(result.to_string(), expected.to_string());
 }
 
@@ -132,7 +256,11 @@ fn test_description_and_wip_status() {
         description: Some("Work in progress".to_owned()),
     };
     let result = Codegen::default().emit_doc(Some(description), Some(&dev_status));
-    let expected = quote! { #[doc = "WIP since 2024-09-02 - Work in progress\n\nThis is a test description."] };
+    let expected = quote! {
+        #[doc = "**WORK IN PROGRESS** since 2024-09-02 — may change without notice. Work in progress"]
+        #[doc = ""]
+        #[doc = "This is a test description."]
+    };
     assert_eq!(result.to_string(), expected.to_string());
 }
 
@@ -140,7 +268,7 @@ fn test_description_and_wip_status() {
 fn test_description_with_tabs() {
     let description = "This is a\ttest\tdescription with tabs.";
     let result = Codegen::default().emit_doc(Some(description), None);
-    let expected = quote! { #[doc = "This is a    test    description with tabs."] };
+    let expected = quote! { #[doc = "This is a test description with tabs."] };
     assert_eq!(result.to_string(), expected.to_string());
 }
 
@@ -169,12 +297,14 @@ fn test_basic_enum() {
                 description: None,
                 dev_status: None,
                 value: 1,
+                params: vec![],
             },
             model::Entry {
                 name: "Two".parse().unwrap(),
                 description: None,
                 dev_status: None,
                 value: 2,
+                params: vec![],
             },
         ],
     };
@@ -206,6 +336,7 @@ fn test_enum_with_description() {
             description: Some("First entry".to_string()),
             dev_status: None,
             value: 0,
+            params: vec![],
         }],
     };
 
@@ -244,13 +375,14 @@ fn test_enum_with_dev_status() {
                 description: Some("Use NewEntry instead".to_string()),
             }),
             value: 0,
+            params: vec![],
         }],
     };
 
     let result = Codegen::default().emit_regular_enum(&enum_def);
 
     let expected = quote! {
-        #[doc = "WIP since v1.0.0 - Work in progress"]
+        #[doc = "**WORK IN PROGRESS** since v1.0.0 — may change without notice. Work in progress"]
         #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
         #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
         #[repr(u8)]
@@ -263,6 +395,384 @@ fn test_enum_with_dev_status() {
     assert_eq!(result.to_string(), expected.to_string());
 }
 
+#[test]
+fn test_enum_entry_with_params() {
+    let enum_def = model::Enum {
+        name: "MAV_CMD".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: vec![model::Entry {
+            name: "MAV_CMD_NAV_WAYPOINT".parse().unwrap(),
+            description: Some("Navigate to waypoint.".to_string()),
+            dev_status: None,
+            value: 16,
+            params: vec![
+                model::Param {
+                    index: 1,
+                    label: Some("Hold".to_string()),
+                    units: Some("s".to_string()),
+                    min_value: Some(0.5),
+                    max_value: None,
+                    description: Some("Hold time.".to_string()),
+                },
+                model::Param {
+                    index: 4,
+                    label: None,
+                    units: None,
+                    min_value: Some(-180.0),
+                    max_value: Some(180.0),
+                    description: Some("Desired yaw\n    angle.".to_string()),
+                },
+            ],
+        }],
+    };
+
+    let result = Codegen::default().emit_regular_enum(&enum_def);
+
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[repr(u8)]
+        pub enum MavCmd {
+            #[doc = "Navigate to waypoint."]
+            #[doc = ""]
+            #[doc = "# Parameters"]
+            #[doc = ""]
+            #[doc = "- **1** Hold (s, min: 0): Hold time."]
+            #[doc = "- **4** (min: -180, max: 180): Desired yaw angle."]
+            MavCmdNavWaypoint = 16,
+        }
+    };
+
+    assert_eq!(result.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_command_labeled_params() {
+    let enum_def = model::Enum {
+        name: "MAV_CMD".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: vec![
+            model::Entry {
+                name: "MAV_CMD_NAV_WAYPOINT".parse().unwrap(),
+                description: None,
+                dev_status: None,
+                value: 16,
+                params: vec![
+                    model::Param {
+                        index: 1,
+                        label: Some("Hold".to_string()),
+                        units: None,
+                        min_value: None,
+                        max_value: None,
+                        description: None,
+                    },
+                    model::Param {
+                        index: 7,
+                        label: Some(" Altitude ".to_string()),
+                        units: None,
+                        min_value: None,
+                        max_value: None,
+                        description: None,
+                    },
+                ],
+            },
+            model::Entry {
+                name: "MAV_CMD_NAV_RETURN_TO_LAUNCH".parse().unwrap(),
+                description: None,
+                dev_status: None,
+                value: 20,
+                params: vec![],
+            },
+        ],
+    };
+
+    let result = Codegen::default().emit_enum_param_labels(&enum_def);
+    let expected = quote! {
+        impl MavCmd {
+            /// Returns the labels of `param1` to `param7` of the command,
+            /// `None` for parameters without a label.
+            pub fn param_labels(self) -> [Option<&'static str>; 7] {
+                match self.bits() {
+                    16 => [Some("Hold"), None, None, None, None, None, Some("Altitude")],
+                    _ => [None; 7],
+                }
+            }
+        }
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+
+    let mut fields = vec![Field {
+        name: "command".parse().unwrap(),
+        r#type: FieldType::Primitive(PrimitiveType::Uint16),
+        r#enum: Some("MAV_CMD".parse().unwrap()),
+        ..default_field()
+    }];
+    fields.extend((1..=7).map(|index| Field {
+        name: format!("param{index}").parse().unwrap(),
+        r#type: FieldType::Primitive(PrimitiveType::Float),
+        ..default_field()
+    }));
+    let message = model::Message {
+        name: "COMMAND_LONG".parse().unwrap(),
+        id: 76,
+        dev_status: None,
+        description: None,
+        fields,
+        extension_fields: vec![],
+    };
+
+    let result =
+        Codegen::default().emit_command_labeled_params(&message, std::slice::from_ref(&enum_def));
+    let expected = quote! {
+        impl CommandLong {
+            /// Returns the values of the command parameters with their
+            /// labels. Parameters without a label are skipped, so unknown
+            /// commands or commands without metadata return nothing.
+            pub fn labeled_params(&self) -> Vec<(&'static str, f32)> {
+                let values = [
+                    self.param1,
+                    self.param2,
+                    self.param3,
+                    self.param4,
+                    self.param5,
+                    self.param6,
+                    self.param7
+                ];
+                self.command
+                    .param_labels()
+                    .into_iter()
+                    .zip(values)
+                    .filter_map(|(label, value)| Some((label?, value)))
+                    .collect()
+            }
+        }
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+
+    // Without parameter metadata there is no `param_labels` to call
+    let enum_def = model::Enum {
+        entries: enum_def.entries[1..].to_vec(),
+        ..enum_def
+    };
+    assert!(Codegen::default()
+        .emit_enum_param_labels(&enum_def)
+        .is_empty());
+    assert!(Codegen::default()
+        .emit_command_labeled_params(&message, std::slice::from_ref(&enum_def))
+        .is_empty());
+}
+
+#[test]
+fn test_enum_serde_options() {
+    let enum_def = model::Enum {
+        name: "MAV_STATE".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: vec![model::Entry {
+            name: "MAV_STATE_UNINIT".parse().unwrap(),
+            description: None,
+            dev_status: None,
+            value: 0,
+            params: vec![],
+        }],
+    };
+
+    let codegen = Codegen {
+        serde_snake_case_enums: true,
+        ..Default::default()
+    };
+    let result = codegen.emit_regular_enum(&enum_def);
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+        #[repr(u8)]
+        pub enum MavState {
+            MavStateUninit = 0,
+        }
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+
+    let codegen = Codegen {
+        serde_snake_case_enums: true,
+        serde_skip: vec!["MAV_STATE".to_owned()],
+        ..Default::default()
+    };
+    let result = codegen.emit_regular_enum(&enum_def);
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+        #[repr(u8)]
+        pub enum MavState {
+            MavStateUninit = 0,
+        }
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_enum_defmt() {
+    let enum_def = model::Enum {
+        name: "MAV_STATE".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: vec![model::Entry {
+            name: "MAV_STATE_UNINIT".parse().unwrap(),
+            description: None,
+            dev_status: None,
+            value: 0,
+            params: vec![],
+        }],
+    };
+
+    let result = Codegen::default().emit_regular_enum(&enum_def);
+    assert!(!result.to_string().contains("defmt"));
+
+    let codegen = Codegen {
+        defmt: true,
+        ..Default::default()
+    };
+    let result = codegen.emit_regular_enum(&enum_def);
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[repr(u8)]
+        pub enum MavState {
+            MavStateUninit = 0,
+        }
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_enum_arbitrary() {
+    let enum_def = model::Enum {
+        name: "MAV_STATE".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: vec![model::Entry {
+            name: "MAV_STATE_UNINIT".parse().unwrap(),
+            description: None,
+            dev_status: None,
+            value: 0,
+            params: vec![],
+        }],
+    };
+
+    let result = Codegen::default().emit_regular_enum(&enum_def);
+    assert!(!result.to_string().contains("arbitrary"));
+
+    let codegen = Codegen {
+        arbitrary: true,
+        ..Default::default()
+    };
+    let result = codegen.emit_regular_enum(&enum_def);
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+        #[repr(u8)]
+        pub enum MavState {
+            MavStateUninit = 0,
+        }
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_enum_log_level() {
+    let enum_def = model::Enum {
+        name: "MAV_SEVERITY".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: vec![model::Entry {
+            name: "MAV_SEVERITY_EMERGENCY".parse().unwrap(),
+            description: None,
+            dev_status: None,
+            value: 0,
+            params: vec![],
+        }],
+    };
+
+    assert!(Codegen::default().emit_enum_log_level(&enum_def).is_empty());
+
+    let codegen = Codegen {
+        log_severity_enum: Some("MAV_SEVERITY".to_owned()),
+        ..Default::default()
+    };
+    let result = codegen.emit_enum_log_level(&enum_def);
+    let expected = quote! {
+        #[cfg(feature = "log")]
+        impl From<MavSeverity> for log::Level {
+            fn from(severity: MavSeverity) -> Self {
+                match severity.bits() {
+                    0..=3 => log::Level::Error,
+                    4 => log::Level::Warn,
+                    5 | 6 => log::Level::Info,
+                    _ => log::Level::Debug,
+                }
+            }
+        }
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+
+    let enum_def = model::Enum {
+        name: "MAV_STATE".parse().unwrap(),
+        ..enum_def
+    };
+    assert!(codegen.emit_enum_log_level(&enum_def).is_empty());
+}
+
+#[test]
+fn test_bitmask_enum_defmt() {
+    let enum_def = model::Enum {
+        name: "TestFlags".parse().unwrap(),
+        bitmask: true,
+        description: None,
+        dev_status: None,
+        entries: vec![model::Entry {
+            name: "inferno".parse().unwrap(),
+            description: None,
+            dev_status: None,
+            value: 1,
+            params: vec![],
+        }],
+    };
+
+    let codegen = Codegen {
+        defmt: true,
+        ..Default::default()
+    };
+    let result = codegen.emit_bitmask_enum(&enum_def);
+
+    let expected = quote! {
+        bitflags! {
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+            pub struct TestFlags: u8 {
+                const Inferno = 1;
+            }
+        }
+
+        #[cfg(feature = "defmt")]
+        impl defmt::Format for TestFlags {
+            fn format(&self, f: defmt::Formatter) {
+                defmt::write!(f, "TestFlags({=?})", self.bits())
+            }
+        }
+    };
+
+    assert_eq!(result.to_string(), expected.to_string());
+}
+
 #[test]
 fn test_enum_with_larger_values() {
     let enum_def = model::Enum {
@@ -276,12 +786,14 @@ fn test_enum_with_larger_values() {
                 description: None,
                 dev_status: None,
                 value: 0,
+                params: vec![],
             },
             model::Entry {
                 name: "LARGE".parse().unwrap(),
                 description: None,
                 dev_status: None,
                 value: 1000000,
+                params: vec![],
             },
         ],
     };
@@ -314,12 +826,14 @@ fn test_basic_bitmask_enum() {
                 description: None,
                 dev_status: None,
                 value: 1,
+                params: vec![],
             },
             model::Entry {
                 name: "dust2".parse().unwrap(),
                 description: None,
                 dev_status: None,
                 value: 2,
+                params: vec![],
             },
         ],
     };
@@ -328,7 +842,7 @@ fn test_basic_bitmask_enum() {
 
     let expected = quote! {
         bitflags! {
-            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
             #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
             pub struct TestFlags: u8 {
                 const Inferno = 1;
@@ -352,6 +866,7 @@ fn test_bitmask_enum_with_description() {
             description: Some("First flag".to_string()),
             dev_status: None,
             value: 1,
+            params: vec![],
         }],
     };
 
@@ -360,7 +875,7 @@ fn test_bitmask_enum_with_description() {
     let expected = quote! {
         bitflags! {
             #[doc = "This is a test bitmask enum"]
-            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
             #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
             pub struct DescFlags: u8 {
                 #[doc = "First flag"]
@@ -391,6 +906,7 @@ fn test_bitmask_enum_with_dev_status() {
                 description: Some("Use NewFlag instead".to_string()),
             }),
             value: 1,
+            params: vec![],
         }],
     };
 
@@ -398,8 +914,8 @@ fn test_bitmask_enum_with_dev_status() {
 
     let expected = quote! {
         bitflags! {
-            #[doc = "WIP since v1.0.0 - Work in progress"]
-            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            #[doc = "**WORK IN PROGRESS** since v1.0.0 — may change without notice. Work in progress"]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
             #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
             pub struct StatusFlags: u8 {
                 #[deprecated(note = "Since v2.0.0, replaced by NewFlag. Use NewFlag instead")]
@@ -424,12 +940,14 @@ fn test_bitmask_enum_with_larger_values() {
                 description: None,
                 dev_status: None,
                 value: 1,
+                params: vec![],
             },
             model::Entry {
                 name: "Large".parse().unwrap(),
                 description: None,
                 dev_status: None,
                 value: 1 << 31,
+                params: vec![],
             },
         ],
     };
@@ -438,7 +956,7 @@ fn test_bitmask_enum_with_larger_values() {
 
     let expected = quote! {
         bitflags! {
-            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
             #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
             pub struct LargeFlags: u32 {
                 const Small = 1;
@@ -463,12 +981,14 @@ fn test_basic_enum_default_impl() {
                 description: None,
                 dev_status: None,
                 value: 0,
+                params: vec![],
             },
             model::Entry {
                 name: "Second".parse().unwrap(),
                 description: None,
                 dev_status: None,
                 value: 1,
+                params: vec![],
             },
         ],
     };
@@ -501,6 +1021,7 @@ fn test_bitmask_enum_converters() {
             description: None,
             dev_status: None,
             value: 0,
+            params: vec![],
         }],
     };
 
@@ -509,11 +1030,13 @@ fn test_bitmask_enum_converters() {
     let expected = quote! {
         impl TestFlags {
             #[allow(unused)]
-            fn try_from_bits(value: u8) -> Result<Self, ParserError> {
-                Self::from_bits(value).ok_or(ParserError::InvalidFlag {
-                    flag_type: "TestFlags",
-                    value: value as u32,
-                })
+            pub(crate) fn try_from_bits(value: u8) -> Result<Self, ParserError> {
+                Ok(Self::from_bits_retain(value))
+            }
+
+            /// Returns the bits without an entry in the definition.
+            pub fn unknown_bits(&self) -> u8 {
+                self.bits() & !Self::all().bits()
             }
         }
     };
@@ -533,6 +1056,7 @@ fn test_regular_enum_converters() {
             description: None,
             dev_status: None,
             value: 0,
+            params: vec![],
         }],
     };
 
@@ -541,7 +1065,7 @@ fn test_regular_enum_converters() {
     let expected = quote! {
         impl TestEnum {
             #[allow(unused)]
-            fn try_from_bits(value: u8) -> Result<Self, ParserError> {
+            pub(crate) fn try_from_bits(value: u8) -> Result<Self, ParserError> {
                 FromPrimitive::from_u8(value).ok_or(ParserError::InvalidEnum {
                     enum_type: "TestEnum",
                     value: value as u32,
@@ -569,12 +1093,14 @@ fn test_emit_regular_enum() {
                 description: Some("First entry".to_string()),
                 dev_status: None,
                 value: 0,
+                params: vec![],
             },
             model::Entry {
                 name: "Second".parse().unwrap(),
                 description: None,
                 dev_status: None,
                 value: 1,
+                params: vec![],
             },
         ],
     };
@@ -604,7 +1130,7 @@ fn test_emit_regular_enum() {
 
         impl CoolEnum {
             #[allow(unused)]
-            fn try_from_bits(value: u8) -> Result<Self, ParserError> {
+            pub(crate) fn try_from_bits(value: u8) -> Result<Self, ParserError> {
                 FromPrimitive::from_u8(value).ok_or(ParserError::InvalidEnum {
                     enum_type: "COOL_ENUM",
                     value: value as u32,
@@ -614,11 +1140,302 @@ fn test_emit_regular_enum() {
                 self as _
             }
         }
+
+        impl TryFrom<u8> for CoolEnum {
+            type Error = InvalidEnumValue;
+
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                Self::try_from_bits(value).map_err(|_| InvalidEnumValue {
+                    enum_name: "COOL_ENUM",
+                    value: u64::from(value),
+                })
+            }
+        }
+
+        impl From<CoolEnum> for u8 {
+            fn from(value: CoolEnum) -> Self {
+                value.bits()
+            }
+        }
+
+        impl PartialEq<u8> for CoolEnum {
+            fn eq(&self, other: &u8) -> bool {
+                self.bits() == *other
+            }
+        }
+
+        impl PartialEq<CoolEnum> for u8 {
+            fn eq(&self, other: &CoolEnum) -> bool {
+                *self == other.bits()
+            }
+        }
     };
 
     assert_eq!(result.to_string(), expected.to_string());
 }
 
+#[test]
+fn test_non_exhaustive_enums() {
+    let enum_def = model::Enum {
+        name: "COOL_ENUM".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: vec![model::Entry {
+            name: "First".parse().unwrap(),
+            description: None,
+            dev_status: None,
+            value: 0,
+            params: vec![],
+        }],
+    };
+    let messages = test_mav_messages();
+    let mav_message = format_ident!("MavMessage");
+    let attr = quote! { #[non_exhaustive] }.to_string();
+
+    let codegen = Codegen::default();
+    assert!(!codegen
+        .emit_regular_enum(&enum_def)
+        .to_string()
+        .contains(&attr));
+    assert!(!codegen
+        .emit_mav_message_def(&messages, &mav_message)
+        .to_string()
+        .contains(&attr));
+
+    let codegen = Codegen {
+        non_exhaustive_enums: true,
+        ..Default::default()
+    };
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[repr(u8)]
+        #[non_exhaustive]
+        pub enum CoolEnum {
+            First = 0,
+        }
+    };
+    assert_eq!(
+        codegen.emit_regular_enum(&enum_def).to_string(),
+        expected.to_string()
+    );
+    assert_eq!(
+        codegen
+            .emit_mav_message_def(&messages, &mav_message)
+            .to_string()
+            .matches(&attr)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_emit_open_enum() {
+    let enum_def = model::Enum {
+        name: "COOL_ENUM".parse().unwrap(),
+        bitmask: false,
+        description: Some("A test enum".to_string()),
+        dev_status: None,
+        entries: vec![
+            model::Entry {
+                name: "First".parse().unwrap(),
+                description: Some("First entry".to_string()),
+                dev_status: None,
+                value: 0,
+                params: vec![],
+            },
+            model::Entry {
+                name: "Second".parse().unwrap(),
+                description: None,
+                dev_status: None,
+                value: 1,
+                params: vec![],
+            },
+        ],
+    };
+
+    let codegen = Codegen {
+        open_enums: true,
+        ..Default::default()
+    };
+    let result = codegen.emit_enum(&enum_def);
+
+    let expected = quote! {
+        #[doc = "A test enum"]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[repr(transparent)]
+        pub struct CoolEnum(pub u8);
+
+        #[allow(non_upper_case_globals)]
+        impl CoolEnum {
+            #[doc = "First entry"]
+            pub const First: Self = Self(0);
+            pub const Second: Self = Self(1);
+        }
+
+        impl CoolEnum {
+            pub const DEFAULT: Self = Self::First;
+        }
+
+        impl Default for CoolEnum {
+            fn default() -> Self {
+                Self::DEFAULT
+            }
+        }
+
+        impl CoolEnum {
+            #[allow(unused)]
+            pub(crate) fn try_from_bits(value: u8) -> Result<Self, ParserError> {
+                Ok(Self(value))
+            }
+
+            pub fn bits(self) -> u8 {
+                self.0
+            }
+        }
+
+        impl From<u8> for CoolEnum {
+            fn from(value: u8) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<CoolEnum> for u8 {
+            fn from(value: CoolEnum) -> Self {
+                value.bits()
+            }
+        }
+
+        impl PartialEq<u8> for CoolEnum {
+            fn eq(&self, other: &u8) -> bool {
+                self.bits() == *other
+            }
+        }
+
+        impl PartialEq<CoolEnum> for u8 {
+            fn eq(&self, other: &CoolEnum) -> bool {
+                *self == other.bits()
+            }
+        }
+    };
+
+    assert_eq!(result.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_compact_enum() {
+    let enum_def = model::Enum {
+        name: "COOL_ENUM".parse().unwrap(),
+        bitmask: false,
+        description: Some("A test enum".to_string()),
+        dev_status: None,
+        entries: vec![model::Entry {
+            name: "Only".parse().unwrap(),
+            description: Some("The only entry".to_string()),
+            dev_status: None,
+            value: 3,
+            params: vec![],
+        }],
+    };
+
+    let codegen = Codegen {
+        compact_single_entry_enums: true,
+        ..Default::default()
+    };
+    let result = codegen.emit_enum(&enum_def);
+
+    let expected = quote! {
+        #[doc = "A test enum"]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct CoolEnum;
+
+        #[allow(non_upper_case_globals)]
+        impl CoolEnum {
+            #[doc = "The only entry"]
+            pub const Only: Self = Self;
+        }
+
+        impl CoolEnum {
+            pub const DEFAULT: Self = Self::Only;
+        }
+
+        impl Default for CoolEnum {
+            fn default() -> Self {
+                Self::DEFAULT
+            }
+        }
+
+        impl CoolEnum {
+            #[allow(unused)]
+            pub(crate) fn try_from_bits(value: u8) -> Result<Self, ParserError> {
+                if value == 3 {
+                    Ok(Self)
+                } else {
+                    Err(ParserError::InvalidEnum {
+                        enum_type: "COOL_ENUM",
+                        value: value as u32,
+                    })
+                }
+            }
+
+            pub fn bits(self) -> u8 {
+                3
+            }
+        }
+
+        impl TryFrom<u8> for CoolEnum {
+            type Error = InvalidEnumValue;
+
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                Self::try_from_bits(value).map_err(|_| InvalidEnumValue {
+                    enum_name: "COOL_ENUM",
+                    value: u64::from(value),
+                })
+            }
+        }
+
+        impl From<CoolEnum> for u8 {
+            fn from(value: CoolEnum) -> Self {
+                value.bits()
+            }
+        }
+
+        impl PartialEq<u8> for CoolEnum {
+            fn eq(&self, other: &u8) -> bool {
+                self.bits() == *other
+            }
+        }
+
+        impl PartialEq<CoolEnum> for u8 {
+            fn eq(&self, other: &CoolEnum) -> bool {
+                *self == other.bits()
+            }
+        }
+    };
+
+    assert_eq!(result.to_string(), expected.to_string());
+
+    // Enums with more entries and open enums keep their usual form
+    let open = Codegen {
+        compact_single_entry_enums: true,
+        open_enums: true,
+        ..Default::default()
+    };
+    assert!(!open.is_compact_enum(&enum_def));
+    let mut two_entries = enum_def.clone();
+    two_entries.entries.push(model::Entry {
+        name: "Other".parse().unwrap(),
+        description: None,
+        dev_status: None,
+        value: 4,
+        params: vec![],
+    });
+    assert!(!codegen.is_compact_enum(&two_entries));
+}
+
 #[test]
 fn test_emit_bitmask_enum() {
     let enum_def = model::Enum {
@@ -632,12 +1449,14 @@ fn test_emit_bitmask_enum() {
                 description: Some("First flag".to_string()),
                 dev_status: None,
                 value: 1,
+                params: vec![],
             },
             model::Entry {
                 name: "Flag2".parse().unwrap(),
                 description: None,
                 dev_status: None,
                 value: 2,
+                params: vec![],
             },
         ],
     };
@@ -647,7 +1466,7 @@ fn test_emit_bitmask_enum() {
     let expected = quote! {
         bitflags! {
             #[doc = "A test bitmask enum"]
-            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
             #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
             pub struct CoolBitmaskEnum: u8 {
                 #[doc = "First flag"]
@@ -668,11 +1487,25 @@ fn test_emit_bitmask_enum() {
 
         impl CoolBitmaskEnum {
             #[allow(unused)]
-            fn try_from_bits(value: u8) -> Result<Self, ParserError> {
-                Self::from_bits(value).ok_or(ParserError::InvalidFlag {
-                    flag_type: "COOL_BITMASK_ENUM",
-                    value: value as u32,
-                })
+            pub(crate) fn try_from_bits(value: u8) -> Result<Self, ParserError> {
+                Ok(Self::from_bits_retain(value))
+            }
+
+            /// Returns the bits without an entry in the definition.
+            pub fn unknown_bits(&self) -> u8 {
+                self.bits() & !Self::all().bits()
+            }
+        }
+
+        impl PartialEq<u8> for CoolBitmaskEnum {
+            fn eq(&self, other: &u8) -> bool {
+                self.bits() == *other
+            }
+        }
+
+        impl PartialEq<CoolBitmaskEnum> for u8 {
+            fn eq(&self, other: &CoolBitmaskEnum) -> bool {
+                *self == other.bits()
             }
         }
     };
@@ -680,6 +1513,145 @@ fn test_emit_bitmask_enum() {
     assert_eq!(result.to_string(), expected.to_string());
 }
 
+#[test]
+fn test_enum_variants() {
+    let enum_def = model::Enum {
+        name: "COOL_ENUM".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: vec![
+            model::Entry {
+                name: "COOL_ENUM_FIRST".parse().unwrap(),
+                description: None,
+                dev_status: None,
+                value: 0,
+                params: vec![],
+            },
+            model::Entry {
+                name: "COOL_ENUM_SECOND".parse().unwrap(),
+                description: None,
+                dev_status: None,
+                value: 2,
+                params: vec![],
+            },
+        ],
+    };
+
+    let codegen = Codegen::default();
+    assert!(codegen.emit_enum_variants(&enum_def).is_empty());
+
+    let codegen = Codegen {
+        enum_variants: true,
+        ..Default::default()
+    };
+    let expected = quote! {
+        impl CoolEnum {
+            #[doc = "All entries of the enum in the order of the definition."]
+            #[allow(deprecated)]
+            pub const fn all_variants() -> &'static [Self] {
+                &[Self::CoolEnumFirst, Self::CoolEnumSecond]
+            }
+
+            #[doc = "Name of the entry in the definition."]
+            pub fn name(&self) -> &'static str {
+                match self.bits() {
+                    0 => "COOL_ENUM_FIRST",
+                    2 => "COOL_ENUM_SECOND",
+                    _ => "",
+                }
+            }
+        }
+    };
+    assert_eq!(
+        codegen.emit_enum_variants(&enum_def).to_string(),
+        expected.to_string()
+    );
+
+    let bitmask = model::Enum {
+        bitmask: true,
+        ..enum_def
+    };
+    let result = codegen.emit_enum_variants(&bitmask).to_string();
+    assert!(
+        result.contains("Every named bit of the bitmask"),
+        "result: {result}"
+    );
+}
+
+#[test]
+fn test_enum_strings() {
+    let enum_def = model::Enum {
+        name: "COOL_ENUM".parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: None,
+        entries: vec![
+            model::Entry {
+                name: "COOL_ENUM_FIRST".parse().unwrap(),
+                description: None,
+                dev_status: None,
+                value: 0,
+                params: vec![],
+            },
+            model::Entry {
+                name: "COOL_ENUM_SECOND".parse().unwrap(),
+                description: None,
+                dev_status: None,
+                value: 2,
+                params: vec![],
+            },
+        ],
+    };
+
+    let codegen = Codegen::default();
+    assert!(codegen.emit_enum_strings(&enum_def).is_empty());
+
+    let codegen = Codegen {
+        enum_strings: true,
+        ..Default::default()
+    };
+    let expected = quote! {
+        impl core::str::FromStr for CoolEnum {
+            type Err = UnknownEnumName;
+
+            #[allow(deprecated)]
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "COOL_ENUM_FIRST" => Ok(Self::CoolEnumFirst),
+                    "COOL_ENUM_SECOND" => Ok(Self::CoolEnumSecond),
+                    _ => Err(UnknownEnumName::new("COOL_ENUM", s)),
+                }
+            }
+        }
+
+        /// Writes the name of the entry in the definition, or the value
+        /// without an entry.
+        impl core::fmt::Display for CoolEnum {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self.bits() {
+                    0 => f.write_str("COOL_ENUM_FIRST"),
+                    2 => f.write_str("COOL_ENUM_SECOND"),
+                    value => write!(f, "{value}"),
+                }
+            }
+        }
+    };
+    assert_eq!(
+        codegen.emit_enum_strings(&enum_def).to_string(),
+        expected.to_string()
+    );
+
+    let heapless = Codegen {
+        enum_strings: true,
+        collections: Collections::Heapless,
+        ..Default::default()
+    };
+    let result = heapless.emit_unknown_enum_name(&[&enum_def]).to_string();
+    let expected = quote! { pub name: heapless::String<16usize> }.to_string();
+    assert!(result.contains(&expected), "{expected} not in {result}");
+}
+
 fn default_field() -> Field {
     Field {
         name: "test".parse().unwrap(),
@@ -699,6 +1671,54 @@ fn default_field() -> Field {
     }
 }
 
+#[test]
+fn test_field_doc_style() {
+    let field = Field {
+        name: "airspeed".parse().unwrap(),
+        r#type: FieldType::Primitive(PrimitiveType::Uint16),
+        units: Some("cm/s".to_owned()),
+        increment: Some(0.5),
+        min_value: Some(0.0),
+        max_value: Some(1000.0),
+        multiplier: Some("1E-2".to_owned()),
+        default: Some(model::DefaultValue::Int(0)),
+        invalid: Some("UINT16_MAX".to_owned()),
+        description: Some("Current   airspeed.".to_owned()),
+        ..default_field()
+    };
+
+    let result = Codegen::default().emit_field_doc(&field);
+    let expected = quote! { #[doc = "Current airspeed."] };
+    assert_eq!(result.to_string(), expected.to_string());
+
+    let codegen = Codegen {
+        doc_style: DocStyle::Full,
+        ..Default::default()
+    };
+    let result = codegen.emit_field_doc(&field);
+    let expected = quote! {
+        #[doc = "Current airspeed."]
+        #[doc = ""]
+        #[doc = "- Units: `cm/s`"]
+        #[doc = "- Minimum: 0"]
+        #[doc = "- Maximum: 1000"]
+        #[doc = "- Increment: 0.5"]
+        #[doc = "- Multiplier: `1E-2`"]
+        #[doc = "- Default: 0"]
+        #[doc = "- Invalid: `UINT16_MAX`"]
+    };
+    assert_eq!(result.to_string(), expected.to_string());
+
+    let field = Field {
+        description: None,
+        units: Some("m".to_owned()),
+        ..default_field()
+    };
+    let result = codegen.emit_field_doc(&field);
+    let expected = quote! { #[doc = "- Units: `m`"] };
+    assert_eq!(result.to_string(), expected.to_string());
+}
+
 fn test_message() -> Message {
     model::Message {
         name: "COOL_TEST_MESSAGE".parse().unwrap(),
@@ -771,12 +1791,45 @@ fn test_emit_message_def() {
 }
 
 #[test]
-fn test_emit_message_def_without_eq() {
-    let codegen = Codegen::default();
+fn test_emit_message_def_extra_derives() {
+    let codegen = Codegen {
+        extra_derives: vec!["Hash".to_owned()],
+        extra_enum_derives: vec!["PartialOrd".to_owned()],
+        ..Default::default()
+    };
     let message = Message {
-        name: "NON_EQ_MESSAGE".parse().unwrap(),
+        name: "HASHED_MESSAGE".parse().unwrap(),
         id: 7,
-        dev_status: Some(DevStatus::Deprecated {
+        dev_status: None,
+        description: None,
+        fields: vec![Field {
+            name: "value".parse().unwrap(),
+            r#type: FieldType::Primitive(PrimitiveType::Uint32),
+            ..default_field()
+        }],
+        extension_fields: vec![],
+    };
+
+    let stream = codegen.emit_message_def(&message);
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[derive(Hash)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct HashedMessage {
+            pub value: u32
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_message_def_without_eq() {
+    let codegen = Codegen::default();
+    let message = Message {
+        name: "NON_EQ_MESSAGE".parse().unwrap(),
+        id: 7,
+        dev_status: Some(DevStatus::Deprecated {
             since: "2024-09".into(),
             replaced_by: "YOU".into(),
             description: None,
@@ -803,6 +1856,200 @@ fn test_emit_message_def_without_eq() {
     assert_eq!(stream.to_string(), expected.to_string());
 }
 
+#[test]
+fn test_emit_message_def_without_clone() {
+    let message = Message {
+        name: "LARGE_MESSAGE".parse().unwrap(),
+        id: 8,
+        dev_status: None,
+        description: None,
+        fields: vec![Field {
+            name: "data".parse().unwrap(),
+            r#type: FieldType::Array(PrimitiveType::Uint8, 200),
+            ..default_field()
+        }],
+        extension_fields: vec![],
+    };
+    let codegen = Codegen {
+        clone_size_threshold: Some(128),
+        ..Default::default()
+    };
+
+    let stream = codegen.emit_message_def(&message);
+    let expected = quote! {
+        #[derive(Debug, PartialEq, Eq)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct LargeMessage {
+            #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
+            pub data: [u8; 200usize]
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    // The message enum can't be cloned with a message without `Clone`
+    let messages = [message, test_message()];
+    let mav_message = format_ident!("MavMessage");
+    let stream = codegen
+        .emit_mav_message_def(&messages, &mav_message)
+        .to_string();
+    let expected = quote! { #[derive(Debug, PartialEq)] }.to_string();
+    assert!(stream.starts_with(&expected), "stream: {stream}");
+
+    let stream = codegen
+        .emit_mav_message_from_impls(&messages, &mav_message)
+        .to_string();
+    assert!(!stream.contains(&quote! { From<&LargeMessage> }.to_string()));
+    assert!(stream.contains(&quote! { From<&CoolTestMessage> }.to_string()));
+
+    let stream = Codegen::default()
+        .emit_message_def(&messages[0])
+        .to_string();
+    let expected = quote! { #[derive(Debug, Clone, Copy, PartialEq, Eq)] }.to_string();
+    assert!(stream.starts_with(&expected), "stream: {stream}");
+}
+
+#[test]
+fn test_emit_message_def_serde_renames() {
+    let codegen = Codegen::default();
+    let names = ["height", "gpsFix", "COG", "type", "self"];
+    let message = Message {
+        name: "RENAMED".parse().unwrap(),
+        id: 1,
+        dev_status: None,
+        description: None,
+        fields: names
+            .iter()
+            .map(|name| Field {
+                name: name.parse().unwrap(),
+                ..default_field()
+            })
+            .collect(),
+        extension_fields: vec![],
+    };
+
+    let stream = codegen.emit_message_def(&message);
+    let input: syn::DeriveInput = syn::parse2(stream).unwrap();
+    let syn::Data::Struct(data) = input.data else {
+        panic!("expected a struct");
+    };
+
+    let keys = data.fields.iter().map(|field| {
+        let rename = field.attrs.iter().find_map(|attr| {
+            let tokens = quote::ToTokens::to_token_stream(&attr.meta).to_string();
+            let (_, rest) = tokens.split_once("rename = \"")?;
+            Some(rest.split('"').next().unwrap().to_owned())
+        });
+        let ident = field.ident.as_ref().unwrap().to_string();
+        rename.unwrap_or_else(|| ident.trim_start_matches("r#").to_owned())
+    });
+
+    assert_eq!(keys.collect::<Vec<_>>(), names);
+}
+
+#[test]
+fn test_emit_message_def_mavlink_version() {
+    let message = Message {
+        name: "HEARTBEAT".parse().unwrap(),
+        id: 0,
+        dev_status: None,
+        description: None,
+        fields: vec![Field {
+            name: "mavlink_version".parse().unwrap(),
+            r#type: FieldType::Primitive(PrimitiveType::Uint8MavlinkVersion),
+            ..default_field()
+        }],
+        extension_fields: vec![],
+    };
+
+    let stream = Codegen::default().emit_message_def(&message);
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct Heartbeat {
+            #[doc = "Managed by the protocol: defaults to `MAVLINK_VERSION`, the version of the dialect."]
+            #[cfg_attr(feature = "serde", serde(skip_deserializing, default = "mavlink_version"))]
+            pub mavlink_version: u8
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    // No serde attributes without the derive
+    let codegen = Codegen {
+        serde_skip: vec!["HEARTBEAT".to_owned()],
+        ..Default::default()
+    };
+    let stream = codegen.emit_message_def(&message);
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct Heartbeat {
+            #[doc = "Managed by the protocol: defaults to `MAVLINK_VERSION`, the version of the dialect."]
+            pub mavlink_version: u8
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_mavlink_version_default() {
+    let message = Message {
+        name: "HEARTBEAT".parse().unwrap(),
+        id: 0,
+        dev_status: None,
+        description: None,
+        fields: vec![
+            Field {
+                name: "custom_mode".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint32),
+                ..default_field()
+            },
+            Field {
+                name: "mavlink_version".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint8MavlinkVersion),
+                ..default_field()
+            },
+        ],
+        extension_fields: vec![],
+    };
+
+    let codegen = Codegen::default();
+    let stream = codegen.emit_message_default_impl(&message);
+    let expected = quote! {
+        impl Heartbeat {
+            pub const DEFAULT: Self = Self {
+                custom_mode: 0,
+                mavlink_version: MAVLINK_VERSION
+            };
+        }
+
+        impl Default for Heartbeat {
+            fn default() -> Self {
+                Self::DEFAULT
+            }
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    let module = model::MavlinkModule {
+        path: "test.xml".into(),
+        version: Some(3),
+        dialect: None,
+        enums: vec![],
+        messages: vec![message],
+        reexports: vec![],
+    };
+    let stream = codegen.emit_module(&module).to_string();
+    let version = quote! { pub const MAVLINK_VERSION: u8 = 3u8; }.to_string();
+    assert!(stream.contains(&version), "stream: {stream}");
+
+    let module = model::MavlinkModule {
+        version: None,
+        ..module
+    };
+    let stream = codegen.emit_module(&module).to_string();
+    let version = quote! { pub const MAVLINK_VERSION: u8 = 0u8; }.to_string();
+    assert!(stream.contains(&version), "stream: {stream}");
+}
+
 #[test]
 fn test_emit_message_default_impl() {
     let codegen = Codegen::default();
@@ -827,131 +2074,1352 @@ fn test_emit_message_default_impl() {
         }
     };
 
-    assert_eq!(stream.to_string(), expected.to_string());
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_message_default_impl_with_defaults() {
+    let codegen = Codegen::default();
+    let mut message = test_message();
+    message.fields[0].default = Some(model::DefaultValue::Entry(
+        "COOL_ENUM_SECOND".parse().unwrap(),
+    ));
+    message.fields[1].default = Some(model::DefaultValue::Int(3));
+    message.fields[2].default = Some(model::DefaultValue::Int(-1));
+    message.fields.push(Field {
+        name: "ratio".parse().unwrap(),
+        r#type: FieldType::Primitive(PrimitiveType::Float),
+        default: Some(model::DefaultValue::Float(f64::NAN)),
+        ..default_field()
+    });
+    message.extension_fields[0].default = Some(model::DefaultValue::Int(16));
+
+    let stream = codegen.emit_message_default_impl(&message);
+    let expected = quote! {
+        impl CoolTestMessage {
+            pub const DEFAULT: Self = Self {
+                enum_array: [CoolEnum::CoolEnumSecond; 4usize],
+                enum_plain: CoolEnum::from_bits_retain(3),
+                plain: -1,
+                plain_array: [0; 20usize],
+                ratio: f32::NAN,
+                extension_field: [16; 8usize]
+            };
+        }
+
+        impl Default for CoolTestMessage {
+            fn default() -> Self {
+                Self::DEFAULT
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_message_serialize_payload() {
+    let codegen = Codegen::default();
+    let message = test_message();
+
+    let stream = codegen.emit_message_serialize_payload(&message);
+    let expected = quote! {
+        impl CoolTestMessage {
+            /// Serialises the payload into the buffer and returns the number
+            /// of used bytes, trailing zeroes are truncated for v2.
+            pub fn serialize_payload(
+                &self,
+                version: MavlinkVersion,
+                buf: &mut [u8],
+            ) -> Result<usize, BufferTooSmall> {
+                let available = buf.len();
+                let payload = buf.get_mut(..Self::ENCODED_LEN).ok_or(BufferTooSmall {
+                    required: Self::ENCODED_LEN,
+                    available,
+                })?;
+                Ok(self.ser(version, payload))
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_message_inline() {
+    let message = test_message();
+
+    let stream = Codegen::default()
+        .emit_message_message_data_impl(&message, &format_ident!("MavMessage"))
+        .to_string();
+    assert!(!stream.contains("inline"), "stream: {stream}");
+
+    let codegen = Codegen {
+        inline: Some(Inline::Hint),
+        ..Default::default()
+    };
+    let stream = codegen
+        .emit_message_message_data_impl(&message, &format_ident!("MavMessage"))
+        .to_string();
+    let expected = [
+        quote! { #[inline] fn ser(&self, version: MavlinkVersion, bytes: &mut [u8]) -> usize },
+        quote! { #[inline] fn deser(version: MavlinkVersion, __input: &[u8]) },
+    ];
+    for expected in expected {
+        let expected = expected.to_string();
+        assert!(stream.contains(&expected), "{expected} not in {stream}");
+    }
+
+    let codegen = Codegen {
+        inline: Some(Inline::Always),
+        ..Default::default()
+    };
+    let stream = codegen.emit_message_serialize_payload(&message).to_string();
+    let expected = quote! { #[inline(always)] pub fn serialize_payload }.to_string();
+    assert!(stream.contains(&expected), "{expected} not in {stream}");
+}
+
+#[test]
+fn test_emit_message_fmt_telemetry() {
+    let message = model::Message {
+        name: "SYSTEM_TIME".parse().unwrap(),
+        id: 2,
+        dev_status: None,
+        description: None,
+        fields: vec![
+            Field {
+                name: "time_boot_ms".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint32),
+                units: Some("ms".into()),
+                ..default_field()
+            },
+            Field {
+                name: "flags".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint16),
+                print_format: Some("0x%04x".into()),
+                ..default_field()
+            },
+            Field {
+                name: "text".parse().unwrap(),
+                r#type: FieldType::Array(PrimitiveType::Char, 8),
+                ..default_field()
+            },
+        ],
+        extension_fields: vec![],
+    };
+
+    let stream = Codegen::default().emit_message_fmt_telemetry(&message);
+    assert!(stream.is_empty());
+
+    let codegen = Codegen {
+        telemetry_formatter: true,
+        ..Default::default()
+    };
+    let stream = codegen.emit_message_fmt_telemetry(&message);
+    let expected = quote! {
+        impl SystemTime {
+            /// Renders the fields as `name: value units` lines.
+            pub fn fmt_telemetry(&self) -> String {
+                use std::fmt::Write;
+
+                let mut out = String::new();
+                let _ = writeln!(out, "time_boot_ms: {:?} ms", self.time_boot_ms);
+                let _ = writeln!(out, "flags: {:#x}", self.flags);
+                let _ = writeln!(out, "text: {}", self.text_as_str());
+                out
+            }
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_message_builder() {
+    let message = model::Message {
+        name: "HEARTBEAT".parse().unwrap(),
+        id: 0,
+        dev_status: None,
+        description: None,
+        fields: vec![
+            Field {
+                name: "custom_mode".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint32),
+                ..default_field()
+            },
+            Field {
+                name: "system_status".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint8),
+                r#enum: Some("MAV_STATE".parse().unwrap()),
+                ..default_field()
+            },
+        ],
+        extension_fields: vec![],
+    };
+
+    let codegen = Codegen {
+        message_builders: true,
+        ..Default::default()
+    };
+    let stream = codegen.emit_message_builder(&message);
+    let expected = quote! {
+        #[doc = "Builder of [`Heartbeat`], fields which aren't set are taken from [`Heartbeat::DEFAULT`]."]
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[must_use]
+        pub struct HeartbeatBuilder {
+            message: Heartbeat,
+        }
+
+        impl HeartbeatBuilder {
+            #[doc = "Sets `custom_mode`."]
+            pub fn with_custom_mode(mut self, value: u32) -> Self {
+                self.message.custom_mode = value;
+                self
+            }
+
+            #[doc = "Sets `system_status`."]
+            pub fn with_system_status(mut self, value: MavState) -> Self {
+                self.message.system_status = value;
+                self
+            }
+
+            /// Returns the message with the fields set so far.
+            pub fn build(self) -> Heartbeat {
+                self.message
+            }
+        }
+
+        impl Default for HeartbeatBuilder {
+            fn default() -> Self {
+                Heartbeat::builder()
+            }
+        }
+
+        impl Heartbeat {
+            #[doc = "Returns [`HeartbeatBuilder`] starting from [`Self::DEFAULT`]."]
+            pub const fn builder() -> HeartbeatBuilder {
+                HeartbeatBuilder {
+                    message: Self::DEFAULT,
+                }
+            }
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_message_addressed() {
+    let target = |name: &str| Field {
+        name: name.parse().unwrap(),
+        r#type: FieldType::Primitive(PrimitiveType::Uint8),
+        ..default_field()
+    };
+    let mut message = model::Message {
+        name: "COMMAND_ACK".parse().unwrap(),
+        id: 77,
+        dev_status: None,
+        description: None,
+        fields: vec![target("target_system")],
+        extension_fields: vec![target("target_component")],
+    };
+
+    let codegen = Codegen::default();
+    let stream = codegen.emit_message_addressed(&message);
+    let expected = quote! {
+        impl CommandAck {
+            /// Returns the default message addressed to the component of
+            /// the system.
+            pub const fn addressed(target_system: u8, target_component: u8) -> Self {
+                Self {
+                    target_system,
+                    target_component,
+                    ..Self::DEFAULT
+                }
+            }
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    message.extension_fields.clear();
+    assert!(codegen.emit_message_addressed(&message).is_empty());
+}
+
+#[test]
+fn test_emit_message_field_metadata() {
+    let message = model::Message {
+        name: "SYSTEM_TIME".parse().unwrap(),
+        id: 2,
+        dev_status: None,
+        description: None,
+        fields: vec![Field {
+            name: "time_boot_ms".parse().unwrap(),
+            r#type: FieldType::Primitive(PrimitiveType::Uint32),
+            units: Some("ms".into()),
+            min_value: Some(0.5),
+            multiplier: Some("1E-3".into()),
+            ..default_field()
+        }],
+        extension_fields: vec![],
+    };
+
+    let stream = Codegen::default().emit_message_field_metadata(&message);
+    let expected = quote! {
+        impl SystemTime {
+            /// Returns the metadata of the field with the name from the
+            /// definition.
+            pub fn field_metadata(name: &str) -> Option<FieldMeta> {
+                match name {
+                    "time_boot_ms" => Some(FieldMeta {
+                        units: Some("ms"),
+                        increment: None,
+                        min: Some(0.5f32),
+                        max: None,
+                        multiplier: Some("1E-3"),
+                    }),
+                    _ => None,
+                }
+            }
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_message_validate() {
+    let message = model::Message {
+        name: "SET_THROTTLE".parse().unwrap(),
+        id: 3,
+        dev_status: None,
+        description: None,
+        fields: vec![
+            Field {
+                name: "throttle".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint16),
+                min_value: Some(0.0),
+                max_value: Some(1000.0),
+                ..default_field()
+            },
+            Field {
+                name: "gains".parse().unwrap(),
+                r#type: FieldType::Array(PrimitiveType::Float, 2),
+                increment: Some(0.5),
+                ..default_field()
+            },
+            Field {
+                name: "count".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint8),
+                ..default_field()
+            },
+        ],
+        extension_fields: vec![],
+    };
+
+    let stream = Codegen::default().emit_message_validate(&message);
+    let expected = quote! {
+        impl SetThrottle {
+            /// Checks the fields against the range and the increment from
+            /// the definition.
+            pub fn validate(&self) -> Result<(), Vec<FieldError> > {
+                let mut errors = <Vec<FieldError> >::new();
+                let value = f64::from(self.throttle);
+                if let Some(kind) = FieldErrorKind::check(value, Some(0f32), Some(1000f32), None) {
+                    errors.push(FieldError { field: "throttle", index: None, value, kind });
+                }
+                for (index, value) in self.gains.iter().enumerate() {
+                    let value = f64::from(*value);
+                    if let Some(kind) = FieldErrorKind::check(value, None, None, Some(0.5f32)) {
+                        errors.push(FieldError { field: "gains", index: Some(index), value, kind });
+                    }
+                }
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    let unbounded = model::Message {
+        fields: vec![message.fields[2].clone()],
+        ..message
+    };
+    let stream = Codegen::default().emit_message_validate(&unbounded);
+    let expected = quote! {
+        impl SetThrottle {
+            /// Checks the fields against the range and the increment from
+            /// the definition.
+            pub fn validate(&self) -> Result<(), Vec<FieldError> > {
+                Ok(())
+            }
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_message_scalar_char() {
+    let codegen = Codegen::default();
+    let message = Message {
+        name: "CHAR_MESSAGE".parse().unwrap(),
+        id: 7,
+        dev_status: None,
+        description: None,
+        fields: vec![Field {
+            name: "flag".parse().unwrap(),
+            ..default_field()
+        }],
+        extension_fields: vec![],
+    };
+    assert_eq!(message.wire_size(), 1);
+
+    let stream = codegen.emit_message_def(&message);
+    let expected = quote! {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct CharMessage {
+            pub flag: u8
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    let ser = codegen.emit_message_serialise_impl(&message).to_string();
+    let put = quote! { __cursor.put_u8(self.flag); }.to_string();
+    assert!(ser.contains(&put), "ser: {ser}");
+
+    let deser = codegen.emit_message_deserialise_impl(&message).to_string();
+    let get = quote! { flag: __cursor.get_u8() }.to_string();
+    assert!(deser.contains(&get), "deser: {deser}");
+}
+
+#[test]
+fn test_emit_message_message_data_impl() {
+    let codegen = Codegen::default();
+    let message = test_message();
+
+    let stream = codegen.emit_message_message_data_impl(&message, &format_ident!("MavMessage"));
+    let expected = quote! {
+        impl MessageData for CoolTestMessage {
+            type Message = MavMessage;
+            const ID: u32 = 10002u32;
+            const NAME: &'static str = "COOL_TEST_MESSAGE";
+            const EXTRA_CRC: u8 = 62u8;
+            const ENCODED_LEN: usize = 93usize;
+
+            fn ser(&self, version: MavlinkVersion, bytes: &mut [u8]) -> usize {
+                let mut __cursor = BytesMut::new(bytes);
+                if __cursor.remaining() < Self::ENCODED_LEN {
+                    panic!(
+                        "buffer is too small (need {} bytes, but got {})",
+                        Self::ENCODED_LEN,
+                        __cursor.remaining(),
+                    );
+                }
+
+                __cursor.put_i32_le(self.enum_plain.bits().try_into().expect("checked"));
+                for i in 0..4usize {
+                    __cursor.put_u8(self.enum_array[i].bits().try_into().expect("checked"));
+                }
+                __cursor.put_i8(self.plain);
+                __cursor.put_slice(&self.plain_array);
+
+                if matches!(version, MavlinkVersion::V2) {
+                    for i in 0..8usize {
+                        __cursor.put_u64_le(self.extension_field[i]);
+                    }
+                    let len = __cursor.len();
+                    ::mavlink_core::utils::remove_trailing_zeroes(&bytes[..len])
+                } else {
+                    __cursor.len()
+                }
+            }
+
+            fn deser(
+                version: MavlinkVersion,
+                __input: &[u8],
+            ) -> Result<Self, ::mavlink_core::error::ParserError> {
+                let __avail_len = __input.len();
+                let mut __payload_buf = [0; Self::ENCODED_LEN];
+                let mut __cursor = if __avail_len < Self::ENCODED_LEN {
+                    __payload_buf[0..__avail_len].copy_from_slice(__input);
+                    Bytes::new(&__payload_buf)
+                } else {
+                    Bytes::new(__input)
+                };
+                Ok(Self{
+                    enum_plain: CoolEnum::try_from_bits(__cursor.get_i32_le().try_into().expect("checked"))?,
+                    enum_array: [
+                        CoolEnum::try_from_bits(__cursor.get_u8().try_into().expect("checked"))?,
+                        CoolEnum::try_from_bits(__cursor.get_u8().try_into().expect("checked"))?,
+                        CoolEnum::try_from_bits(__cursor.get_u8().try_into().expect("checked"))?,
+                        CoolEnum::try_from_bits(__cursor.get_u8().try_into().expect("checked"))?,
+                    ],
+                    plain: __cursor.get_i8(),
+                    plain_array: __cursor.get_array(),
+                    extension_field: if matches!(version, MavlinkVersion::V2) {
+                        [
+                            __cursor.get_u64_le(),
+                            __cursor.get_u64_le(),
+                            __cursor.get_u64_le(),
+                            __cursor.get_u64_le(),
+                            __cursor.get_u64_le(),
+                            __cursor.get_u64_le(),
+                            __cursor.get_u64_le(),
+                            __cursor.get_u64_le(),
+                        ]
+                    } else {
+                        Self::DEFAULT.extension_field
+                    }
+                })
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_empty_message_message_data_impl() {
+    let codegen = Codegen::default();
+    let message = Message {
+        name: "EMPTY_PING".parse().unwrap(),
+        id: 42000,
+        dev_status: None,
+        description: None,
+        fields: vec![],
+        extension_fields: vec![],
+    };
+
+    let stream = codegen.emit_message_message_data_impl(&message, &format_ident!("MavMessage"));
+    let expected = quote! {
+        impl MessageData for EmptyPing {
+            type Message = MavMessage;
+            const ID: u32 = 42000u32;
+            const NAME: &'static str = "EMPTY_PING";
+            const EXTRA_CRC: u8 = 65u8;
+            const ENCODED_LEN: usize = 0usize;
+
+            fn ser(&self, _version: MavlinkVersion, _bytes: &mut [u8]) -> usize {
+                0
+            }
+
+            fn deser(
+                _version: MavlinkVersion,
+                _input: &[u8],
+            ) -> Result<Self, ::mavlink_core::error::ParserError> {
+                Ok(Self {})
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+fn test_mav_messages() -> Vec<model::Message> {
+    vec![
+        model::Message {
+            name: "HEARTBEAT".parse().unwrap(),
+            id: 0,
+            dev_status: None,
+            description: None,
+            fields: vec![default_field()],
+            extension_fields: vec![],
+        },
+        model::Message {
+            name: "PROTOCOL_VERSION".parse().unwrap(),
+            id: 300,
+            dev_status: None,
+            description: None,
+            fields: vec![default_field()],
+            extension_fields: vec![],
+        },
+    ]
+}
+
+#[test]
+fn test_emit_mav_message_def() {
+    let codegen = Codegen::default();
+    let messages = test_mav_messages();
+
+    let stream = codegen.emit_mav_message_def(&messages, &format_ident!("MavMessage"));
+    let expected = quote! {
+        #[derive(Debug, Clone, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[cfg_attr(feature = "serde", serde(tag = "mavpackettype"))]
+        pub enum MavMessage {
+            #[cfg_attr(feature = "serde", serde(rename = "HEARTBEAT"))]
+            Heartbeat(Heartbeat),
+            #[cfg_attr(feature = "serde", serde(rename = "PROTOCOL_VERSION"))]
+            ProtocolVersion(ProtocolVersion),
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_mav_message_extra_crc_lookup() {
+    let codegen = Codegen::default();
+    let messages = test_mav_messages();
+
+    let stream = codegen.emit_mav_message_extra_crc_lookup(&messages, &format_ident!("MavMessage"));
+    let mut by_extra_crc = messages
+        .iter()
+        .map(|message| {
+            let extra_crc = Literal::u8_unsuffixed(message.extra_crc());
+            let id = Literal::u32_unsuffixed(message.id);
+            (message.extra_crc(), quote! { #extra_crc => &[#id] })
+        })
+        .collect::<Vec<_>>();
+    by_extra_crc.sort_by_key(|(extra_crc, _)| *extra_crc);
+    let by_extra_crc = by_extra_crc.into_iter().map(|(_, arm)| arm);
+    let expected = quote! {
+        impl MavMessage {
+            /// Returns CRC_EXTRA of the message with the given ID, or
+            /// `None` if the message is unknown. It's a `const fn`, so
+            /// it can be evaluated at compile time.
+            pub const fn try_extra_crc(id: u32) -> Option<u8> {
+                match id {
+                    Heartbeat::ID => Some(Heartbeat::EXTRA_CRC),
+                    ProtocolVersion::ID => Some(ProtocolVersion::EXTRA_CRC),
+                    _ => None,
+                }
+            }
+
+            /// Returns IDs of the messages with the given CRC_EXTRA in
+            /// ascending order, e.g. to find the message of a frame with
+            /// an unknown ID. CRC_EXTRA isn't unique, so there may be
+            /// several of them.
+            pub const fn ids_by_extra_crc(extra_crc: u8) -> &'static [u32] {
+                match extra_crc {
+                    #(#by_extra_crc,)*
+                    _ => &[],
+                }
+            }
+
+            /// Validates the X.25 (CRC-16/MCRF4XX) checksum of a frame of the
+            /// message with the given ID. `data` are the checksummed bytes of
+            /// the frame, i.e. the header after the magic byte followed by the
+            /// payload, CRC_EXTRA of the message is added by the function.
+            /// Frames of unknown messages are never valid.
+            pub fn validate_crc(id: u32, data: &[u8], crc: u16) -> bool {
+                let extra_crc = match Self::try_extra_crc(id) {
+                    Some(extra_crc) => extra_crc,
+                    None => return false,
+                };
+                let accumulate = |crc: u16, byte: u8| {
+                    let tmp = byte ^ (crc as u8);
+                    let tmp = tmp ^ (tmp << 4);
+                    let tmp = u16::from(tmp);
+                    (crc >> 8) ^ (tmp << 8) ^ (tmp << 3) ^ (tmp >> 4)
+                };
+                let checksum = data.iter().fold(0xFFFF, |crc, byte| accumulate(crc, *byte));
+                accumulate(checksum, extra_crc) == crc
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_mav_message_name_lookup() {
+    let codegen = Codegen::default();
+    let messages = test_mav_messages();
+
+    let stream = codegen.emit_mav_message_name_lookup(&messages, &format_ident!("MavMessage"));
+    let expected = quote! {
+        impl MavMessage {
+            /// IDs of all messages of the dialect in ascending order.
+            pub const ALL_MESSAGE_IDS: &[u32] = &[0, 300];
+
+            /// Names of all messages of the dialect in the order of
+            /// [`Self::ALL_MESSAGE_IDS`].
+            pub const ALL_MESSAGE_NAMES: &[&str] = &["HEARTBEAT", "PROTOCOL_VERSION"];
+
+            /// Returns the ID of the message with the given name, or
+            /// `None` if the message is unknown.
+            pub fn id_from_name(name: &str) -> Option<u32> {
+                match name {
+                    Heartbeat::NAME => Some(Heartbeat::ID),
+                    ProtocolVersion::NAME => Some(ProtocolVersion::ID),
+                    _ => None,
+                }
+            }
+
+            /// Returns the name of the message with the given ID, or
+            /// `None` if the message is unknown.
+            pub fn name_from_id(id: u32) -> Option<&'static str> {
+                match id {
+                    Heartbeat::ID => Some(Heartbeat::NAME),
+                    ProtocolVersion::ID => Some(ProtocolVersion::NAME),
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    let stream = codegen.emit_message_encoded_len_v1(&messages[1]);
+    let expected = quote! {
+        impl ProtocolVersion {
+            /// Length of the MAVLink 1 payload, which has no extension fields.
+            pub const ENCODED_LEN_V1: usize = 1usize;
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_mav_message_from_impls() {
+    let codegen = Codegen::default();
+    let messages = test_mav_messages();
+
+    let stream = codegen.emit_mav_message_from_impls(&messages, &format_ident!("MavMessage"));
+    let expected = quote! {
+        impl From<Heartbeat> for MavMessage {
+            fn from(message: Heartbeat) -> Self {
+                Self::Heartbeat(message)
+            }
+        }
+
+        impl From<&Heartbeat> for MavMessage {
+            fn from(message: &Heartbeat) -> Self {
+                Self::Heartbeat(*message)
+            }
+        }
+
+        impl From<ProtocolVersion> for MavMessage {
+            fn from(message: ProtocolVersion) -> Self {
+                Self::ProtocolVersion(message)
+            }
+        }
+
+        impl From<&ProtocolVersion> for MavMessage {
+            fn from(message: &ProtocolVersion) -> Self {
+                Self::ProtocolVersion(*message)
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_message_string_accessors() {
+    let message = model::Message {
+        name: "STATUSTEXT".parse().unwrap(),
+        id: 253,
+        dev_status: None,
+        description: None,
+        fields: vec![
+            Field {
+                name: "severity".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint8),
+                ..default_field()
+            },
+            Field {
+                name: "text".parse().unwrap(),
+                r#type: FieldType::Array(PrimitiveType::Char, 50),
+                ..default_field()
+            },
+        ],
+        extension_fields: vec![],
+    };
+
+    let stream = Codegen::default().emit_message_string_accessors(&message);
+    let expected = quote! {
+        impl Statustext {
+            #[doc = "Returns `text` up to the first NUL, invalid UTF-8 is replaced with `U+FFFD`."]
+            pub fn text_as_str(&self) -> std::borrow::Cow<'_, str> {
+                let len = self.text.iter().position(|c| *c == 0).unwrap_or(self.text.len());
+                String::from_utf8_lossy(&self.text[..len])
+            }
+
+            #[doc = "Sets `text` padded with NULs, longer values are truncated."]
+            pub fn set_text_str(&mut self, value: &str) {
+                let len = value.len().min(self.text.len());
+                self.text.fill(0);
+                self.text[..len].copy_from_slice(&value.as_bytes()[..len]);
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    let message = model::Message {
+        fields: message.fields[..1].to_vec(),
+        ..message
+    };
+    assert!(Codegen::default()
+        .emit_message_string_accessors(&message)
+        .is_empty());
+}
+
+#[test]
+fn test_emit_message_invalid_accessors() {
+    let message = Message {
+        name: "GPS_INPUT".parse().unwrap(),
+        id: 232,
+        dev_status: None,
+        description: None,
+        fields: vec![
+            Field {
+                name: "vn".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Float),
+                invalid: Some("NaN".into()),
+                ..default_field()
+            },
+            Field {
+                name: "hdop".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint16),
+                invalid: Some("UINT16_MAX".into()),
+                ..default_field()
+            },
+            Field {
+                name: "voltages".parse().unwrap(),
+                r#type: FieldType::Array(PrimitiveType::Uint16, 2),
+                invalid: Some("[UINT16_MAX]".into()),
+                ..default_field()
+            },
+        ],
+        extension_fields: vec![],
+    };
+
+    let stream = Codegen::default().emit_message_invalid_accessors(&message);
+    let expected = quote! {
+        impl GpsInput {
+            #[doc = "Whether `vn` holds the invalid value `NaN`."]
+            pub fn vn_is_invalid(&self) -> bool {
+                self.vn.is_nan()
+            }
+
+            #[doc = "Returns the message with `vn` set to the invalid value `NaN`."]
+            pub fn with_vn_invalid(mut self) -> Self {
+                self.vn = f32::NAN;
+                self
+            }
+
+            #[doc = "Whether `hdop` holds the invalid value `UINT16_MAX`."]
+            pub fn hdop_is_invalid(&self) -> bool {
+                self.hdop == 65535
+            }
+
+            #[doc = "Returns the message with `hdop` set to the invalid value `UINT16_MAX`."]
+            pub fn with_hdop_invalid(mut self) -> Self {
+                self.hdop = 65535;
+                self
+            }
+
+            #[doc = "Whether `voltages` holds the invalid value `[UINT16_MAX]`."]
+            pub fn voltages_is_invalid(&self) -> bool {
+                self.voltages.iter().all(|&value| value == 65535)
+            }
+
+            #[doc = "Returns the message with `voltages` set to the invalid value `[UINT16_MAX]`."]
+            pub fn with_voltages_invalid(mut self) -> Self {
+                self.voltages = [65535; 2usize];
+                self
+            }
+        }
+    };
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    let valid = Message {
+        fields: vec![default_field()],
+        ..message
+    };
+    assert!(Codegen::default()
+        .emit_message_invalid_accessors(&valid)
+        .is_empty());
+
+    // Invalid values, which don't fit the field type, are rejected by the
+    // normaliser, so they never reach the codegen
+    let xml = r#"<?xml version="1.0"?>
+        <mavlink>
+            <messages>
+                <message id="232" name="GPS_INPUT">
+                    <field type="uint8_t" name="satellites" invalid="UINT16_MAX">Satellites</field>
+                </message>
+            </messages>
+        </mavlink>
+    "#;
+    let Err(crate::Error::Normalisation(errors, _)) = crate::load_module_from_str(xml) else {
+        panic!("the normalisation should fail");
+    };
+    assert_eq!(
+        errors,
+        [crate::normaliser::Error::BadInvalidValue {
+            message: "GPS_INPUT".parse().unwrap(),
+            field: "satellites".parse().unwrap(),
+            value: "UINT16_MAX".into(),
+        }]
+    );
+}
+
+#[test]
+fn test_emit_message_string_accessors_with_collections() {
+    let message = model::Message {
+        name: "STATUSTEXT".parse().unwrap(),
+        id: 253,
+        dev_status: None,
+        description: None,
+        fields: vec![Field {
+            name: "text".parse().unwrap(),
+            r#type: FieldType::Array(PrimitiveType::Char, 50),
+            ..default_field()
+        }],
+        extension_fields: vec![],
+    };
+
+    let codegen = Codegen {
+        collections: Collections::Alloc,
+        ..Default::default()
+    };
+    let stream = codegen.emit_message_string_accessors(&message).to_string();
+    let expected = quote! {
+        pub fn text_as_str(&self) -> alloc::borrow::Cow<'_, str> {
+            let len = self.text.iter().position(|c| *c == 0).unwrap_or(self.text.len());
+            alloc::string::String::from_utf8_lossy(&self.text[..len])
+        }
+    };
+    assert!(stream.contains(&expected.to_string()), "stream: {stream}");
+
+    let codegen = Codegen {
+        collections: Collections::Heapless,
+        ..Default::default()
+    };
+    let stream = codegen.emit_message_string_accessors(&message).to_string();
+    let expected = quote! {
+        #[doc = "Returns `text` up to the first NUL or invalid UTF-8 byte."]
+        pub fn text_as_str(&self) -> &str {
+            let len = self.text.iter().position(|c| *c == 0).unwrap_or(self.text.len());
+            match core::str::from_utf8(&self.text[..len]) {
+                Ok(value) => value,
+                Err(err) => core::str::from_utf8(&self.text[..err.valid_up_to()])
+                    .expect("valid up to the error"),
+            }
+        }
+    };
+    assert!(stream.contains(&expected.to_string()), "stream: {stream}");
 }
 
 #[test]
-fn test_emit_message_message_data_impl() {
-    let codegen = Codegen::default();
-    let message = test_message();
+fn test_emit_message_ref() {
+    let message = model::Message {
+        name: "STATUSTEXT".parse().unwrap(),
+        id: 253,
+        dev_status: None,
+        description: None,
+        fields: vec![
+            Field {
+                name: "severity".parse().unwrap(),
+                r#type: FieldType::Primitive(PrimitiveType::Uint8),
+                ..default_field()
+            },
+            Field {
+                name: "text".parse().unwrap(),
+                r#type: FieldType::Array(PrimitiveType::Char, 4),
+                ..default_field()
+            },
+        ],
+        extension_fields: vec![],
+    };
 
-    let stream = codegen.emit_message_message_data_impl(&message);
+    let stream = Codegen::default().emit_message_ref(&message);
     let expected = quote! {
-        impl MessageData for CoolTestMessage {
-            type Message = MavMessage;
-            const ID: u32 = 10002u32;
-            const NAME: &'static str = "COOL_TEST_MESSAGE";
-            const EXTRA_CRC: u8 = 62u8;
-            const ENCODED_LEN: usize = 93usize;
-
-            fn ser(&self, version: MavlinkVersion, bytes: &mut [u8]) -> usize {
-                let mut __cursor = BytesMut::new(bytes);
-                if __cursor.remaining() < Self::ENCODED_LEN {
-                    panic!(
-                        "buffer is too small (need {} bytes, but got {})",
-                        Self::ENCODED_LEN,
-                        __cursor.remaining(),
-                    );
-                }
-
-                __cursor.put_i32_le(self.enum_plain.bits().try_into().expect("checked"));
-                for i in 0..4usize {
-                    __cursor.put_u8(self.enum_array[i].bits().try_into().expect("checked"));
-                }
-                __cursor.put_i8(self.plain);
-                __cursor.put_slice(&self.plain_array);
-                for i in 0..8usize {
-                    __cursor.put_u64_le(self.extension_field[i]);
-                }
-
-                if matches!(version, MavlinkVersion::V2) {
-                    let len = __cursor.len();
-                    ::mavlink_core::utils::remove_trailing_zeroes(&bytes[..len])
-                } else {
-                    __cursor.len()
-                }
-            }
+        #[doc = "Borrowed [`Statustext`], which references string fields in the payload instead of copying them."]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq,)]
+        pub struct StatustextRef<'a> {
+            pub severity: u8,
+            ///
+            /// Bytes truncated from the end of a MAVLink 2 payload are missing.
+            pub text: &'a [u8]
+        }
 
-            fn deser(
+        impl<'a> StatustextRef<'a> {
+            /// Parses the payload of the message, string fields reference the input.
+            pub fn deser(
                 _version: MavlinkVersion,
-                __input: &[u8],
+                __input: &'a [u8],
             ) -> Result<Self, ::mavlink_core::error::ParserError> {
-                let __avail_len = __input.len();
-                let mut __payload_buf = [0; Self::ENCODED_LEN];
-                let mut __cursor = if __avail_len < Self::ENCODED_LEN {
+                let __avail_len = __input.len().min(5usize);
+                let mut __payload_buf = [0; 5usize];
+                let mut __cursor = if __avail_len < 5usize {
                     __payload_buf[0..__avail_len].copy_from_slice(__input);
                     Bytes::new(&__payload_buf)
                 } else {
                     Bytes::new(__input)
                 };
-                Ok(Self{
-                    enum_plain: CoolEnum::try_from_bits(__cursor.get_i32_le().try_into().expect("checked"))?,
-                    enum_array: [
-                        CoolEnum::try_from_bits(__cursor.get_u8().try_into().expect("checked"))?,
-                        CoolEnum::try_from_bits(__cursor.get_u8().try_into().expect("checked"))?,
-                        CoolEnum::try_from_bits(__cursor.get_u8().try_into().expect("checked"))?,
-                        CoolEnum::try_from_bits(__cursor.get_u8().try_into().expect("checked"))?,
-                    ],
-                    plain: __cursor.get_i8(),
-                    plain_array: __cursor.get_array(),
-                    extension_field: [
-                        __cursor.get_u64_le(),
-                        __cursor.get_u64_le(),
-                        __cursor.get_u64_le(),
-                        __cursor.get_u64_le(),
-                        __cursor.get_u64_le(),
-                        __cursor.get_u64_le(),
-                        __cursor.get_u64_le(),
-                        __cursor.get_u64_le(),
-                    ]
+                Ok(Self {
+                    severity: __cursor.get_u8(),
+                    text: {
+                        let _: [u8; 4usize] = __cursor.get_array();
+                        &__input[1usize.min(__avail_len)..5usize.min(__avail_len)]
+                    }
                 })
             }
+
+            #[doc = "Copies the message into [`Statustext`], padding string fields with NULs."]
+            ///
+            /// Panics if a string field is longer than in the definition.
+            pub fn to_owned(&self) -> Statustext {
+                Statustext {
+                    severity: self.severity,
+                    text: {
+                        let mut value = [0; 4usize];
+                        value[..self.text.len()].copy_from_slice(self.text);
+                        value
+                    }
+                }
+            }
+
+            #[doc = "Returns `text` up to the first NUL, invalid UTF-8 is replaced with `U+FFFD`."]
+            pub fn text_as_str(&self) -> std::borrow::Cow<'a, str> {
+                let value: &'a [u8] = self.text;
+                let len = value.iter().position(|c| *c == 0).unwrap_or(value.len());
+                String::from_utf8_lossy(&value[..len])
+            }
         }
     };
 
     assert_eq!(stream.to_string(), expected.to_string());
+
+    let message = model::Message {
+        fields: message.fields[..1].to_vec(),
+        ..message
+    };
+    assert!(Codegen::default().emit_message_ref(&message).is_empty());
 }
 
-fn test_mav_messages() -> Vec<model::Message> {
-    vec![
-        model::Message {
-            name: "HEARTBEAT".parse().unwrap(),
-            id: 0,
-            dev_status: None,
+#[test]
+fn test_emit_module_no_std() {
+    let codegen = Codegen {
+        no_std: true,
+        emit_framing: true,
+        ..Default::default()
+    };
+    assert_eq!(codegen.collections(), Collections::Heapless);
+
+    let module = model::MavlinkModule {
+        path: "test.xml".into(),
+        version: None,
+        dialect: None,
+        enums: vec![],
+        messages: test_mav_messages(),
+        reexports: vec![],
+    };
+    let stream = codegen.emit_module(&module).to_string();
+    assert!(!stream.contains("std ::"), "stream: {stream}");
+    assert!(
+        stream.contains(&quote!(impl core::error::Error for BufferTooSmall {}).to_string()),
+        "stream: {stream}"
+    );
+
+    let codegen = Codegen {
+        no_std: true,
+        collections: Collections::Alloc,
+        ..Default::default()
+    };
+    assert_eq!(codegen.collections(), Collections::Alloc);
+}
+
+#[test]
+fn test_emit_module_edition() {
+    let module = model::MavlinkModule {
+        path: "test.xml".into(),
+        version: None,
+        dialect: None,
+        enums: vec![],
+        messages: test_mav_messages(),
+        reexports: vec![],
+    };
+    let imports = quote! {
+        #[allow(unused_imports)]
+        use core::convert::{TryFrom, TryInto};
+    }
+    .to_string();
+
+    let stream = Codegen::default().emit_module(&module).to_string();
+    assert!(!stream.contains(&imports), "stream: {stream}");
+
+    let codegen = Codegen {
+        edition: Edition::Edition2018,
+        ..Default::default()
+    };
+    let stream = codegen.emit_module(&module).to_string();
+    assert!(stream.contains(&imports), "stream: {stream}");
+}
+
+#[test]
+fn test_emit_message_def_wip() {
+    let message = Message {
+        name: "NEW_MESSAGE".parse().unwrap(),
+        id: 7,
+        dev_status: Some(DevStatus::Wip {
+            since: Some("2024-10".into()),
             description: None,
-            fields: vec![default_field()],
-            extension_fields: vec![],
-        },
-        model::Message {
-            name: "PROTOCOL_VERSION".parse().unwrap(),
-            id: 300,
-            dev_status: None,
+        }),
+        description: Some("A message under development".into()),
+        fields: vec![default_field()],
+        extension_fields: vec![],
+    };
+
+    let stream = Codegen::default().emit_message_def(&message).to_string();
+    let doc = quote! {
+        #[doc = "**WORK IN PROGRESS** since 2024-10 — may change without notice."]
+        #[doc = ""]
+        #[doc = "A message under development"]
+    };
+    assert!(stream.starts_with(&doc.to_string()), "stream: {stream}");
+}
+
+#[test]
+fn test_emit_module_strict_wip() {
+    let wip = || {
+        Some(DevStatus::Wip {
+            since: None,
             description: None,
-            fields: vec![default_field()],
-            extension_fields: vec![],
+        })
+    };
+    let wip_enum = |name: &str| model::Enum {
+        name: name.parse().unwrap(),
+        bitmask: false,
+        description: None,
+        dev_status: wip(),
+        entries: vec![model::Entry {
+            name: format!("{name}_ONE").parse().unwrap(),
+            description: None,
+            dev_status: None,
+            value: 1,
+            params: vec![],
+        }],
+    };
+
+    let mut messages = test_mav_messages();
+    messages[0].fields[0].r#type = FieldType::Primitive(PrimitiveType::Uint8);
+    messages[0].fields[0].r#enum = Some("USED_ENUM".parse().unwrap());
+    messages[1].dev_status = wip();
+    let module = model::MavlinkModule {
+        path: "test.xml".into(),
+        version: None,
+        dialect: None,
+        enums: vec![wip_enum("USED_ENUM"), wip_enum("NEW_ENUM")],
+        messages,
+        reexports: vec![],
+    };
+
+    let stream = Codegen::default().emit_module(&module).to_string();
+    assert!(!stream.contains("\"wip\""), "stream: {stream}");
+
+    let codegen = Codegen {
+        strict_wip: true,
+        ..Default::default()
+    };
+    let stream = codegen.emit_module(&module).to_string();
+    let gated = [
+        quote! {
+            #[cfg(feature = "wip")]
+            mod gated_message_protocol_version
         },
-    ]
+        quote! {
+            #[cfg(feature = "wip")]
+            pub use gated_message_protocol_version::*;
+        },
+        quote! {
+            #[cfg(feature = "wip")]
+            mod gated_enum_new_enum
+        },
+        quote! {
+            #[cfg(feature = "wip")]
+            #[cfg_attr(feature = "serde", serde(rename = "PROTOCOL_VERSION"))]
+            ProtocolVersion(ProtocolVersion)
+        },
+        quote! {
+            #[cfg(feature = "wip")]
+            ProtocolVersion::ID => Some(ProtocolVersion::EXTRA_CRC)
+        },
+    ];
+    for gated in gated {
+        assert!(stream.contains(&gated.to_string()), "stream: {stream}");
+    }
+    // A stable message uses the enum, so it can't be gated.
+    assert!(!stream.contains("gated_enum_used_enum"), "stream: {stream}");
+    assert!(
+        !stream.contains("gated_message_heartbeat"),
+        "stream: {stream}"
+    );
 }
 
 #[test]
-fn test_emit_mav_message_def() {
-    let codegen = Codegen::default();
-    let messages = test_mav_messages();
+fn test_emit_module_per_message_features() {
+    let codegen = Codegen {
+        per_message_features: true,
+        strict_wip: true,
+        ..Default::default()
+    };
+    let mut messages = test_mav_messages();
+    messages[0].fields[0].r#type = FieldType::Primitive(PrimitiveType::Uint8);
+    messages[0].fields[0].r#enum = Some("STATE".parse().unwrap());
+    messages[1].dev_status = Some(DevStatus::Wip {
+        since: None,
+        description: None,
+    });
+    let module = model::MavlinkModule {
+        path: "test.xml".into(),
+        version: None,
+        dialect: None,
+        enums: vec![model::Enum {
+            name: "STATE".parse().unwrap(),
+            bitmask: false,
+            description: None,
+            dev_status: None,
+            entries: vec![model::Entry {
+                name: "STATE_ACTIVE".parse().unwrap(),
+                description: None,
+                dev_status: None,
+                value: 1,
+                params: vec![],
+            }],
+        }],
+        messages,
+        reexports: vec![],
+    };
 
-    let stream = codegen.emit_mav_message_def(&messages);
+    let stream = codegen.emit_mav_message_def(&module.messages, &format_ident!("MavMessage"));
     let expected = quote! {
         #[derive(Debug, Clone, PartialEq)]
         #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
         #[cfg_attr(feature = "serde", serde(tag = "mavpackettype"))]
         pub enum MavMessage {
+            #[cfg(feature = "HEARTBEAT")]
             #[cfg_attr(feature = "serde", serde(rename = "HEARTBEAT"))]
             Heartbeat(Heartbeat),
+            #[cfg(all(feature = "wip", feature = "PROTOCOL_VERSION"))]
             #[cfg_attr(feature = "serde", serde(rename = "PROTOCOL_VERSION"))]
             ProtocolVersion(ProtocolVersion),
         }
     };
+    assert_eq!(stream.to_string(), expected.to_string());
+
+    let stream = codegen.emit_module(&module).to_string();
+    let gated = [
+        quote! {
+            #[cfg(any(feature = "HEARTBEAT"))]
+            mod gated_enum_state
+        },
+        quote! {
+            #[cfg(feature = "HEARTBEAT")]
+            Heartbeat::ID => {
+                Heartbeat::deser(version, payload).map(Self::Heartbeat)
+            }
+        },
+    ];
+    for gated in gated {
+        assert!(stream.contains(&gated.to_string()), "stream: {stream}");
+    }
+
+    assert_eq!(
+        codegen.message_features(&module),
+        ["HEARTBEAT", "PROTOCOL_VERSION"]
+    );
+    assert!(Codegen::default().message_features(&module).is_empty());
+}
+
+#[test]
+fn test_emit_reflect_with_collections() {
+    let message = model::Message {
+        name: "TEST_MESSAGE".parse().unwrap(),
+        id: 1,
+        dev_status: None,
+        description: None,
+        fields: vec![Field {
+            name: "array_field".parse().unwrap(),
+            r#type: FieldType::Array(PrimitiveType::Uint16, 4),
+            ..default_field()
+        }],
+        extension_fields: vec![],
+    };
+
+    let codegen = Codegen {
+        reflection: true,
+        collections: Collections::Alloc,
+        ..Default::default()
+    };
+    let stream = codegen.emit_reflect_prelude().to_string();
+    assert!(stream.contains(&quote!(U16Array(alloc::vec::Vec<u16>)).to_string()));
+
+    let codegen = Codegen {
+        reflection: true,
+        collections: Collections::Heapless,
+        ..Default::default()
+    };
+    let stream = codegen.emit_reflect_prelude().to_string();
+    assert!(stream.contains(&quote!(U16Array(heapless::Vec<u16, 255usize>)).to_string()));
+
+    let stream = codegen.emit_message_reflect_impl(&message).to_string();
+    let expected = [
+        quote! {
+            "array_field" => Some(FieldValue::U16Array(
+                heapless::Vec::from_slice(&self.array_field).expect("array fits")
+            ))
+        },
+        quote! {
+            self.array_field = values.as_slice().try_into().map_err(|_| ReflectError::InvalidValue)?;
+        },
+    ];
+    for expected in expected {
+        let expected = expected.to_string();
+        assert!(stream.contains(&expected), "{expected} not in {stream}");
+    }
+}
+
+#[test]
+fn test_emit_message_info() {
+    let codegen = Codegen::default();
+    let messages = test_mav_messages();
+
+    let stream = codegen.emit_message_info(&messages);
+    let expected = quote! {
+        /// Metadata of a message type.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct MessageInfo {
+            pub id: u32,
+            pub name: &'static str,
+            pub extra_crc: u8,
+            /// Payload length including the extension fields.
+            pub max_payload_len: usize,
+        }
+
+        /// All messages of the dialect, e.g. to register handlers for
+        /// each of them.
+        pub const MESSAGE_INFO: &[MessageInfo] = &[
+            MessageInfo {
+                id: Heartbeat::ID,
+                name: Heartbeat::NAME,
+                extra_crc: Heartbeat::EXTRA_CRC,
+                max_payload_len: Heartbeat::ENCODED_LEN,
+            },
+            MessageInfo {
+                id: ProtocolVersion::ID,
+                name: ProtocolVersion::NAME,
+                extra_crc: ProtocolVersion::EXTRA_CRC,
+                max_payload_len: ProtocolVersion::ENCODED_LEN,
+            }
+        ];
+
+        /// The largest payload length of the messages, e.g. to allocate
+        /// a buffer for any of them.
+        pub const MAX_PAYLOAD_LEN: usize = 1usize;
+    };
 
     assert_eq!(stream.to_string(), expected.to_string());
+    assert_eq!(
+        stream.to_string().matches("MessageInfo {").count(),
+        messages.len() + 1
+    );
 }
 
 #[test]
@@ -959,8 +3427,10 @@ fn test_emit_mav_message_impl() {
     let codegen = Codegen::default();
     let messages = test_mav_messages();
 
-    let stream = codegen.emit_mav_message_impl(&messages);
+    let stream = codegen.emit_mav_message_impl(&messages, &format_ident!("MavMessage"));
     let expected = quote! {
+        /// Every message of the dialect as [`mavlink_core::Message`], which
+        /// requires `mavlink-core` 0.13 or newer.
         impl Message for MavMessage {
             fn ser(&self, version: MavlinkVersion, bytes: &mut [u8]) -> usize {
                 match self {
@@ -1027,3 +3497,208 @@ fn test_emit_mav_message_impl() {
 
     assert_eq!(stream.to_string(), expected.to_string());
 }
+
+#[test]
+fn test_emit_message_reflect_impl() {
+    let codegen = Codegen::default();
+    let message = test_message();
+
+    let stream = codegen.emit_message_reflect_impl(&message);
+    let expected = quote! {
+        impl Reflect for CoolTestMessage {
+            fn field_names(&self) -> &'static [&'static str] {
+                &["enum_array", "enum_plain", "plain", "plain_array", "extension_field"]
+            }
+
+            fn get_field(&self, name: &str) -> Option<FieldValue> {
+                match name {
+                    "enum_array" => Some(FieldValue::U8Array(
+                        self.enum_array
+                            .iter()
+                            .map(|value| value.bits().try_into().expect("checked"))
+                            .collect()
+                    )),
+                    "enum_plain" => Some(FieldValue::I32(self.enum_plain.bits().try_into().expect("checked"))),
+                    "plain" => Some(FieldValue::I8(self.plain)),
+                    "plain_array" => Some(FieldValue::U8Array(self.plain_array.to_vec())),
+                    "extension_field" => Some(FieldValue::U64Array(self.extension_field.to_vec())),
+                    _ => None,
+                }
+            }
+
+            fn set_field(&mut self, name: &str, value: FieldValue) -> Result<(), ReflectError> {
+                match name {
+                    "enum_array" => match value {
+                        FieldValue::U8Array(values) => {
+                            if values.len() != 4usize {
+                                return Err(ReflectError::InvalidValue);
+                            }
+                            let mut array = [CoolEnum::DEFAULT; 4usize];
+                            for (item, value) in array.iter_mut().zip(values) {
+                                let value = value.try_into().map_err(|_| ReflectError::InvalidValue)?;
+                                *item = CoolEnum::try_from_bits(value)
+                                    .map_err(|_| ReflectError::InvalidValue)?;
+                            }
+                            self.enum_array = array;
+                        },
+                        _ => return Err(ReflectError::TypeMismatch),
+                    },
+                    "enum_plain" => match value {
+                        FieldValue::I32(value) => {
+                            let value = value.try_into().map_err(|_| ReflectError::InvalidValue)?;
+                            self.enum_plain = CoolEnum::try_from_bits(value)
+                                .map_err(|_| ReflectError::InvalidValue)?;
+                        },
+                        _ => return Err(ReflectError::TypeMismatch),
+                    },
+                    "plain" => match value {
+                        FieldValue::I8(value) => self.plain = value,
+                        _ => return Err(ReflectError::TypeMismatch),
+                    },
+                    "plain_array" => match value {
+                        FieldValue::U8Array(values) => {
+                            self.plain_array = values.try_into().map_err(|_| ReflectError::InvalidValue)?;
+                        },
+                        _ => return Err(ReflectError::TypeMismatch),
+                    },
+                    "extension_field" => match value {
+                        FieldValue::U64Array(values) => {
+                            self.extension_field = values.try_into().map_err(|_| ReflectError::InvalidValue)?;
+                        },
+                        _ => return Err(ReflectError::TypeMismatch),
+                    },
+                    _ => return Err(ReflectError::UnknownField),
+                }
+                Ok(())
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_module_without_reflection() {
+    let codegen = Codegen::default();
+    let module = model::MavlinkModule {
+        path: "test.xml".into(),
+        version: None,
+        dialect: None,
+        enums: vec![],
+        messages: test_mav_messages(),
+        reexports: vec![],
+    };
+
+    let stream = codegen.emit_module(&module).to_string();
+    assert!(!stream.contains("Reflect"));
+    assert!(!stream.contains("FieldValue"));
+}
+
+#[test]
+fn test_emit_module_with_dyn_messages() {
+    let module = model::MavlinkModule {
+        path: "test.xml".into(),
+        version: None,
+        dialect: None,
+        enums: vec![],
+        messages: test_mav_messages(),
+        reexports: vec![],
+    };
+
+    let stream = Codegen::default().emit_module(&module).to_string();
+    assert!(!stream.contains("DynMessage"));
+
+    let codegen = Codegen {
+        dyn_messages: true,
+        ..Default::default()
+    };
+    let stream = codegen.emit_module(&module).to_string();
+    let expected = [
+        quote! { pub trait DynMessage },
+        quote! { fn serialize(&self, version: MavlinkVersion, buf: &mut Vec<u8>); },
+        quote! { impl<T: MessageData> DynMessage for T },
+        quote! { impl DynMessage for MavMessage },
+    ];
+    for expected in expected {
+        let expected = expected.to_string();
+        assert!(stream.contains(&expected), "{expected} not in {stream}");
+    }
+}
+
+#[test]
+fn test_emit_framing_extra_crc() {
+    let codegen = Codegen::default();
+    let messages = test_mav_messages();
+
+    let stream = codegen.emit_framing_extra_crc(&messages);
+    let expected = quote! {
+        #[doc = r" Returns CRC_EXTRA of the message with the given ID."]
+        pub const fn extra_crc(id: u32) -> Option<u8> {
+            match id {
+                0u32 => Some(247u8),
+                300u32 => Some(253u8),
+                _ => None,
+            }
+        }
+    };
+
+    assert_eq!(stream.to_string(), expected.to_string());
+}
+
+#[test]
+fn test_emit_module_with_mav_message_name() {
+    let codegen = Codegen {
+        reflection: true,
+        mav_message_name: Some("{module}Message".to_string()),
+        ..Default::default()
+    };
+    let module = model::MavlinkModule {
+        path: "/defs/common.xml".into(),
+        version: None,
+        dialect: None,
+        enums: vec![],
+        messages: test_mav_messages(),
+        reexports: vec![],
+    };
+
+    assert_eq!(codegen.mav_message_name(&module), "CommonMessage");
+
+    let stream = codegen.emit_module(&module).to_string();
+    assert!(!stream.contains("MavMessage"), "stream: {stream}");
+
+    let expected = [
+        quote! { pub enum CommonMessage },
+        quote! { type Message = CommonMessage; },
+        quote! { impl Message for CommonMessage },
+        quote! { impl Reflect for CommonMessage },
+    ];
+    for expected in expected {
+        let expected = expected.to_string();
+        assert!(stream.contains(&expected), "{expected} not in {stream}");
+    }
+
+    let renamed = model::MavlinkModule {
+        path: "/defs/ardupilotmega.generated-v2.xml".into(),
+        ..module.clone()
+    };
+    assert_eq!(
+        codegen.mav_message_name(&renamed),
+        "ArdupilotmegaGeneratedV2Message"
+    );
+    assert_eq!(
+        codegen
+            .with_module_name("dialect")
+            .mav_message_name(&module),
+        "DialectMessage"
+    );
+
+    let codegen = Codegen {
+        mav_message_name: Some("DialectMessage".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(codegen.mav_message_name(&module), "DialectMessage");
+    assert_eq!(
+        codegen.with_module_name("common").mav_message_name(&module),
+        "DialectMessage"
+    );
+}
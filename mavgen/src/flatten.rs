@@ -15,12 +15,78 @@ pub struct MavlinkModule {
     pub dialect: Option<u8>,
     pub enums: Vec<xml::Enum>,
     pub messages: Vec<xml::Message>,
+    /// Files where the messages are defined, in the same order as `messages`.
+    pub message_paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The same enum is defined as a bitmask in one file and as a regular
+    /// enum in another one.
+    ConflictingBitmask {
+        r#enum: String,
+        path_1: PathBuf,
+        path_2: PathBuf,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ConflictingBitmask {
+                r#enum,
+                path_1,
+                path_2,
+            } => write!(
+                f,
+                "enum {} is a bitmask only in one of {} and {}",
+                r#enum,
+                path_1.display(),
+                path_2.display(),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Enum merged from its definitions in several files.
+///
+/// Only borrows the parsed files, so that the entries are cloned once when
+/// the module is built.
+#[derive(Debug)]
+struct MergedEnum<'a> {
+    /// The first definition, which provides everything except the entries.
+    first: &'a xml::Enum,
+    /// File where the enum is first defined.
+    path: &'a Path,
+    /// Entries of every definition in the order of the files.
+    entries: Vec<&'a [xml::Entry]>,
+}
+
+impl MergedEnum<'_> {
+    fn to_enum(&self) -> xml::Enum {
+        let len = self.entries.iter().map(|entries| entries.len()).sum();
+        let mut entries = Vec::with_capacity(len);
+        for chunk in &self.entries {
+            entries.extend_from_slice(chunk);
+        }
+
+        xml::Enum {
+            name: self.first.name.clone(),
+            bitmask: self.first.bitmask,
+            description: self.first.description.clone(),
+            dev_status: self.first.dev_status.clone(),
+            entries,
+        }
+    }
 }
 
 #[derive(Debug, Default)]
 struct MessageAndEnumCollector<'a> {
-    messages: Vec<xml::Message>,
-    enums: Vec<xml::Enum>,
+    /// Messages with the files where they are defined.
+    messages: Vec<(&'a xml::Message, &'a Path)>,
+    enums: Vec<MergedEnum<'a>>,
     /// Map from enum name to enum index in the self.enums.
     ///
     /// Used to preserve the enum order but speed up search of the same enums
@@ -28,11 +94,14 @@ struct MessageAndEnumCollector<'a> {
     enum_index: HashMap<&'a str, usize>,
 
     processed: HashSet<&'a Path>,
+
+    errors: Vec<Error>,
 }
 
 fn flatten_recursive<'a>(
     collector: &mut MessageAndEnumCollector<'a>,
     files: &'a HashMap<PathBuf, parser::MavlinkFile>,
+    path: &'a Path,
     module: &'a MavlinkFile,
 ) {
     for include in &module.normalised_includes {
@@ -42,12 +111,14 @@ fn flatten_recursive<'a>(
 
         if !collector.processed.contains(include.as_path()) {
             collector.processed.insert(include);
-            flatten_recursive(collector, files, file);
+            flatten_recursive(collector, files, include, file);
         }
     }
 
     if let Some(messages) = &module.mavlink.messages {
-        collector.messages.extend_from_slice(&messages.0);
+        collector
+            .messages
+            .extend(messages.0.iter().map(|message| (message, path)));
     }
 
     if let Some(enums) = &module.mavlink.enums {
@@ -57,33 +128,100 @@ fn flatten_recursive<'a>(
         for enum_ in &enums.0 {
             if let Some(idx) = collector.enum_index.get(enum_.name.as_str()) {
                 let target_enum = &mut collector.enums[*idx];
-                target_enum.entries.extend_from_slice(&enum_.entries);
+                if target_enum.first.bitmask.unwrap_or(false) != enum_.bitmask.unwrap_or(false) {
+                    collector.errors.push(Error::ConflictingBitmask {
+                        r#enum: enum_.name.clone(),
+                        path_1: target_enum.path.to_owned(),
+                        path_2: path.to_owned(),
+                    });
+                }
+                target_enum.entries.push(&enum_.entries);
             } else {
                 let idx = collector.enums.len();
-                collector.enums.push(enum_.clone());
+                collector.enums.push(MergedEnum {
+                    first: enum_,
+                    path,
+                    entries: vec![&enum_.entries],
+                });
                 collector.enum_index.insert(&enum_.name, idx);
             }
         }
     }
 }
 
+fn include_order_recursive<'a>(
+    order: &mut Vec<PathBuf>,
+    visited: &mut HashSet<&'a Path>,
+    files: &'a HashMap<PathBuf, parser::MavlinkFile>,
+    path: &'a Path,
+) {
+    if !visited.insert(path) {
+        return;
+    }
+
+    let module = files
+        .get(path)
+        .expect("bug: the file should already be parsed");
+
+    for include in &module.normalised_includes {
+        include_order_recursive(order, visited, files, include);
+    }
+
+    order.push(path.to_owned());
+}
+
+/// Returns the file and all the files it includes, directly or not, so that
+/// every file goes after its includes.
+pub fn include_order(
+    files: &HashMap<PathBuf, parser::MavlinkFile>,
+    normalised: &Path,
+) -> Vec<PathBuf> {
+    let mut order = vec![];
+    include_order_recursive(&mut order, &mut HashSet::new(), files, normalised);
+    order
+}
+
 pub fn flatten(
     files: &HashMap<PathBuf, parser::MavlinkFile>,
     normalised: &Path,
-) -> std::io::Result<MavlinkModule> {
+) -> Result<MavlinkModule, Vec<Error>> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("flatten", path = %normalised.display()).entered();
+
     let module = files
         .get(normalised)
         .expect("bug: the file should be parsed");
 
     let mut collector = MessageAndEnumCollector::default();
-    flatten_recursive(&mut collector, files, module);
+    flatten_recursive(&mut collector, files, normalised, module);
+
+    if !collector.errors.is_empty() {
+        return Err(collector.errors);
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        files = collector.processed.len() + 1,
+        enums = collector.enums.len(),
+        messages = collector.messages.len(),
+        "flattened module"
+    );
+
+    // The parsed files may be flattened again for other roots, so the items
+    // are cloned here, once per module.
+    let (messages, message_paths): (Vec<_>, Vec<_>) = collector
+        .messages
+        .into_iter()
+        .map(|(message, path)| (message.clone(), path.to_owned()))
+        .unzip();
 
     Ok(MavlinkModule {
         path: normalised.to_owned(),
         version: module.mavlink.version,
         dialect: module.mavlink.dialect,
-        enums: collector.enums,
-        messages: collector.messages,
+        enums: collector.enums.iter().map(MergedEnum::to_enum).collect(),
+        messages,
+        message_paths,
     })
 }
 
@@ -139,11 +277,12 @@ mod tests {
         assert_eq!(module.dialect, Some(2));
         assert_eq!(module.enums, mavlink.enums.as_ref().unwrap().0);
         assert_eq!(module.messages, mavlink.messages.as_ref().unwrap().0);
+        assert_eq!(module.message_paths, [PathBuf::from("/cwd/test.xml")]);
     }
 
-    #[test]
-    fn test_chain() {
-        let world = MockWorld(HashMap::from_iter([
+    /// Three files, which include each other in a chain.
+    fn chain_world() -> MockWorld {
+        MockWorld(HashMap::from_iter([
             (
                 PathBuf::from("/cwd/test-1.xml"),
                 String::from(
@@ -227,7 +366,12 @@ mod tests {
                     "#,
                 ),
             ),
-        ]));
+        ]))
+    }
+
+    #[test]
+    fn test_chain() {
+        let world = chain_world();
 
         let mut parser = Parser::new(world);
         parser.parse(Path::new("test-1.xml"));
@@ -285,6 +429,118 @@ mod tests {
         assert_eq!(module.version, expected.version);
         assert_eq!(module.messages, expected.messages.unwrap().0);
         assert_eq!(module.enums, expected.enums.unwrap().0);
+        assert_eq!(
+            module.message_paths,
+            [
+                PathBuf::from("/cwd/test-3.xml"),
+                PathBuf::from("/cwd/test-2.xml"),
+                PathBuf::from("/cwd/test-1.xml"),
+            ]
+        );
+
+        // The parsed files are only borrowed, so they can be flattened again
+        assert_eq!(
+            flatten(&files, Path::new("/cwd/test-1.xml")).unwrap(),
+            module
+        );
+
+        assert_eq!(
+            include_order(&files, Path::new("/cwd/test-1.xml")),
+            [
+                PathBuf::from("/cwd/test-3.xml"),
+                PathBuf::from("/cwd/test-2.xml"),
+                PathBuf::from("/cwd/test-1.xml"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chain_all_message_ids() {
+        let mut parser = Parser::new(chain_world());
+        parser.parse(Path::new("test-1.xml"));
+        let files = parser.finish().unwrap();
+
+        let module = flatten(&files, Path::new("/cwd/test-1.xml")).unwrap();
+        let module = crate::normaliser::Normaliser::default()
+            .normalise_module(module)
+            .unwrap();
+        let code = crate::codegen::rust::Codegen::default()
+            .emit_module(&module)
+            .to_string();
+
+        // The messages are defined in the order 223, 2, 151
+        let expected = [
+            quote::quote! { pub const ALL_MESSAGE_IDS: &[u32] = &[2, 151, 223]; },
+            quote::quote! {
+                pub const ALL_MESSAGE_NAMES: &[&str] =
+                    &["SYSTEM_TIME", "SET_MAG_OFFSETS", "COMMAND_INT_STAMPED"];
+            },
+        ];
+        for expected in expected {
+            let expected = expected.to_string();
+            assert!(code.contains(&expected), "{expected} not in {code}");
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_chain_tracing_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{field::Field, span};
+
+        /// Records the names of the spans with their `path` fields.
+        #[derive(Clone, Default)]
+        struct SpanRecorder(Arc<Mutex<Vec<String>>>);
+
+        struct PathVisitor<'a>(&'a mut String);
+
+        impl tracing::field::Visit for PathVisitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "path" {
+                    *self.0 = format!(" {value:?}");
+                }
+            }
+        }
+
+        impl tracing::Subscriber for SpanRecorder {
+            fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                let mut path = String::new();
+                span.record(&mut PathVisitor(&mut path));
+
+                let mut spans = self.0.lock().unwrap();
+                spans.push(format!("{}{path}", span.metadata().name()));
+                span::Id::from_u64(spans.len() as u64)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, _: &tracing::Event<'_>) {}
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let recorder = SpanRecorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let mut parser = Parser::new(chain_world());
+            parser.parse(Path::new("test-1.xml"));
+            let files = parser.finish().unwrap();
+            flatten(&files, Path::new("/cwd/test-1.xml")).unwrap();
+        });
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                "parse /cwd/test-1.xml",
+                "parse /cwd/test-2.xml",
+                "parse /cwd/test-3.xml",
+                "finish",
+                "flatten /cwd/test-1.xml",
+            ]
+        );
     }
 
     #[test]
@@ -502,5 +758,65 @@ mod tests {
         assert_eq!(module.version, expected.version);
         assert_eq!(module.messages, expected.messages.unwrap().0);
         assert_eq!(module.enums, expected.enums.unwrap().0);
+        assert_eq!(
+            include_order(&files, Path::new("/cwd/test-1.xml")),
+            [
+                PathBuf::from("/cwd/test-common.xml"),
+                PathBuf::from("/cwd/test-3.xml"),
+                PathBuf::from("/cwd/test-2.xml"),
+                PathBuf::from("/cwd/test-1.xml"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conflicting_bitmask() {
+        let world = MockWorld(HashMap::from_iter([
+            (
+                PathBuf::from("/cwd/test-1.xml"),
+                String::from(
+                    r#"<?xml version="1.0"?>
+                    <mavlink>
+                        <include>test-2.xml</include>
+                        <enums>
+                            <enum name="MAV_MODE_FLAG">
+                                <entry value="4" name="MAV_MODE_FLAG_AUTO_ENABLED"/>
+                            </enum>
+                        </enums>
+                    </mavlink>
+                "#,
+                ),
+            ),
+            (
+                PathBuf::from("/cwd/test-2.xml"),
+                String::from(
+                    r#"<?xml version="1.0"?>
+                    <mavlink>
+                        <enums>
+                            <enum name="MAV_MODE_FLAG" bitmask="true">
+                                <entry value="1" name="MAV_MODE_FLAG_CUSTOM_MODE_ENABLED"/>
+                                <entry value="2" name="MAV_MODE_FLAG_TEST_ENABLED"/>
+                            </enum>
+                        </enums>
+                    </mavlink>
+                "#,
+                ),
+            ),
+        ]));
+
+        let mut parser = Parser::new(world);
+        parser.parse(Path::new("test-1.xml"));
+        let files = parser.finish().unwrap();
+
+        let errors = flatten(&files, Path::new("/cwd/test-1.xml")).unwrap_err();
+
+        assert_eq!(
+            errors,
+            [Error::ConflictingBitmask {
+                r#enum: "MAV_MODE_FLAG".into(),
+                path_1: PathBuf::from("/cwd/test-2.xml"),
+                path_2: PathBuf::from("/cwd/test-1.xml"),
+            }]
+        );
     }
 }
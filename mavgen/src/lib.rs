@@ -1,53 +1,267 @@
 use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
     ffi::OsString,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
 pub mod codegen;
 pub mod flatten;
 pub mod model;
+pub mod nested;
 pub mod normaliser;
 pub mod parser;
 pub mod xml;
 
-use parser::World;
+pub use parser::{FileSource, FsSource};
 
 #[derive(Debug)]
 pub enum Error {
     CreateDir(std::io::Error, PathBuf),
     ParseXml(Vec<parser::Error>),
     NormalisePath(std::io::Error, PathBuf),
-    Flattening(std::io::Error),
+    Flattening(Vec<flatten::Error>, PathBuf),
     Normalisation(Vec<normaliser::Error>, PathBuf),
-    InvalidFilename(OsString),
+    /// The name of the definition file can't be used as a module name.
+    InvalidFilename(OsString, PathBuf),
     WritingToFile(std::io::Error, PathBuf),
+    /// Names of the definition collide with the generated code.
+    Collisions(Vec<codegen::rust::Collision>, PathBuf),
+    /// Extra derives of [`codegen::rust::Codegen`] can't be added.
+    InvalidDerives(Vec<codegen::rust::InvalidDerive>),
+    /// [`Options::module_name`] isn't a valid identifier or is a keyword.
+    InvalidModuleName(String),
+    /// [`codegen::rust::Codegen::mav_message_name`] expands to a name, which
+    /// isn't a valid identifier or is a keyword, for the definition.
+    InvalidMavMessageName(String, PathBuf),
+}
+
+/// Options of the generation.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    pub codegen: codegen::rust::Codegen,
+    /// Instead of flattening, emit one module per XML file including the
+    /// included ones, which re-exports the items defined in its includes.
+    ///
+    /// Only used by [`generate_dir_with_options`] and
+    /// [`generate_with_source_and_options`].
+    pub nested: bool,
+    /// Generate files, which are included by several inputs, as separate
+    /// modules and re-export their enums and messages from the inputs
+    /// instead of emitting them again, if they are structurally identical.
+    ///
+    /// Unlike [`Self::nested`], other includes are still flattened into the
+    /// modules. Only used by [`generate_dir_with_options`] and
+    /// [`generate_with_source_and_options`].
+    pub deduplicate: bool,
+    /// Write the code as printed by the token stream instead of formatting
+    /// it with `prettyplease`, which is faster but hard to read.
+    pub skip_formatting: bool,
+    /// Don't generate enums, which aren't referenced by any message field,
+    /// see [`model::MavlinkModule::unused_enums`].
+    ///
+    /// Ignored with [`Self::nested`] and [`Self::deduplicate`], as the enums
+    /// may be used by the modules, which include the module.
+    pub prune_unused_enums: bool,
+    /// Generate only some of the messages, see
+    /// [`model::MavlinkModule::filter_messages`].
+    ///
+    /// Applied after flattening, so it may select messages from the included
+    /// files. Ignored with [`Self::nested`] and [`Self::deduplicate`].
+    pub message_filter: Option<model::MessageFilter>,
+    /// Fail on the warnings of [`normaliser::check_warnings`] instead of
+    /// adding them to [`Self::warnings`].
+    pub strict: bool,
+    /// Skip included files, which don't exist, with a warning, see
+    /// [`parser::Parser::allow_missing_includes`].
+    pub allow_missing_includes: bool,
+    /// Name of the module generated from a single definition instead of the
+    /// one derived from the file name, e.g. `ardupilotmega` for
+    /// `ardupilotmega.generated.xml`. It has to be a valid identifier, which
+    /// isn't a keyword. It's also used for `{module}` of
+    /// [`codegen::rust::Codegen::mav_message_name`].
+    ///
+    /// Only used for a single module, i.e. by [`generate_one_with_options`],
+    /// [`generate_from_str_with_options`] and the functions returning the
+    /// code of one module, e.g. [`generate_module_to_string`]. The file name
+    /// is only taken from it, when the output is a directory.
+    pub module_name: Option<String>,
+    /// Warnings of the generation, e.g. the skipped includes, for the caller
+    /// to report.
+    pub warnings: Warnings,
+}
+
+/// Suspicious input, which doesn't stop the generation, see
+/// [`Options::warnings`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// An included file doesn't exist and was skipped, see
+    /// [`Options::allow_missing_includes`].
+    MissingInclude(parser::MissingInclude),
+    /// A warning of [`normaliser::check_warnings`] for the definition, when
+    /// not [`Options::strict`].
+    Definition(normaliser::Warning, PathBuf),
+    /// The definition has `uint8_t_mavlink_version` fields, but no version,
+    /// so they default to 0.
+    NoVersion(PathBuf),
+    /// The generated code can't be parsed by `syn`, so it's written as
+    /// printed by the token stream instead of formatting it.
+    Formatting(String),
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::MissingInclude(missing) => write!(f, "{missing}, skipping it"),
+            Warning::Definition(warning, path) => write!(f, "{}: {warning}", path.display()),
+            Warning::NoVersion(path) => write!(
+                f,
+                "{} has no version, `uint8_t_mavlink_version` fields default to 0",
+                path.display()
+            ),
+            Warning::Formatting(err) => write!(
+                f,
+                "failed to format the generated code, writing it as is: {err}"
+            ),
+        }
+    }
+}
+
+/// Collects the warnings of the generation, which the library doesn't print.
+///
+/// Clones share the collected warnings, so that they can be taken from the
+/// options after the generation.
+#[derive(Debug, Default, Clone)]
+pub struct Warnings(Arc<Mutex<Vec<Warning>>>);
+
+impl Warnings {
+    fn push(&self, warning: Warning) {
+        self.0.lock().unwrap().push(warning);
+    }
+
+    /// Returns the warnings collected so far and clears them.
+    pub fn take(&self) -> Vec<Warning> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+/// A generated file, which isn't written yet, see
+/// [`generate_with_source_to_files`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub contents: String,
 }
 
 pub fn generate_dir(files: &[PathBuf], out_dir: &Path) -> Result<(), Error> {
+    generate_dir_with_options(files, out_dir, &Options::default())
+}
+
+pub fn generate_dir_with_options(
+    files: &[PathBuf],
+    out_dir: &Path,
+    options: &Options,
+) -> Result<(), Error> {
+    generate_with_source_and_options(FsSource, files, out_dir, options)
+}
+
+/// Same as [`generate_dir`], but the definitions and their includes are read
+/// from `source` instead of the disk.
+///
+/// ```
+/// use std::{collections::HashMap, path::PathBuf};
+///
+/// let source = HashMap::from([
+///     (
+///         PathBuf::from("defs/dialect.xml"),
+///         r#"<mavlink>
+///             <include>common.xml</include>
+///             <messages>
+///                 <message id="1" name="PING">
+///                     <field type="uint32_t" name="seq">Sequence.</field>
+///                 </message>
+///             </messages>
+///         </mavlink>"#
+///             .to_owned(),
+///     ),
+///     (
+///         PathBuf::from("defs/common.xml"),
+///         r#"<mavlink>
+///             <messages>
+///                 <message id="0" name="HEARTBEAT">
+///                     <field type="uint32_t" name="custom_mode">Custom mode.</field>
+///                 </message>
+///             </messages>
+///         </mavlink>"#
+///             .to_owned(),
+///     ),
+/// ]);
+///
+/// let out_dir = std::env::temp_dir().join(format!("mavgen-doc-{}", std::process::id()));
+/// mavgen::generate_with_source(&source, &[PathBuf::from("defs/dialect.xml")], &out_dir).unwrap();
+///
+/// let code = std::fs::read_to_string(out_dir.join("dialect.rs")).unwrap();
+/// assert!(code.contains("pub struct Heartbeat {"));
+/// assert!(code.contains("pub struct Ping {"));
+/// # std::fs::remove_dir_all(&out_dir).unwrap();
+/// ```
+pub fn generate_with_source(
+    source: impl FileSource,
+    roots: &[PathBuf],
+    out_dir: &Path,
+) -> Result<(), Error> {
+    generate_with_source_and_options(source, roots, out_dir, &Options::default())
+}
+
+pub fn generate_with_source_and_options(
+    source: impl FileSource,
+    roots: &[PathBuf],
+    out_dir: &Path,
+    options: &Options,
+) -> Result<(), Error> {
+    let files = generate_with_source_to_files(source, roots, out_dir, options)?;
     std::fs::create_dir_all(out_dir).map_err(|err| Error::CreateDir(err, out_dir.to_path_buf()))?;
+    write_files(files)
+}
 
-    let mut parser = parser::Parser::new(parser::FsWorld);
-    for file in files {
+/// Same as [`generate_with_source_and_options`], but returns the files
+/// instead of writing them, e.g. to check the definitions without touching
+/// the output.
+pub fn generate_with_source_to_files(
+    source: impl FileSource,
+    roots: &[PathBuf],
+    out_dir: &Path,
+    options: &Options,
+) -> Result<Vec<GeneratedFile>, Error> {
+    let mut parser = parser::Parser::new(&source);
+    parser.allow_missing_includes(options.allow_missing_includes);
+    for file in roots {
         parser.parse(file);
     }
 
-    let parsed = parser.finish().map_err(Error::ParseXml)?;
+    let parsed = finish_parsing(parser, options)?;
+
+    if options.nested || options.deduplicate {
+        return nested_dir_files(&source, &parsed, roots, out_dir, options);
+    }
 
     let mut modules = vec![];
 
-    for file in files {
-        let normalised = parser::FsWorld
+    for file in roots {
+        let normalised = source
             .normalise_path(file)
             .map_err(|err| Error::NormalisePath(err, file.to_path_buf()))?;
 
-        let module = flatten::flatten(&parsed, &normalised).map_err(Error::Flattening)?;
+        let module = flatten::flatten(&parsed, &normalised)
+            .map_err(|err| Error::Flattening(err, file.to_path_buf()))?;
 
         modules.push(module);
     }
 
     let mut normalised_modules = vec![];
 
-    for (module, path) in modules.into_iter().zip(files) {
+    for (module, path) in modules.into_iter().zip(roots) {
         let normaliser = normaliser::Normaliser::default();
         let normalised = normaliser
             .normalise_module(module)
@@ -55,75 +269,1385 @@ pub fn generate_dir(files: &[PathBuf], out_dir: &Path) -> Result<(), Error> {
         normalised_modules.push(normalised);
     }
 
-    let codegen = codegen::rust::Codegen::default();
+    flat_dir_files(
+        &normalised_modules,
+        out_dir,
+        options,
+        cfg!(feature = "parallel"),
+    )
+}
+
+fn flat_dir_files(
+    modules: &[model::MavlinkModule],
+    out_dir: &Path,
+    options: &Options,
+    parallel: bool,
+) -> Result<Vec<GeneratedFile>, Error> {
+    let module_names = modules
+        .iter()
+        .map(module_name)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let generate = |(module, module_name): (&model::MavlinkModule, &String)| {
+        let mut new_path = out_dir.join(Path::new(module_name));
+        new_path.set_extension("rs");
+
+        let mut files = vec![GeneratedFile {
+            path: new_path,
+            contents: module_to_string(module, options)?,
+        }];
+
+        if let Some(features) = message_features_toml(module, options) {
+            files.push(GeneratedFile {
+                path: out_dir.join(format!("{module_name}.features.toml")),
+                contents: features,
+            });
+        }
+        Ok::<_, Error>(files)
+    };
+
+    let mut files = vec![];
+    if parallel {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+
+            let generated = modules
+                .par_iter()
+                .zip(&module_names)
+                .map(generate)
+                .collect::<Result<Vec<_>, _>>()?;
+            files.extend(generated.into_iter().flatten());
+        }
+        #[cfg(not(feature = "parallel"))]
+        unreachable!("parallel generation requires the `parallel` feature");
+    } else {
+        for module in modules.iter().zip(&module_names) {
+            files.extend(generate(module)?);
+        }
+    }
+
+    // Added in the order of the inputs, so that `mod.rs` doesn't depend on
+    // which module was written first.
     let mut mod_codegen = codegen::rust::ModCodegen::default();
+    for module_name in &module_names {
+        mod_codegen.add_mod(module_name);
+    }
+
+    files.push(GeneratedFile {
+        path: out_dir.join(Path::new("mod.rs")),
+        contents: format_stream(mod_codegen.finish(), options),
+    });
+
+    Ok(files)
+}
+
+/// Generates a module per file, which re-exports the items defined the same
+/// in its includes.
+///
+/// With [`Options::deduplicate`] only the inputs and the files included by
+/// several inputs get a module.
+fn nested_dir_files(
+    source: &impl FileSource,
+    parsed: &HashMap<PathBuf, parser::MavlinkFile>,
+    roots: &[PathBuf],
+    out_dir: &Path,
+    options: &Options,
+) -> Result<Vec<GeneratedFile>, Error> {
+    let mut inputs = vec![];
+    for file in roots {
+        let normalised = source
+            .normalise_path(file)
+            .map_err(|err| Error::NormalisePath(err, file.to_path_buf()))?;
+        inputs.push(flatten::include_order(parsed, &normalised));
+    }
+
+    let is_generated = |path: &PathBuf| {
+        options.nested
+            || inputs.iter().any(|includes| includes.last() == Some(path))
+            || inputs
+                .iter()
+                .filter(|includes| includes.contains(path))
+                .count()
+                > 1
+    };
+
+    let mut order = vec![];
+    for path in inputs.iter().flatten() {
+        if !order.contains(path) && is_generated(path) {
+            order.push(path.clone());
+        }
+    }
+
+    let mut modules: HashMap<&Path, (String, model::MavlinkModule)> = HashMap::new();
+    let mut dependents: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut files = vec![];
 
-    for module in normalised_modules {
-        let module_name = module.path.file_stem().expect("path should be a file");
-        let module_name = module_name
-            .to_str()
-            .ok_or_else(|| Error::InvalidFilename(module_name.to_os_string()))?;
+    for path in &order {
+        let module = flatten::flatten(parsed, path)
+            .map_err(|err| Error::Flattening(err, path.to_path_buf()))?;
+        let mut module = normaliser::Normaliser::default()
+            .normalise_module(module)
+            .map_err(|err| Error::Normalisation(err, path.to_path_buf()))?;
+        let name = module_name(&module)?;
 
-        let module_name = codegen::rust::naming::snake_case(module_name);
+        // Includes go before the file, so they are already generated
+        let includes = flatten::include_order(parsed, path)
+            .into_iter()
+            .filter(|include| include != path)
+            .filter_map(|include| modules.get(include.as_path()))
+            .collect::<Vec<_>>();
+        module.reexports = nested::find_reexports(
+            &module,
+            &includes
+                .iter()
+                .map(|(name, module)| nested::IncludedModule { name, module })
+                .collect::<Vec<_>>(),
+        );
 
-        let mut new_path = out_dir.join(Path::new(&module_name));
+        for (include_name, _) in includes {
+            dependents
+                .entry(include_name.clone())
+                .or_default()
+                .insert(name.clone());
+        }
+
+        let mut new_path = out_dir.join(Path::new(&name));
         new_path.set_extension("rs");
 
-        let stream = codegen.emit_module(&module);
-        // TODO: dump raw stream to a temp file for debugging
-        let ast = syn::parse2(stream).expect("stream must be correct");
-        let formatted = prettyplease::unparse(&ast);
-        std::fs::write(&new_path, formatted).map_err(|err| Error::WritingToFile(err, new_path))?;
+        files.push(GeneratedFile {
+            path: new_path,
+            contents: module_to_string(&module, options)?,
+        });
 
-        mod_codegen.add_mod(&module_name);
+        modules.insert(path, (name, module));
     }
 
-    let stream = mod_codegen.finish();
-    // TODO: dump raw stream to a temp file for debugging
-    let ast = syn::parse2(stream).expect("stream must be correct");
-    let formatted = prettyplease::unparse(&ast);
-    let mod_path = out_dir.join(Path::new("mod.rs"));
-    std::fs::write(&mod_path, formatted).map_err(|err| Error::WritingToFile(err, mod_path))?;
+    let mut mod_codegen = codegen::rust::ModCodegen::default();
 
-    Ok(())
+    for path in &order {
+        let (name, _) = &modules[path.as_path()];
+        let dependents = dependents
+            .remove(name)
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<Vec<_>>();
+        mod_codegen.add_included_mod(name, &dependents);
+    }
+
+    files.push(GeneratedFile {
+        path: out_dir.join(Path::new("mod.rs")),
+        contents: format_stream(mod_codegen.finish(), options),
+    });
+
+    Ok(files)
 }
 
 pub fn generate_one(input: &Path, output: &Path) -> Result<(), Error> {
-    let mut parser = parser::Parser::new(parser::FsWorld);
+    generate_one_with_options(input, output, &Options::default())
+}
+
+pub fn generate_one_with_options(
+    input: &Path,
+    output: &Path,
+    options: &Options,
+) -> Result<(), Error> {
+    write_files(generate_one_to_files(input, output, options)?)
+}
+
+/// Same as [`generate_one_with_options`], but returns the file instead of
+/// writing it.
+pub fn generate_one_to_files(
+    input: &Path,
+    output: &Path,
+    options: &Options,
+) -> Result<Vec<GeneratedFile>, Error> {
+    let (module, _) = load_module_with_options(input, options)?;
+    Ok(vec![module_file(
+        &module,
+        output,
+        output.is_file(),
+        options,
+    )?])
+}
+
+/// Generates the module and returns its code instead of writing it.
+pub fn generate_one_to_string(input: &Path) -> Result<String, Error> {
+    generate_one_to_string_with_options(input, &Options::default())
+}
+
+pub fn generate_one_to_string_with_options(
+    input: &Path,
+    options: &Options,
+) -> Result<String, Error> {
+    let (module, _) = load_module_with_options(input, options)?;
+    generate_module_to_string(&module, options)
+}
+
+/// Generates every definition into a module of one file, e.g. to `include!`
+/// it from a build script.
+///
+/// Each definition is flattened independently into `pub mod <name>`, where
+/// the name is the snake_case name of the file.
+pub fn generate_combined(inputs: &[PathBuf], output: &Path) -> Result<(), Error> {
+    generate_combined_with_options(inputs, output, &Options::default())
+}
+
+/// Same as [`generate_combined`], [`Options::nested`] and
+/// [`Options::deduplicate`] are ignored.
+pub fn generate_combined_with_options(
+    inputs: &[PathBuf],
+    output: &Path,
+    options: &Options,
+) -> Result<(), Error> {
+    write_files(generate_combined_to_files(inputs, output, options)?)
+}
+
+/// Same as [`generate_combined_with_options`], but returns the file instead
+/// of writing it.
+pub fn generate_combined_to_files(
+    inputs: &[PathBuf],
+    output: &Path,
+    options: &Options,
+) -> Result<Vec<GeneratedFile>, Error> {
+    let mut stream = proc_macro2::TokenStream::new();
+
+    for input in inputs {
+        let (module, _) = load_module_with_options(input, options)?;
+        let name = quote::format_ident!("{}", module_name(&module)?);
+        let module_stream = emit_module_stream(&module, options)?;
+
+        stream.extend(quote::quote! {
+            pub mod #name {
+                #module_stream
+            }
+        });
+    }
+
+    Ok(vec![GeneratedFile {
+        path: output.to_path_buf(),
+        contents: format_stream(stream, options),
+    }])
+}
+
+/// Returns formatted code of an already loaded module.
+///
+/// Fails if names of the module collide with the generated items.
+pub fn generate_module_to_string(
+    module: &model::MavlinkModule,
+    options: &Options,
+) -> Result<String, Error> {
+    module_to_string(module, &with_module_name(options)?)
+}
+
+/// Same as [`generate_module_to_string`], but ignores
+/// [`Options::module_name`], as it's one of multiple modules.
+fn module_to_string(module: &model::MavlinkModule, options: &Options) -> Result<String, Error> {
+    let stream = emit_module_stream(module, options)?;
+    Ok(format_stream(stream, options))
+}
+
+fn emit_module_stream(
+    module: &model::MavlinkModule,
+    options: &Options,
+) -> Result<proc_macro2::TokenStream, Error> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!(
+        "codegen",
+        path = %module.path.display(),
+        enums = module.enums.len(),
+        messages = module.messages.len(),
+    )
+    .entered();
+
+    let flat = !options.nested && !options.deduplicate;
+    let pruned;
+    let module = if flat && (options.prune_unused_enums || options.message_filter.is_some()) {
+        let mut module = module.clone();
+        if let Some(filter) = &options.message_filter {
+            module.filter_messages(filter);
+        }
+        if options.prune_unused_enums {
+            module.prune_unused_enums();
+        }
+        pruned = module;
+        &pruned
+    } else {
+        module
+    };
+
+    let has_mavlink_version = module
+        .messages
+        .iter()
+        .flat_map(|message| &message.fields)
+        .any(|field| {
+            field.r#type == model::FieldType::Primitive(model::PrimitiveType::Uint8MavlinkVersion)
+        });
+    if has_mavlink_version && module.version.is_none() {
+        options
+            .warnings
+            .push(Warning::NoVersion(module.path.clone()));
+    }
+
+    let warnings = normaliser::check_warnings(module);
+    if options.strict && !warnings.is_empty() {
+        let errors = warnings
+            .into_iter()
+            .map(normaliser::Error::Strict)
+            .collect();
+        return Err(Error::Normalisation(errors, module.path.clone()));
+    }
+    for warning in warnings {
+        options
+            .warnings
+            .push(Warning::Definition(warning, module.path.clone()));
+    }
+
+    if let Some(name) = options.codegen.invalid_mav_message_name(module) {
+        return Err(Error::InvalidMavMessageName(name, module.path.clone()));
+    }
+
+    let invalid_derives = options.codegen.invalid_derives();
+    if !invalid_derives.is_empty() {
+        return Err(Error::InvalidDerives(invalid_derives));
+    }
+
+    let collisions = options.codegen.find_collisions(module);
+    if !collisions.is_empty() {
+        return Err(Error::Collisions(collisions, module.path.clone()));
+    }
+
+    Ok(options.codegen.emit_module(module))
+}
+
+/// Parses, flattens and normalises the definition without generating code.
+pub fn load_module(input: &Path) -> Result<model::MavlinkModule, Error> {
+    load_module_with_includes(input).map(|(module, _)| module)
+}
+
+/// Same as [`load_module`], but also returns the files included by the
+/// definition, directly or not, so that every file goes after its includes.
+pub fn load_module_with_includes(
+    input: &Path,
+) -> Result<(model::MavlinkModule, Vec<PathBuf>), Error> {
+    load_module_with_options(input, &Options::default())
+}
+
+/// Same as [`load_module_with_includes`], but takes the parsing options into
+/// account.
+fn load_module_with_options(
+    input: &Path,
+    options: &Options,
+) -> Result<(model::MavlinkModule, Vec<PathBuf>), Error> {
+    let mut parser = parser::Parser::new(FsSource);
+    parser.allow_missing_includes(options.allow_missing_includes);
     parser.parse(input);
-    let parsed = parser.finish().map_err(Error::ParseXml)?;
+    let parsed = finish_parsing(parser, options)?;
 
-    let normalised = parser::FsWorld
+    let normalised = FsSource
         .normalise_path(input)
         .map_err(|err| Error::NormalisePath(err, input.to_path_buf()))?;
 
-    let module = flatten::flatten(&parsed, &normalised).map_err(Error::Flattening)?;
+    let module = flatten::flatten(&parsed, &normalised)
+        .map_err(|err| Error::Flattening(err, input.to_path_buf()))?;
+
+    let module = normaliser::Normaliser::default()
+        .normalise_module(module)
+        .map_err(|err| Error::Normalisation(err, input.to_path_buf()))?;
+
+    let includes = flatten::include_order(&parsed, &normalised)
+        .into_iter()
+        .filter(|path| path != &normalised)
+        .collect();
+
+    Ok((module, includes))
+}
+
+/// Path used to identify definitions passed as a string.
+const STR_INPUT_PATH: &str = "stdin.xml";
+
+/// Generates a module from a definition without a file, e.g. read from stdin.
+///
+/// Such definition can't include other files. If `output` is a directory,
+/// the module is written into `stdin.rs` inside it.
+pub fn generate_from_str(xml: &str, output: &Path) -> Result<(), Error> {
+    generate_from_str_with_options(xml, output, &Options::default())
+}
+
+pub fn generate_from_str_with_options(
+    xml: &str,
+    output: &Path,
+    options: &Options,
+) -> Result<(), Error> {
+    write_files(generate_from_str_to_files(xml, output, options)?)
+}
 
+/// Same as [`generate_from_str_with_options`], but returns the file instead
+/// of writing it.
+pub fn generate_from_str_to_files(
+    xml: &str,
+    output: &Path,
+    options: &Options,
+) -> Result<Vec<GeneratedFile>, Error> {
+    let module = load_module_from_str(xml)?;
+    Ok(vec![module_file(
+        &module,
+        output,
+        !output.is_dir(),
+        options,
+    )?])
+}
+
+/// Same as [`load_module`], but for a definition without a file.
+pub fn load_module_from_str(xml: &str) -> Result<model::MavlinkModule, Error> {
+    let path = Path::new(STR_INPUT_PATH);
+
+    let mut parser = parser::Parser::new(FsSource);
+    parser.parse_str(path, xml);
+    let parsed = parser.finish().map_err(Error::ParseXml)?;
+
+    let module = flatten::flatten(&parsed, path)
+        .map_err(|err| Error::Flattening(err, path.to_path_buf()))?;
+    normaliser::Normaliser::default()
+        .normalise_module(module)
+        .map_err(|err| Error::Normalisation(err, path.to_path_buf()))
+}
+
+/// Finishes parsing and prints the skipped includes, once for all files.
+fn finish_parsing(
+    parser: parser::Parser<impl FileSource>,
+    options: &Options,
+) -> Result<HashMap<PathBuf, parser::MavlinkFile>, Error> {
+    for missing in parser.missing_includes() {
+        options
+            .warnings
+            .push(Warning::MissingInclude(missing.clone()));
+    }
+
+    parser.finish().map_err(Error::ParseXml)
+}
+
+fn module_name(module: &model::MavlinkModule) -> Result<String, Error> {
     let module_name = module.path.file_stem().expect("path should be a file");
     let module_name = module_name
         .to_str()
-        .ok_or_else(|| Error::InvalidFilename(module_name.to_os_string()))?;
-    let module_name = codegen::rust::naming::snake_case(module_name);
+        .ok_or_else(|| Error::InvalidFilename(module_name.to_os_string(), module.path.clone()))?;
+    // File names may contain dashes, which aren't valid in module names
+    Ok(codegen::rust::naming::snake_case(module_name).replace('-', "_"))
+}
 
-    let normaliser = normaliser::Normaliser::default();
-    let normalised = normaliser
-        .normalise_module(module)
-        .map_err(|err| Error::Normalisation(err, input.to_path_buf()))?;
+fn validate_module_name(name: &str) -> Result<(), Error> {
+    match name.parse::<model::Ident>() {
+        Ok(ident) if ident.keyword_escape() == model::KeywordEscape::None => Ok(()),
+        _ => Err(Error::InvalidModuleName(name.to_owned())),
+    }
+}
 
-    let codegen = codegen::rust::Codegen::default();
+/// Validates [`Options::module_name`] and applies it to the codegen.
+fn with_module_name(options: &Options) -> Result<Cow<'_, Options>, Error> {
+    let Some(name) = &options.module_name else {
+        return Ok(Cow::Borrowed(options));
+    };
+    validate_module_name(name)?;
+    Ok(Cow::Owned(Options {
+        codegen: options.codegen.with_module_name(name),
+        ..options.clone()
+    }))
+}
 
-    let output = if output.is_file() {
+fn module_file(
+    module: &model::MavlinkModule,
+    output: &Path,
+    output_is_file: bool,
+    options: &Options,
+) -> Result<GeneratedFile, Error> {
+    let output = if output_is_file {
         output.to_path_buf()
     } else {
-        output.join(format!("{}.rs", module_name))
+        let name = match &options.module_name {
+            Some(name) => name.clone(),
+            None => module_name(module)?,
+        };
+        output.join(format!("{name}.rs"))
     };
 
-    let stream = codegen.emit_module(&normalised);
-    // TODO: dump raw stream to a temp file for debugging
-    let ast = syn::parse2(stream).expect("stream must be correct");
-    let formatted = prettyplease::unparse(&ast);
-    std::fs::write(&output, formatted)
-        .map_err(|err| Error::WritingToFile(err, output.to_path_buf()))?;
+    Ok(GeneratedFile {
+        path: output,
+        contents: generate_module_to_string(module, options)?,
+    })
+}
+
+/// Lists the features of [`codegen::rust::Codegen::per_message_features`]
+/// as entries of the `[features]` table of `Cargo.toml`.
+fn message_features_toml(module: &model::MavlinkModule, options: &Options) -> Option<String> {
+    let features = options.codegen.message_features(module);
+    if features.is_empty() {
+        return None;
+    }
+
+    let mut toml = format!(
+        "# Features of the messages of {}, add them to the `[features]` of the crate.\n",
+        module.path.display()
+    );
+    for feature in features {
+        toml.push_str(&format!("{feature} = []\n"));
+    }
+    Some(toml)
+}
+
+fn write_files(files: Vec<GeneratedFile>) -> Result<(), Error> {
+    files
+        .into_iter()
+        .try_for_each(|file| write_file(file.path, file.contents))
+}
+
+fn write_file(path: PathBuf, contents: String) -> Result<(), Error> {
+    #[cfg(feature = "tracing")]
+    let _span =
+        tracing::info_span!("write", path = %path.display(), bytes = contents.len()).entered();
+
+    std::fs::write(&path, contents).map_err(|err| Error::WritingToFile(err, path))
+}
+
+fn format_stream(stream: proc_macro2::TokenStream, options: &Options) -> String {
+    if options.skip_formatting {
+        return stream.to_string();
+    }
+
+    match syn::parse2::<syn::File>(stream.clone()) {
+        Ok(ast) => prettyplease::unparse(&ast),
+        Err(err) => {
+            options.warnings.push(Warning::Formatting(err.to_string()));
+            stream.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_one_to_string() {
+        let dir = std::env::temp_dir().join(format!("mavgen-to-string-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("test_dialect.xml");
+        std::fs::write(
+            &input,
+            r#"<?xml version="1.0"?>
+            <mavlink>
+                <messages>
+                    <message id="0" name="HEARTBEAT">
+                        <field type="uint32_t" name="custom_mode">Custom mode</field>
+                        <field type="uint8_t_mavlink_version" name="mavlink_version">Version</field>
+                    </message>
+                </messages>
+            </mavlink>
+            "#,
+        )
+        .unwrap();
+
+        let code = generate_one_to_string(&input).unwrap();
+        assert!(code.contains("pub enum MavMessage {"), "code: {code}");
+        assert!(code.contains("pub struct Heartbeat {"), "code: {code}");
+
+        let module = load_module(&input).unwrap();
+        assert_eq!(
+            generate_module_to_string(&module, &Options::default()).unwrap(),
+            code
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    const HEARTBEAT_XML: &str = r#"<?xml version="1.0"?>
+        <mavlink>
+            <messages>
+                <message id="0" name="HEARTBEAT">
+                    <field type="uint32_t" name="custom_mode">Custom mode</field>
+                    <field type="uint8_t_mavlink_version" name="mavlink_version">Version</field>
+                </message>
+            </messages>
+        </mavlink>
+        "#;
+
+    #[test]
+    fn test_formatted_code_parses() {
+        let module = load_module_from_str(HEARTBEAT_XML).unwrap();
+
+        let code = generate_module_to_string(&module, &Options::default()).unwrap();
+        assert!(code.lines().count() > 1, "code: {code}");
+        syn::parse_file(&code).unwrap();
+
+        let options = Options {
+            skip_formatting: true,
+            ..Default::default()
+        };
+        let unformatted = generate_module_to_string(&module, &options).unwrap();
+        assert_ne!(unformatted, code);
+        syn::parse_file(&unformatted).unwrap();
+    }
+
+    #[test]
+    fn test_generate_with_collisions() {
+        let xml = HEARTBEAT_XML.replace("HEARTBEAT", "BUFFER_TOO_SMALL");
+        let module = load_module_from_str(&xml).unwrap();
+
+        let Err(Error::Collisions(collisions, path)) =
+            generate_module_to_string(&module, &Options::default())
+        else {
+            panic!("the generation should fail");
+        };
+        assert_eq!(
+            collisions,
+            [codegen::rust::Collision::Type {
+                definition: "BUFFER_TOO_SMALL".to_owned(),
+                name: "BufferTooSmall".to_owned(),
+            }]
+        );
+        assert_eq!(path, Path::new(STR_INPUT_PATH));
+    }
+
+    #[test]
+    fn test_generate_with_invalid_derives() {
+        let module = load_module_from_str(HEARTBEAT_XML).unwrap();
+        let mut options = Options::default();
+        options.codegen.extra_derives = vec!["Hash".to_owned(), "Clone".to_owned()];
+
+        let Err(Error::InvalidDerives(invalid)) = generate_module_to_string(&module, &options)
+        else {
+            panic!("the generation should fail");
+        };
+        assert_eq!(
+            invalid,
+            [codegen::rust::InvalidDerive::Generated {
+                derive: "Clone".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_strict_entry_prefix() {
+        let xml = r#"<?xml version="1.0"?>
+            <mavlink>
+                <enums>
+                    <enum name="LIMITS_STATE">
+                        <entry value="0" name="LIMITS_INIT"/>
+                        <entry value="1" name="LIMITS_STATE_DISABLED"/>
+                    </enum>
+                </enums>
+                <messages>
+                    <message id="0" name="HEARTBEAT">
+                        <field type="uint8_t" name="state" enum="LIMITS_STATE">State</field>
+                    </message>
+                </messages>
+            </mavlink>
+            "#;
+        let module = load_module_from_str(xml).unwrap();
+        let warning = normaliser::Warning::EntryWithoutEnumPrefix {
+            r#enum: "LIMITS_STATE".parse().unwrap(),
+            entry: "LIMITS_INIT".parse().unwrap(),
+        };
+
+        let options = Options::default();
+        generate_module_to_string(&module, &options).unwrap();
+        assert_eq!(
+            options.warnings.take(),
+            [Warning::Definition(
+                warning.clone(),
+                PathBuf::from(STR_INPUT_PATH)
+            )]
+        );
+
+        let options = Options {
+            strict: true,
+            ..Default::default()
+        };
+        let Err(Error::Normalisation(errors, path)) = generate_module_to_string(&module, &options)
+        else {
+            panic!("the generation should fail");
+        };
+        assert_eq!(errors, [normaliser::Error::Strict(warning)]);
+        assert_eq!(path, Path::new(STR_INPUT_PATH));
+        assert!(options.warnings.take().is_empty());
+    }
+
+    #[test]
+    fn test_no_version_warning() {
+        let xml = r#"<?xml version="1.0"?>
+            <mavlink>
+                <messages>
+                    <message id="0" name="HEARTBEAT">
+                        <field type="uint8_t_mavlink_version" name="mavlink_version">Version</field>
+                    </message>
+                </messages>
+            </mavlink>
+            "#;
+        let module = load_module_from_str(xml).unwrap();
+
+        let options = Options::default();
+        generate_module_to_string(&module, &options).unwrap();
+        assert_eq!(
+            options.warnings.take(),
+            [Warning::NoVersion(PathBuf::from(STR_INPUT_PATH))]
+        );
+
+        let module =
+            load_module_from_str(&xml.replace("<messages>", "<version>3</version><messages>"))
+                .unwrap();
+        generate_module_to_string(&module, &options).unwrap();
+        assert!(options.warnings.take().is_empty());
+    }
+
+    #[test]
+    fn test_prune_unused_enums() {
+        let xml = r#"<?xml version="1.0"?>
+            <mavlink>
+                <enums>
+                    <enum name="MAV_STATE">
+                        <entry value="0" name="MAV_STATE_UNINIT"/>
+                    </enum>
+                    <enum name="ORPHAN">
+                        <entry value="0" name="ORPHAN_ENTRY"/>
+                    </enum>
+                </enums>
+                <messages>
+                    <message id="0" name="HEARTBEAT">
+                        <field type="uint8_t" name="system_status" enum="MAV_STATE">State</field>
+                    </message>
+                </messages>
+            </mavlink>
+            "#;
+        let module = load_module_from_str(xml).unwrap();
+        let orphan: model::Ident = "ORPHAN".parse().unwrap();
+        assert_eq!(module.unused_enums(), [&orphan]);
+
+        let code = generate_module_to_string(&module, &Options::default()).unwrap();
+        assert!(code.contains("pub enum Orphan {"), "code: {code}");
+
+        let options = Options {
+            prune_unused_enums: true,
+            ..Default::default()
+        };
+        let code = generate_module_to_string(&module, &options).unwrap();
+        assert!(code.contains("pub enum MavState {"), "code: {code}");
+        assert!(!code.contains("Orphan"), "code: {code}");
+    }
+
+    #[test]
+    fn test_message_filter() {
+        let xml = r#"<?xml version="1.0"?>
+            <mavlink>
+                <enums>
+                    <enum name="MAV_STATE">
+                        <entry value="0" name="MAV_STATE_UNINIT"/>
+                    </enum>
+                    <enum name="MAV_FRAME">
+                        <entry value="0" name="MAV_FRAME_GLOBAL"/>
+                    </enum>
+                    <enum name="ORPHAN">
+                        <entry value="0" name="ORPHAN_ENTRY"/>
+                    </enum>
+                </enums>
+                <messages>
+                    <message id="0" name="HEARTBEAT">
+                        <field type="uint8_t" name="system_status" enum="MAV_STATE">State</field>
+                    </message>
+                    <message id="1" name="SYS_STATUS">
+                        <field type="uint8_t" name="frame" enum="MAV_FRAME">Frame</field>
+                    </message>
+                    <message id="2" name="SYSTEM_TIME">
+                        <field type="uint64_t" name="time_unix_usec">Time</field>
+                    </message>
+                </messages>
+            </mavlink>
+            "#;
+        let module = load_module_from_str(xml).unwrap();
+
+        let options = Options {
+            message_filter: Some(model::MessageFilter::Allow(
+                [model::MessageSelector::Id(0), model::MessageSelector::Id(2)].into(),
+            )),
+            ..Default::default()
+        };
+        let code = generate_module_to_string(&module, &options).unwrap();
+        let file = syn::parse_file(&code).unwrap();
+        let variants = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                syn::Item::Enum(item) if item.ident == "MavMessage" => Some(item),
+                _ => None,
+            })
+            .unwrap()
+            .variants
+            .iter()
+            .map(|variant| variant.ident.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(variants, ["Heartbeat", "SystemTime"]);
+        assert!(code.contains("pub enum MavState {"), "code: {code}");
+        assert!(!code.contains("MavFrame"), "code: {code}");
+        assert!(code.contains("pub enum Orphan {"), "code: {code}");
+
+        let options = Options {
+            message_filter: Some(model::MessageFilter::Deny(
+                ["HEARTBEAT".parse().unwrap()].into(),
+            )),
+            ..Default::default()
+        };
+        let code = generate_module_to_string(&module, &options).unwrap();
+        assert!(!code.contains("Heartbeat"), "code: {code}");
+        assert!(!code.contains("MavState"), "code: {code}");
+        assert!(code.contains("pub struct SysStatus {"), "code: {code}");
+    }
+
+    #[test]
+    fn test_generate_combined() {
+        let dir = std::env::temp_dir().join(format!("mavgen-combined-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let first = dir.join("first.xml");
+        std::fs::write(&first, HEARTBEAT_XML).unwrap();
+        let second = dir.join("second-dialect.xml");
+        std::fs::write(&second, HEARTBEAT_XML.replace("HEARTBEAT", "PING")).unwrap();
+
+        let output = dir.join("combined.rs");
+        generate_combined(&[first, second], &output).unwrap();
+
+        let code = std::fs::read_to_string(&output).unwrap();
+        let file = syn::parse_file(&code).unwrap();
+        let modules = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Mod(module) => Some(module.ident.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(modules, ["first", "second_dialect"]);
+        assert_eq!(file.items.len(), 2);
+        assert!(code.contains("pub mod first {"), "code: {code}");
+        assert!(code.contains("pub mod second_dialect {"), "code: {code}");
+        assert!(code.contains("pub struct Heartbeat {"), "code: {code}");
+        assert!(code.contains("pub struct Ping {"), "code: {code}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_generate_with_invalid_mav_message_name() {
+        let module = model::MavlinkModule {
+            path: PathBuf::from("defs/1_common.xml"),
+            ..load_module_from_str(HEARTBEAT_XML).unwrap()
+        };
+
+        let mut options = Options::default();
+        options.codegen.mav_message_name = Some("{module}Message".to_owned());
+        let Err(Error::InvalidMavMessageName(name, path)) =
+            generate_module_to_string(&module, &options)
+        else {
+            panic!("the generation should fail");
+        };
+        assert_eq!(name, "1CommonMessage");
+        assert_eq!(path, module.path);
+
+        options.codegen.mav_message_name = Some("type".to_owned());
+        let Err(Error::InvalidMavMessageName(name, _)) =
+            generate_module_to_string(&module, &options)
+        else {
+            panic!("the generation should fail");
+        };
+        assert_eq!(name, "type");
+    }
+
+    #[test]
+    fn test_generate_with_module_name() {
+        let out_dir =
+            std::env::temp_dir().join(format!("mavgen-module-name-{}", std::process::id()));
+        std::fs::create_dir_all(&out_dir).unwrap();
+
+        let options = Options {
+            module_name: Some("ardupilotmega".to_owned()),
+            ..Default::default()
+        };
+        generate_from_str_with_options(HEARTBEAT_XML, &out_dir, &options).unwrap();
+        let code = std::fs::read_to_string(out_dir.join("ardupilotmega.rs")).unwrap();
+        assert!(code.contains("pub struct Heartbeat {"));
+        assert!(!out_dir.join("stdin.rs").exists());
+
+        let options = Options {
+            module_name: Some("ardupilotmega.generated".to_owned()),
+            ..Default::default()
+        };
+        let Err(Error::InvalidModuleName(name)) =
+            generate_from_str_with_options(HEARTBEAT_XML, &out_dir, &options)
+        else {
+            panic!("the generation should fail");
+        };
+        assert_eq!(name, "ardupilotmega.generated");
+
+        let options = Options {
+            module_name: Some("type".to_owned()),
+            ..Default::default()
+        };
+        let Err(Error::InvalidModuleName(name)) =
+            generate_from_str_with_options(HEARTBEAT_XML, &out_dir, &options)
+        else {
+            panic!("the generation should fail");
+        };
+        assert_eq!(name, "type");
+
+        let mut options = Options {
+            module_name: Some("ardupilotmega".to_owned()),
+            ..Default::default()
+        };
+        options.codegen.mav_message_name = Some("{module}Message".to_owned());
+        generate_from_str_with_options(HEARTBEAT_XML, &out_dir, &options).unwrap();
+        let code = std::fs::read_to_string(out_dir.join("ardupilotmega.rs")).unwrap();
+        assert!(
+            code.contains("pub enum ArdupilotmegaMessage {"),
+            "code: {code}"
+        );
+        std::fs::remove_dir_all(&out_dir).unwrap();
+
+        // The code of one module is named the same, as the file
+        let module = load_module_from_str(HEARTBEAT_XML).unwrap();
+        let code = generate_module_to_string(&module, &options).unwrap();
+        assert!(
+            code.contains("pub enum ArdupilotmegaMessage {"),
+            "code: {code}"
+        );
+
+        options.module_name = Some("type".to_owned());
+        let Err(Error::InvalidModuleName(name)) = generate_module_to_string(&module, &options)
+        else {
+            panic!("the generation should fail");
+        };
+        assert_eq!(name, "type");
+    }
+
+    #[test]
+    fn test_generate_message_features() {
+        let source = HashMap::from([(PathBuf::from("defs/dialect.xml"), HEARTBEAT_XML.to_owned())]);
+        let roots = [PathBuf::from("defs/dialect.xml")];
+        let out_dir =
+            std::env::temp_dir().join(format!("mavgen-message-features-{}", std::process::id()));
+
+        let mut options = Options::default();
+        options.codegen.per_message_features = true;
+        generate_with_source_and_options(&source, &roots, &out_dir, &options).unwrap();
+
+        let features = std::fs::read_to_string(out_dir.join("dialect.features.toml")).unwrap();
+        assert!(
+            features.ends_with("\nHEARTBEAT = []\n"),
+            "features: {features}"
+        );
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    #[test]
+    fn test_generate_to_files() {
+        let source = HashMap::from([(PathBuf::from("defs/dialect.xml"), HEARTBEAT_XML.to_owned())]);
+        let roots = [PathBuf::from("defs/dialect.xml")];
+        let out_dir = std::env::temp_dir().join(format!("mavgen-to-files-{}", std::process::id()));
+
+        let files =
+            generate_with_source_to_files(&source, &roots, &out_dir, &Options::default()).unwrap();
+        let paths = files.iter().map(|file| &file.path).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [&out_dir.join("dialect.rs"), &out_dir.join("mod.rs")]
+        );
+        assert!(files[0].contents.contains("pub struct Heartbeat {"));
+        assert!(!out_dir.exists());
+    }
+
+    #[test]
+    fn test_generate_with_missing_include() {
+        let source = HashMap::from([(
+            PathBuf::from("defs/dialect.xml"),
+            HEARTBEAT_XML.replace("<mavlink>", "<mavlink><include>huge.xml</include>"),
+        )]);
+        let roots = [PathBuf::from("defs/dialect.xml")];
+        let out_dir =
+            std::env::temp_dir().join(format!("mavgen-missing-include-{}", std::process::id()));
+
+        let Err(Error::ParseXml(errors)) = generate_with_source(&source, &roots, &out_dir) else {
+            panic!("the generation should fail");
+        };
+        assert!(matches!(&errors[..], [parser::Error::Io { .. }]));
+
+        let options = Options {
+            allow_missing_includes: true,
+            ..Default::default()
+        };
+        generate_with_source_and_options(&source, &roots, &out_dir, &options).unwrap();
+        let code = std::fs::read_to_string(out_dir.join("dialect.rs")).unwrap();
+        assert!(code.contains("pub struct Heartbeat {"), "code: {code}");
+        let warnings = options.warnings.take();
+        let [Warning::MissingInclude(missing)] = &warnings[..] else {
+            panic!("warnings: {warnings:?}");
+        };
+        assert!(missing.include.ends_with("huge.xml"), "{missing:?}");
+        assert!(options.warnings.take().is_empty());
+
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_stream_fallback() {
+        let stream = quote::quote! { fn main() { let x = ; } };
+        let options = Options::default();
+        assert_eq!(format_stream(stream.clone(), &options), stream.to_string());
+        assert!(matches!(
+            &options.warnings.take()[..],
+            [Warning::Formatting(_)]
+        ));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_dir_matches_serial() {
+        let dir = std::env::temp_dir().join(format!("mavgen-parallel-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let modules = (0..8)
+            .map(|i| {
+                let input = dir.join(format!("dialect_{i}.xml"));
+                std::fs::write(
+                    &input,
+                    HEARTBEAT_XML.replace("HEARTBEAT", &format!("PING_{i}")),
+                )
+                .unwrap();
+                load_module(&input).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let read_dir = |out_dir: &Path| {
+            let mut files = std::fs::read_dir(out_dir)
+                .unwrap()
+                .map(|entry| {
+                    let path = entry.unwrap().path();
+                    (
+                        path.file_name().unwrap().to_owned(),
+                        std::fs::read(&path).unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+
+        let serial_dir = dir.join("serial");
+        std::fs::create_dir_all(&serial_dir).unwrap();
+        write_files(flat_dir_files(&modules, &serial_dir, &Options::default(), false).unwrap())
+            .unwrap();
+        let serial = read_dir(&serial_dir);
+        assert_eq!(serial.len(), 9);
+
+        for run in 0..4 {
+            let parallel_dir = dir.join(format!("parallel_{run}"));
+            std::fs::create_dir_all(&parallel_dir).unwrap();
+            write_files(
+                flat_dir_files(&modules, &parallel_dir, &Options::default(), true).unwrap(),
+            )
+            .unwrap();
+            assert_eq!(read_dir(&parallel_dir), serial);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_generate_nested_dir() {
+        let dir = std::env::temp_dir().join(format!("mavgen-nested-{}", std::process::id()));
+        let out_dir = dir.join("out");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(
+            dir.join("test-1.xml"),
+            r#"<?xml version="1.0"?>
+            <mavlink>
+                <include>test-2.xml</include>
+                <dialect>1</dialect>
+                <version>2</version>
+                <enums>
+                    <enum name="ICAROUS_TRACK_BAND_TYPES">
+                        <entry name="ICAROUS_TRACK_BAND_TYPE_NONE" value="0"/>
+                        <entry name="ICAROUS_TRACK_BAND_TYPE_NEAR" value="1"/>
+                        <entry name="ICAROUS_TRACK_BAND_TYPE_RECOVERY" value="2"/>
+                    </enum>
+                    <enum name="MAV_CMD">
+                        <entry value="218" name="MAV_CMD_DO_AUX_FUNCTION"/>
+                    </enum>
+                </enums>
+                <messages>
+                    <message id="151" name="SET_MAG_OFFSETS">
+                        <field type="uint8_t" name="target_system">System ID.</field>
+                        <field type="uint8_t" name="target_component">Component ID.</field>
+                        <field type="int16_t" name="mag_ofs_x">Magnetometer X offset.</field>
+                    </message>
+                </messages>
+            </mavlink>
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("test-2.xml"),
+            r#"<?xml version="1.0"?>
+            <mavlink>
+                <dialect>3</dialect>
+                <version>4</version>
+                <include>test-3.xml</include>
+                <enums>
+                    <enum name="MAV_CMD">
+                        <entry value="5001" name="MAV_CMD_NAV_FENCE_POLYGON_VERTEX_INCLUSION"/>
+                    </enum>
+                </enums>
+                <messages>
+                    <message id="2" name="SYSTEM_TIME">
+                        <field type="uint64_t" name="time_unix_usec" units="us"/>
+                        <field type="uint32_t" name="time_boot_ms" units="ms"/>
+                    </message>
+                </messages>
+            </mavlink>
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("test-3.xml"),
+            r#"<?xml version="1.0"?>
+            <mavlink>
+                <dialect>4</dialect>
+                <version>5</version>
+                <enums>
+                    <enum name="MAV_CMD">
+                        <entry name="MAV_CMD_RESET_MPPT" value="40001"/>
+                        <entry name="MAV_CMD_PAYLOAD_CONTROL" value="40002"/>
+                    </enum>
+                    <enum name="GSM_MODEM_TYPE">
+                        <entry value="0" name="GSM_MODEM_TYPE_UNKNOWN"/>
+                        <entry value="1" name="GSM_MODEM_TYPE_HUAWEI_E3372"/>
+                    </enum>
+                </enums>
+                <messages>
+                    <message id="223" name="COMMAND_INT_STAMPED">
+                        <field type="uint32_t" name="utc_time"/>
+                        <field type="uint64_t" name="vehicle_timestamp"/>
+                    </message>
+                </messages>
+            </mavlink>
+            "#,
+        )
+        .unwrap();
+
+        let options = Options {
+            nested: true,
+            ..Default::default()
+        };
+        generate_dir_with_options(&[dir.join("test-1.xml")], &out_dir, &options).unwrap();
+
+        let mut emitted = std::fs::read_dir(&out_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        emitted.sort();
+        assert_eq!(emitted, ["mod.rs", "test_1.rs", "test_2.rs", "test_3.rs"]);
+
+        let read = |name: &str| std::fs::read_to_string(out_dir.join(name)).unwrap();
 
-    Ok(())
+        let test_3 = read("test_3.rs");
+        assert!(!test_3.contains("pub use super::"), "code: {test_3}");
+        assert!(test_3.contains("pub enum GsmModemType {"), "code: {test_3}");
+        assert!(
+            test_3.contains("pub struct CommandIntStamped {"),
+            "code: {test_3}"
+        );
+
+        let test_2 = read("test_2.rs");
+        assert!(
+            test_2.contains("pub use super::test_3::GsmModemType;"),
+            "code: {test_2}"
+        );
+        assert!(
+            test_2.contains("pub use super::test_3::CommandIntStamped;"),
+            "code: {test_2}"
+        );
+        assert!(
+            !test_2.contains("pub enum GsmModemType {"),
+            "code: {test_2}"
+        );
+        assert!(
+            !test_2.contains("pub struct CommandIntStamped {"),
+            "code: {test_2}"
+        );
+        // Extended by this file, so it's a different enum.
+        assert!(test_2.contains("pub enum MavCmd {"), "code: {test_2}");
+        assert!(test_2.contains("pub struct SystemTime {"), "code: {test_2}");
+
+        let test_1 = read("test_1.rs");
+        assert!(
+            test_1.contains("pub use super::test_3::GsmModemType;"),
+            "code: {test_1}"
+        );
+        assert!(
+            test_1.contains("pub use super::test_3::CommandIntStamped;"),
+            "code: {test_1}"
+        );
+        assert!(
+            test_1.contains("pub use super::test_2::SystemTime;"),
+            "code: {test_1}"
+        );
+        assert!(test_1.contains("pub enum MavCmd {"), "code: {test_1}");
+        assert!(
+            test_1.contains("pub struct SetMagOffsets {"),
+            "code: {test_1}"
+        );
+        assert!(
+            test_1.contains("CommandIntStamped(CommandIntStamped)"),
+            "code: {test_1}"
+        );
+
+        let mod_rs = read("mod.rs");
+        assert!(
+            mod_rs.contains(
+                r#"#[cfg(any(feature = "test_3", feature = "test_1", feature = "test_2"))]"#
+            ),
+            "code: {mod_rs}"
+        );
+        assert!(
+            mod_rs.contains(r#"#[cfg(any(feature = "test_2", feature = "test_1"))]"#),
+            "code: {mod_rs}"
+        );
+        assert!(
+            mod_rs.contains(r#"#[cfg(any(feature = "test_1"))]"#),
+            "code: {mod_rs}"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_generate_deduplicated_dir() {
+        let dir = std::env::temp_dir().join(format!("mavgen-dedup-{}", std::process::id()));
+        let out_dir = dir.join("out");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(
+            dir.join("shared.xml"),
+            r#"<?xml version="1.0"?>
+            <mavlink>
+                <enums>
+                    <enum name="SHARED_STATE">
+                        <entry value="0" name="SHARED_STATE_IDLE"/>
+                        <entry value="1" name="SHARED_STATE_ACTIVE"/>
+                    </enum>
+                    <enum name="MAV_CMD">
+                        <entry value="16" name="MAV_CMD_NAV_WAYPOINT"/>
+                    </enum>
+                </enums>
+                <messages>
+                    <message id="0" name="HEARTBEAT">
+                        <field type="uint8_t" name="state" enum="SHARED_STATE">State</field>
+                    </message>
+                </messages>
+            </mavlink>
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("extra.xml"),
+            r#"<?xml version="1.0"?>
+            <mavlink>
+                <enums>
+                    <enum name="EXTRA_STATE">
+                        <entry value="0" name="EXTRA_STATE_NONE"/>
+                    </enum>
+                </enums>
+            </mavlink>
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("dialect_a.xml"),
+            r#"<?xml version="1.0"?>
+            <mavlink>
+                <include>shared.xml</include>
+                <include>extra.xml</include>
+            </mavlink>
+            "#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("dialect_b.xml"),
+            r#"<?xml version="1.0"?>
+            <mavlink>
+                <include>shared.xml</include>
+                <enums>
+                    <enum name="MAV_CMD">
+                        <entry value="218" name="MAV_CMD_DO_AUX_FUNCTION"/>
+                    </enum>
+                </enums>
+            </mavlink>
+            "#,
+        )
+        .unwrap();
+
+        let options = Options {
+            deduplicate: true,
+            ..Default::default()
+        };
+        let files = [dir.join("dialect_a.xml"), dir.join("dialect_b.xml")];
+        generate_dir_with_options(&files, &out_dir, &options).unwrap();
+
+        let mut emitted = std::fs::read_dir(&out_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        emitted.sort();
+        assert_eq!(
+            emitted,
+            ["dialect_a.rs", "dialect_b.rs", "mod.rs", "shared.rs"]
+        );
+
+        let read = |name: &str| std::fs::read_to_string(out_dir.join(name)).unwrap();
+        let shared = read("shared.rs");
+        let dialect_a = read("dialect_a.rs");
+        let dialect_b = read("dialect_b.rs");
+
+        let definitions = [&shared, &dialect_a, &dialect_b]
+            .iter()
+            .map(|code| code.matches("pub enum SharedState {").count())
+            .sum::<usize>();
+        assert_eq!(definitions, 1);
+        assert!(shared.contains("pub enum SharedState {"), "code: {shared}");
+        for code in [&dialect_a, &dialect_b] {
+            assert!(
+                code.contains("pub use super::shared::SharedState;"),
+                "code: {code}"
+            );
+        }
+
+        // Included only by one input, so it's flattened as usual
+        assert!(
+            dialect_a.contains("pub enum ExtraState {"),
+            "code: {dialect_a}"
+        );
+        // Extended by the dialect, so it isn't the same enum
+        assert!(
+            dialect_a.contains("pub use super::shared::MavCmd;"),
+            "code: {dialect_a}"
+        );
+        assert!(dialect_b.contains("pub enum MavCmd {"), "code: {dialect_b}");
+
+        let mod_rs = read("mod.rs");
+        assert!(
+            mod_rs.contains(
+                r#"#[cfg(any(feature = "shared", feature = "dialect_a", feature = "dialect_b"))]"#
+            ),
+            "code: {mod_rs}"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,4 +1,9 @@
-use std::path::PathBuf;
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::PathBuf,
+};
+
+use unicode_xid::UnicodeXID;
 
 use crate::xml;
 
@@ -17,6 +22,56 @@ impl AsRef<str> for Ident {
     }
 }
 
+impl Ident {
+    /// How the name has to be escaped to be used as is in Rust code.
+    pub fn keyword_escape(&self) -> KeywordEscape {
+        KeywordEscape::of(&self.0)
+    }
+}
+
+/// Escaping of names, which collide with Rust keywords.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordEscape {
+    /// Not a keyword.
+    None,
+    /// A keyword, which can be used as a raw identifier, e.g. `r#type`.
+    Raw,
+    /// A keyword, which can't be a raw identifier, so it gets the `_` suffix,
+    /// e.g. `self_`.
+    Suffix,
+}
+
+impl KeywordEscape {
+    /// Strict and reserved keywords of all editions.
+    const RAW_KEYWORDS: &'static [&'static str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in",
+        "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+        "return", "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe",
+        "unsized", "use", "virtual", "where", "while", "yield",
+    ];
+    const NON_RAW_KEYWORDS: &'static [&'static str] = &["crate", "self", "Self", "super"];
+
+    pub fn of(name: &str) -> Self {
+        if Self::RAW_KEYWORDS.contains(&name) {
+            Self::Raw
+        } else if Self::NON_RAW_KEYWORDS.contains(&name) {
+            Self::Suffix
+        } else {
+            Self::None
+        }
+    }
+
+    /// Returns the escaped name.
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            Self::None => name.to_owned(),
+            Self::Raw => format!("r#{name}"),
+            Self::Suffix => format!("{name}_"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DevStatus {
     Deprecated {
@@ -52,6 +107,20 @@ pub struct Entry {
     pub description: Option<String>,
     pub dev_status: Option<DevStatus>,
     pub value: u64,
+    /// Parameters of commands, sorted by index. Missing indices are skipped.
+    pub params: Vec<Param>,
+}
+
+/// Parameter of a `MAV_CMD` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    /// Index from 1 to 7.
+    pub index: u8,
+    pub label: Option<String>,
+    pub units: Option<String>,
+    pub min_value: Option<f32>,
+    pub max_value: Option<f32>,
+    pub description: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -113,12 +182,32 @@ impl PrimitiveType {
             PrimitiveType::Uint64 => "uint64_t",
         }
     }
+
+    /// The smallest and the largest values of integer types, chars are
+    /// `uint8_t`.
+    pub fn int_range(self) -> Option<(i128, i128)> {
+        let range = match self {
+            PrimitiveType::Float | PrimitiveType::Double => return None,
+            PrimitiveType::Char | PrimitiveType::Uint8 | PrimitiveType::Uint8MavlinkVersion => {
+                (u8::MIN.into(), u8::MAX.into())
+            }
+            PrimitiveType::Int8 => (i8::MIN.into(), i8::MAX.into()),
+            PrimitiveType::Uint16 => (u16::MIN.into(), u16::MAX.into()),
+            PrimitiveType::Int16 => (i16::MIN.into(), i16::MAX.into()),
+            PrimitiveType::Uint32 => (u32::MIN.into(), u32::MAX.into()),
+            PrimitiveType::Int32 => (i32::MIN.into(), i32::MAX.into()),
+            PrimitiveType::Uint64 => (u64::MIN.into(), u64::MAX.into()),
+            PrimitiveType::Int64 => (i64::MIN.into(), i64::MAX.into()),
+        };
+        Some(range)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FieldType {
     Primitive(PrimitiveType),
-    Array(PrimitiveType, u8),
+    /// Array with its length, which is limited only by the payload size.
+    Array(PrimitiveType, u16),
 }
 
 impl FieldType {
@@ -181,12 +270,90 @@ pub struct Field {
     pub min_value: Option<f32>,
     pub max_value: Option<f32>,
     pub multiplier: Option<String>,
-    pub default: Option<String>,
+    pub default: Option<DefaultValue>,
     pub instance: Option<bool>,
     pub invalid: Option<String>,
     pub description: Option<String>,
 }
 
+/// The `default` attribute of a field, checked against the field type.
+///
+/// The value of an array field is the value of each element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefaultValue {
+    Int(i128),
+    Float(f64),
+    /// An entry of the enum referenced by the field.
+    Entry(Ident),
+}
+
+/// The `invalid` attribute of a field, checked against the field type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidValue {
+    /// The value of one element, either an integer or a float.
+    pub value: DefaultValue,
+    /// Only the first element of an array is invalid, `[value,]` in the
+    /// definition, instead of every element, `[value]`.
+    pub first_only: bool,
+}
+
+impl Field {
+    /// Parses the `invalid` attribute against the field type.
+    ///
+    /// The value is an integer, a float including `NaN`, or a limit of the
+    /// type like `UINT16_MAX`. Returns `None` for enum and char fields and
+    /// for values, which don't fit the type, which the normaliser rejects.
+    pub fn invalid_value(&self) -> Option<InvalidValue> {
+        let invalid = self.invalid.as_deref()?.trim();
+        let typ = self.r#type.primitive_type();
+        if self.r#enum.is_some() || typ == PrimitiveType::Char {
+            return None;
+        }
+
+        let (value, first_only) = match (self.r#type, invalid.strip_prefix('[')) {
+            (FieldType::Array(_, _), Some(rest)) => {
+                let value = rest.strip_suffix(']')?;
+                match value.strip_suffix(',') {
+                    Some(first) => (first.trim(), true),
+                    None => (value.trim(), false),
+                }
+            }
+            (FieldType::Primitive(_), None) => (invalid, false),
+            _ => return None,
+        };
+
+        let value = match typ.int_range() {
+            None => DefaultValue::Float(value.parse().ok()?),
+            Some((min, max)) => {
+                let value = match value {
+                    "INT8_MIN" => i8::MIN.into(),
+                    "INT8_MAX" => i8::MAX.into(),
+                    "UINT8_MAX" => u8::MAX.into(),
+                    "INT16_MIN" => i16::MIN.into(),
+                    "INT16_MAX" => i16::MAX.into(),
+                    "UINT16_MAX" => u16::MAX.into(),
+                    "INT32_MIN" => i32::MIN.into(),
+                    "INT32_MAX" => i32::MAX.into(),
+                    "UINT32_MAX" => u32::MAX.into(),
+                    "INT64_MIN" => i64::MIN.into(),
+                    "INT64_MAX" => i64::MAX.into(),
+                    "UINT64_MAX" => u64::MAX.into(),
+                    value => match value.strip_prefix("0x") {
+                        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+                        None => value.parse().ok()?,
+                    },
+                };
+                if !(min..=max).contains(&value) {
+                    return None;
+                }
+                DefaultValue::Int(value)
+            }
+        };
+
+        Some(InvalidValue { value, first_only })
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum FieldKind {
     Regular,
@@ -212,6 +379,11 @@ impl Message {
             .sum()
     }
 
+    /// Whether the message has no fields at all, so its payload is always empty.
+    pub fn payload_is_empty(&self) -> bool {
+        self.fields.is_empty() && self.extension_fields.is_empty()
+    }
+
     pub fn all_fields(&self) -> impl Iterator<Item = (&Field, FieldKind)> {
         let fields = self
             .fields
@@ -225,6 +397,20 @@ impl Message {
         fields.chain(ext_fields)
     }
 
+    /// Whether the message is addressed to a component with `uint8_t`
+    /// fields `target_system` and `target_component`, like commands.
+    pub fn is_addressed(&self) -> bool {
+        let has_target = |name: &str| {
+            self.all_fields().any(|(field, _)| {
+                field.name.as_ref() == name
+                    && field.r#enum.is_none()
+                    && field.r#type == FieldType::Primitive(PrimitiveType::Uint8)
+            })
+        };
+
+        has_target("target_system") && has_target("target_component")
+    }
+
     pub fn sorted_fields(&self) -> Vec<&Field> {
         // See https://mavlink.io/en/guide/serialization.html#field_reordering
         let mut copy = self.fields.iter().collect::<Vec<_>>();
@@ -236,6 +422,14 @@ impl Message {
         });
         copy
     }
+
+    /// Fields in the order they are placed in the payload: sorted regular
+    /// fields followed by extension fields in declaration order.
+    pub fn wire_ordered_fields(&self) -> Vec<&Field> {
+        let mut fields = self.sorted_fields();
+        fields.extend(&self.extension_fields);
+        fields
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -245,6 +439,116 @@ pub struct MavlinkModule {
     pub dialect: Option<u8>,
     pub enums: Vec<Enum>,
     pub messages: Vec<Message>,
+    /// Enums and messages, which are defined in other modules and only
+    /// re-exported by this one.
+    pub reexports: Vec<Reexport>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reexport {
+    /// Name of the module, where the item is defined.
+    pub module: String,
+    pub name: Ident,
+}
+
+impl MavlinkModule {
+    /// Enums, which aren't referenced by any field of the messages.
+    pub fn unused_enums(&self) -> Vec<&Ident> {
+        let used: HashSet<&Ident> = self
+            .messages
+            .iter()
+            .flat_map(|message| message.fields.iter().chain(&message.extension_fields))
+            .filter_map(|field| field.r#enum.as_ref())
+            .collect();
+
+        self.enums
+            .iter()
+            .map(|r#enum| &r#enum.name)
+            .filter(|name| !used.contains(name))
+            .collect()
+    }
+
+    /// Removes the enums returned by [`Self::unused_enums`].
+    pub fn prune_unused_enums(&mut self) {
+        let unused: HashSet<Ident> = self.unused_enums().into_iter().cloned().collect();
+        self.enums.retain(|r#enum| !unused.contains(&r#enum.name));
+        self.reexports
+            .retain(|reexport| !unused.contains(&reexport.name));
+    }
+
+    /// Removes the messages rejected by the filter and the enums, which were
+    /// referenced only by them.
+    ///
+    /// Enums, which weren't used by any message before, are kept.
+    pub fn filter_messages(&mut self, filter: &MessageFilter) {
+        let unused_before: HashSet<Ident> = self.unused_enums().into_iter().cloned().collect();
+
+        let removed: HashSet<Ident> = self
+            .messages
+            .iter()
+            .filter(|message| !filter.retains(message))
+            .map(|message| message.name.clone())
+            .collect();
+        self.messages.retain(|message| filter.retains(message));
+
+        let unused: HashSet<Ident> = self
+            .unused_enums()
+            .into_iter()
+            .filter(|name| !unused_before.contains(*name))
+            .cloned()
+            .collect();
+        self.enums.retain(|r#enum| !unused.contains(&r#enum.name));
+        self.reexports.retain(|reexport| {
+            !unused.contains(&reexport.name) && !removed.contains(&reexport.name)
+        });
+    }
+}
+
+/// Refers to a message by its id or by its name in the definition.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MessageSelector {
+    Id(u32),
+    Name(String),
+}
+
+impl MessageSelector {
+    pub fn matches(&self, message: &Message) -> bool {
+        match self {
+            Self::Id(id) => message.id == *id,
+            Self::Name(name) => message.name.as_ref() == name,
+        }
+    }
+}
+
+impl std::str::FromStr for MessageSelector {
+    type Err = std::convert::Infallible;
+
+    /// Numbers are parsed as ids and anything else as names.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse() {
+            Ok(id) => Self::Id(id),
+            Err(_) => Self::Name(s.to_owned()),
+        })
+    }
+}
+
+/// Which messages of a module to generate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageFilter {
+    /// Only the selected messages.
+    Allow(BTreeSet<MessageSelector>),
+    /// All messages except the selected ones.
+    Deny(BTreeSet<MessageSelector>),
+}
+
+impl MessageFilter {
+    /// Whether the message should be generated.
+    pub fn retains(&self, message: &Message) -> bool {
+        match self {
+            Self::Allow(selectors) => selectors.iter().any(|s| s.matches(message)),
+            Self::Deny(selectors) => !selectors.iter().any(|s| s.matches(message)),
+        }
+    }
 }
 
 impl Enum {
@@ -285,7 +589,9 @@ impl Message {
             crc.digest(b" ");
 
             if let FieldType::Array(_, size) = field.r#type {
-                crc.digest(&[size]);
+                // The length is a single byte, longer arrays don't fit into
+                // the payload anyway
+                crc.digest(&[size as u8]);
             }
         }
 
@@ -368,22 +674,18 @@ impl std::str::FromStr for Ident {
             "volatile",
         ];
 
-        // TODO: ideally, it should parse identifiers in the same way python or
-        // rust parses them:
-        // identifier   ::=  xid_start xid_continue*
-        //
-        // Currently they will accept symbols like :: or ^ just fine.
-        // Rust compiler uses https://github.com/unicode-rs/unicode-xid/
-
-        if s.is_empty() || s == "_" {
+        // Same grammar as the Rust compiler uses:
+        // identifier ::= (xid_start | "_") xid_continue*
+        if s == "_" {
             return Err(InvalidIdentError);
         }
 
-        if matches!(s.chars().next(), Some('0'..='9')) {
-            return Err(InvalidIdentError);
-        }
-
-        if s.contains(char::is_whitespace) {
+        let mut chars = s.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|first| first == '_' || first.is_xid_start())
+            && chars.all(UnicodeXID::is_xid_continue);
+        if !valid {
             return Err(InvalidIdentError);
         }
 
@@ -395,13 +697,82 @@ impl std::str::FromStr for Ident {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct InvalidTypeError;
+/// Field type, which isn't a MAVLink type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidTypeError {
+    /// The part of the type, which failed to parse, e.g. `uint8t` of
+    /// `uint8t[4]`.
+    pub r#type: String,
+    /// The rest of the type, e.g. `[4]` of `uint8t[4]`, which is kept in
+    /// the suggestion.
+    pub suffix: String,
+}
+
+impl InvalidTypeError {
+    /// Names of the types accepted in the definitions.
+    pub const VALID_TYPES: &'static [&'static str] = &[
+        "float",
+        "double",
+        "char",
+        "int8_t",
+        "uint8_t",
+        "uint8_t_mavlink_version",
+        "int16_t",
+        "uint16_t",
+        "int32_t",
+        "uint32_t",
+        "int64_t",
+        "uint64_t",
+    ];
+
+    fn new(r#type: &str) -> Self {
+        Self {
+            r#type: r#type.to_owned(),
+            suffix: String::new(),
+        }
+    }
+
+    /// The valid type closest to the invalid one with the same suffix, if
+    /// it's probably a typo, e.g. `uint8_t[4]` for `uint8t[4]`.
+    pub fn suggestion(&self) -> Option<String> {
+        const MAX_DISTANCE: usize = 3;
+
+        Self::VALID_TYPES
+            .iter()
+            .map(|valid| (edit_distance(&self.r#type, valid), *valid))
+            .filter(|(distance, _)| *distance <= MAX_DISTANCE)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, valid)| format!("{valid}{}", self.suffix))
+    }
+}
 
 impl std::fmt::Display for InvalidTypeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "invalid field type")
+        write!(f, "unknown type '{}{}'", self.r#type, self.suffix)?;
+        if let Some(suggestion) = self.suggestion() {
+            write!(f, ", did you mean '{suggestion}'?")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidTypeError {}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            row[j + 1] = substitution.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
     }
+
+    prev[b.len()]
 }
 
 impl std::str::FromStr for PrimitiveType {
@@ -421,7 +792,7 @@ impl std::str::FromStr for PrimitiveType {
             "uint32_t" => Ok(Self::Uint32),
             "int64_t" => Ok(Self::Int64),
             "uint64_t" => Ok(Self::Uint64),
-            _ => Err(InvalidTypeError),
+            _ => Err(InvalidTypeError::new(s)),
         }
     }
 }
@@ -433,10 +804,15 @@ impl std::str::FromStr for FieldType {
         if let Some(without_closing_bracket) = s.strip_suffix(']') {
             let (type_part, size_part) = without_closing_bracket
                 .split_once('[')
-                .ok_or(InvalidTypeError)?;
+                .ok_or_else(|| InvalidTypeError::new(s))?;
 
-            let typ = type_part.parse()?;
-            let size = size_part.parse().map_err(|_| InvalidTypeError)?;
+            let typ = type_part
+                .parse()
+                .map_err(|err: InvalidTypeError| InvalidTypeError {
+                    suffix: s[type_part.len()..].to_owned(),
+                    ..err
+                })?;
+            let size = size_part.parse().map_err(|_| InvalidTypeError::new(s))?;
 
             Ok(Self::Array(typ, size))
         } else {
@@ -528,6 +904,15 @@ mod tests {
         Ident::from_str("").unwrap_err();
         Ident::from_str("_").unwrap_err();
         Ident::from_str(" ::<> ").unwrap_err();
+        Ident::from_str("foo::bar").unwrap_err();
+        Ident::from_str("a^b").unwrap_err();
+        Ident::from_str("1abc").unwrap_err();
+        Ident::from_str("dash-case").unwrap_err();
+        assert_eq!(Ident::from_str("café").unwrap(), Ident("café".to_owned()));
+        assert_eq!(
+            Ident::from_str("_private").unwrap(),
+            Ident("_private".to_owned())
+        );
         assert_eq!(Ident::from_str("HELLO").unwrap(), Ident("HELLO".to_owned()));
         assert_eq!(
             Ident::from_str("THIS_SHOULD_BE_VALID").unwrap(),
@@ -536,6 +921,24 @@ mod tests {
         assert_eq!(Ident::from_str("A").unwrap(), Ident("A".to_owned()));
     }
 
+    #[test]
+    fn test_ident_keyword_escape() {
+        let cases = [
+            ("type", KeywordEscape::Raw, "r#type"),
+            ("match", KeywordEscape::Raw, "r#match"),
+            ("self", KeywordEscape::Suffix, "self_"),
+            ("Self", KeywordEscape::Suffix, "Self_"),
+            ("TYPE", KeywordEscape::None, "TYPE"),
+            ("type_mask", KeywordEscape::None, "type_mask"),
+        ];
+
+        for (input, escape, escaped) in cases {
+            let ident = Ident::from_str(input).unwrap();
+            assert_eq!(ident.keyword_escape(), escape, "case {input:?}");
+            assert_eq!(escape.apply(input), escaped, "case {input:?}");
+        }
+    }
+
     #[test]
     fn test_field_type_parse() {
         let valid_cases = [
@@ -572,6 +975,11 @@ mod tests {
                 "uint64_t[100]",
                 FieldType::Array(PrimitiveType::Uint64, 100),
             ),
+            ("int16_t[300]", FieldType::Array(PrimitiveType::Int16, 300)),
+            (
+                "uint8_t[65535]",
+                FieldType::Array(PrimitiveType::Uint8, 65535),
+            ),
         ];
 
         for (input, output) in valid_cases {
@@ -584,7 +992,7 @@ mod tests {
             "not_found",
             "INT8_T",
             "int16_t[9][10]",
-            "int16_t[300]",
+            "int16_t[70000]",
         ];
 
         for case in invalid_cases {
@@ -593,6 +1001,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_field_type_parse_error() {
+        let cases = [
+            ("uint8t", "uint8t", Some("uint8_t")),
+            ("uint8t[4]", "uint8t", Some("uint8_t[4]")),
+            ("flaot", "flaot", Some("float")),
+            ("flaot[16]", "flaot", Some("float[16]")),
+            ("int16_t[70000]", "int16_t[70000]", None),
+            ("turbo_fish", "turbo_fish", None),
+        ];
+
+        for (input, r#type, suggestion) in cases {
+            let err = FieldType::from_str(input).unwrap_err();
+            assert_eq!(err.r#type, r#type, "case {input:?}");
+            assert_eq!(err.suggestion().as_deref(), suggestion, "case {input:?}");
+        }
+
+        assert_eq!(
+            FieldType::from_str("uint8t").unwrap_err().to_string(),
+            "unknown type 'uint8t', did you mean 'uint8_t'?"
+        );
+        assert_eq!(
+            FieldType::from_str("uint8t[4]").unwrap_err().to_string(),
+            "unknown type 'uint8t[4]', did you mean 'uint8_t[4]'?"
+        );
+        assert_eq!(
+            FieldType::from_str("turbo_fish").unwrap_err().to_string(),
+            "unknown type 'turbo_fish'"
+        );
+    }
+
     #[test]
     fn test_min_size() {
         let mut enm = Enum {
@@ -606,12 +1045,14 @@ mod tests {
                     description: None,
                     dev_status: None,
                     value: 0,
+                    params: vec![],
                 },
                 Entry {
                     name: Ident::from_str("TEST_1").unwrap(),
                     description: None,
                     dev_status: None,
                     value: 1,
+                    params: vec![],
                 },
             ],
         };
@@ -845,4 +1286,130 @@ mod tests {
 
         assert_eq!(message.extra_crc(), 50);
     }
+
+    #[test]
+    fn test_crc_extra_sorts_fields() {
+        let message = Message {
+            name: "SYSTEM_TIME".parse().unwrap(),
+            id: 2,
+            dev_status: None,
+            description: None,
+            // Smaller field goes first to check the wire order is used
+            fields: vec![
+                Field {
+                    name: "time_boot_ms".parse().unwrap(),
+                    r#type: FieldType::Primitive(PrimitiveType::Uint32),
+                    ..default_field()
+                },
+                Field {
+                    name: "time_unix_usec".parse().unwrap(),
+                    r#type: FieldType::Primitive(PrimitiveType::Uint64),
+                    ..default_field()
+                },
+            ],
+            extension_fields: vec![],
+        };
+
+        assert_eq!(message.extra_crc(), 137);
+    }
+
+    #[test]
+    fn test_empty_message() {
+        let message = Message {
+            name: "EMPTY_PING".parse().unwrap(),
+            id: 42000,
+            dev_status: None,
+            description: None,
+            fields: vec![],
+            extension_fields: vec![],
+        };
+
+        assert!(message.payload_is_empty());
+        assert_eq!(message.wire_size(), 0);
+        // Only the name and the trailing space are hashed
+        assert_eq!(message.extra_crc(), 65);
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let field = |r#type: &str, invalid: &str| Field {
+            r#type: r#type.parse().unwrap(),
+            invalid: Some(invalid.into()),
+            ..default_field()
+        };
+        let int = |value| {
+            Some(InvalidValue {
+                value: DefaultValue::Int(value),
+                first_only: false,
+            })
+        };
+
+        assert_eq!(field("uint16_t", "UINT16_MAX").invalid_value(), int(65535));
+        assert_eq!(field("int8_t", "INT8_MIN").invalid_value(), int(-128));
+        assert_eq!(field("int32_t", "-1").invalid_value(), int(-1));
+        assert_eq!(
+            field("uint16_t[4]", "[UINT16_MAX]").invalid_value(),
+            int(65535)
+        );
+        assert_eq!(
+            field("uint8_t[4]", "[0,]").invalid_value(),
+            Some(InvalidValue {
+                value: DefaultValue::Int(0),
+                first_only: true,
+            })
+        );
+
+        let nan = field("float", "NaN").invalid_value().unwrap();
+        assert!(matches!(nan.value, DefaultValue::Float(value) if value.is_nan()));
+
+        for (r#type, invalid) in [
+            ("uint8_t", "UINT16_MAX"),
+            ("uint8_t", "-1"),
+            ("uint16_t", "[0]"),
+            ("uint16_t[4]", "0"),
+            ("float", "one"),
+            ("char[4]", "[0]"),
+        ] {
+            assert_eq!(
+                field(r#type, invalid).invalid_value(),
+                None,
+                "case {type:?} {invalid:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_wire_ordered_fields() {
+        let field = |name: &str, r#type| Field {
+            name: name.parse().unwrap(),
+            r#type,
+            ..default_field()
+        };
+
+        let message = Message {
+            name: "TEST".parse().unwrap(),
+            id: 1,
+            dev_status: None,
+            description: None,
+            fields: vec![
+                field("a", FieldType::Primitive(PrimitiveType::Uint8)),
+                field("b", FieldType::Primitive(PrimitiveType::Uint32)),
+                field("c", FieldType::Array(PrimitiveType::Uint16, 4)),
+                field("d", FieldType::Primitive(PrimitiveType::Uint8)),
+                field("e", FieldType::Primitive(PrimitiveType::Uint32)),
+            ],
+            extension_fields: vec![
+                field("f", FieldType::Primitive(PrimitiveType::Uint8)),
+                field("g", FieldType::Primitive(PrimitiveType::Uint32)),
+            ],
+        };
+
+        let names = message
+            .wire_ordered_fields()
+            .into_iter()
+            .map(|field| field.name.as_ref())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["b", "e", "c", "a", "d", "f", "g"]);
+    }
 }
@@ -0,0 +1,91 @@
+use crate::model::{Ident, MavlinkModule, Reexport};
+
+/// Module, which is already normalised and named.
+#[derive(Debug, Clone, Copy)]
+pub struct IncludedModule<'a> {
+    pub name: &'a str,
+    pub module: &'a MavlinkModule,
+}
+
+impl IncludedModule<'_> {
+    /// Returns the name of the module, where the item is actually defined.
+    fn origin(&self, name: &Ident) -> Option<&str> {
+        if let Some(reexport) = self.module.reexports.iter().find(|r| &r.name == name) {
+            Some(&reexport.module)
+        } else if self.module.enums.iter().any(|e| &e.name == name)
+            || self.module.messages.iter().any(|m| &m.name == name)
+        {
+            Some(self.name)
+        } else {
+            None
+        }
+    }
+}
+
+/// Finds the enums and messages of the module, which are defined exactly the
+/// same in one of the included modules, so that they can be re-exported
+/// from where they are originally defined.
+///
+/// Enums extended by the module are different types and aren't re-exported,
+/// and neither are messages using them.
+pub fn find_reexports(module: &MavlinkModule, includes: &[IncludedModule<'_>]) -> Vec<Reexport> {
+    let mut reexports: Vec<Reexport> = vec![];
+
+    for r#enum in &module.enums {
+        let origin = includes.iter().find_map(|include| {
+            include
+                .module
+                .enums
+                .contains(r#enum)
+                .then(|| include.origin(&r#enum.name))
+                .flatten()
+        });
+
+        if let Some(origin) = origin {
+            reexports.push(Reexport {
+                module: origin.to_owned(),
+                name: r#enum.name.clone(),
+            });
+        }
+    }
+
+    let enum_origin = |name: &Ident| {
+        reexports
+            .iter()
+            .find(|reexport| &reexport.name == name)
+            .map(|reexport| reexport.module.clone())
+    };
+
+    let mut message_reexports = vec![];
+
+    for message in &module.messages {
+        let origin = includes.iter().find_map(|include| {
+            if !include.module.messages.contains(message) {
+                return None;
+            }
+
+            let same_enums = message
+                .fields
+                .iter()
+                .chain(&message.extension_fields)
+                .filter_map(|field| field.r#enum.as_ref())
+                .all(|name| enum_origin(name).as_deref() == include.origin(name));
+
+            if same_enums {
+                include.origin(&message.name)
+            } else {
+                None
+            }
+        });
+
+        if let Some(origin) = origin {
+            message_reexports.push(Reexport {
+                module: origin.to_owned(),
+                name: message.name.clone(),
+            });
+        }
+    }
+
+    reexports.extend(message_reexports);
+    reexports
+}
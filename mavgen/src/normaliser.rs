@@ -1,10 +1,14 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 use crate::{
+    codegen::rust::naming,
     flatten,
     model::{
-        DevStatus, Entry, Enum, Field, FieldType, Ident, MavlinkModule, Message, PrimitiveType,
-        RustSizeType,
+        DefaultValue, DevStatus, Entry, Enum, Field, FieldType, Ident, MavlinkModule, Message,
+        Param, PrimitiveType, RustSizeType,
     },
     xml,
 };
@@ -35,21 +39,49 @@ pub enum Error {
         entry_2: Ident,
         value: u64,
     },
+    NameCollision {
+        item: &'static str,
+        super_item: Option<Ident>,
+        name_1: Ident,
+        name_2: Ident,
+    },
     RepeatedMessageId {
         msg_1: Ident,
         msg_2: Ident,
         id: u32,
+        path_1: PathBuf,
+        path_2: PathBuf,
+    },
+    ConflictingMessageDefinition {
+        message: Ident,
+        id: u32,
+        path_1: PathBuf,
+        path_2: PathBuf,
+    },
+    /// The same fields of a message are split differently into regular and
+    /// extension fields, which changes the wire order and the CRC extra.
+    ConflictingExtensions {
+        message: Ident,
+        id: u32,
+        path_1: PathBuf,
+        path_2: PathBuf,
     },
     InvalidFieldType {
         message: Ident,
         field: Ident,
         r#type: String,
+        /// The valid type closest to the invalid one, if it's probably a typo.
+        suggestion: Option<String>,
     },
     InvalidEnumReference {
         message: Ident,
         field: Ident,
         r#enum: String,
     },
+    ZeroLengthArray {
+        message: Ident,
+        field: Ident,
+    },
     NoSubItems {
         item: &'static str,
         sub_items: &'static str,
@@ -66,6 +98,28 @@ pub enum Error {
         size: usize,
         max_size: usize,
     },
+    InvalidParamIndex {
+        entry: Ident,
+        index: u8,
+    },
+    RepeatedParamIndex {
+        entry: Ident,
+        index: u8,
+    },
+    InvalidDefaultValue {
+        message: Ident,
+        field: Ident,
+        value: String,
+    },
+    /// The `invalid` attribute doesn't fit the field type, see
+    /// [`Field::invalid_value`].
+    BadInvalidValue {
+        message: Ident,
+        field: Ident,
+        value: String,
+    },
+    /// A warning reported as an error in the strict mode.
+    Strict(Warning),
 }
 
 impl std::fmt::Display for Error {
@@ -104,15 +158,12 @@ impl std::fmt::Display for Error {
                 item,
                 name
             ),
-            Error::InvalidEntry {
-                err: _,
-                r#enum,
-                entry,
-            } => write!(
+            Error::InvalidEntry { err, r#enum, entry } => write!(
                 f,
-                "{} invalid enum entry {}",
+                "{} invalid enum entry {}: {}",
                 MaybeSuper(Some(r#enum)),
                 entry,
+                err,
             ),
 
             Error::BitmaskWithoutValue { r#enum } => write!(
@@ -133,22 +184,76 @@ impl std::fmt::Display for Error {
                 entry_2,
                 value,
             ),
-            Error::RepeatedMessageId { msg_1, msg_2, id } => write!(
+            Error::NameCollision {
+                item,
+                super_item,
+                name_1,
+                name_2,
+            } => write!(
                 f,
-                "messages {} and {} have the same message ID of {}",
-                msg_1, msg_2, id,
+                "{} {}s {} and {} have the same name in generated code",
+                MaybeSuper(super_item.as_ref()),
+                item,
+                name_1,
+                name_2,
             ),
-            Error::InvalidFieldType {
+            Error::RepeatedMessageId {
+                msg_1,
+                msg_2,
+                id,
+                path_1,
+                path_2,
+            } => write!(
+                f,
+                "messages {} ({}) and {} ({}) have the same message ID of {}",
+                msg_1,
+                path_1.display(),
+                msg_2,
+                path_2.display(),
+                id,
+            ),
+            Error::ConflictingMessageDefinition {
                 message,
-                field,
-                r#type,
+                id,
+                path_1,
+                path_2,
             } => write!(
                 f,
-                "{} field {} has invalid type {:?}",
-                MaybeSuper(Some(message)),
+                "message {} with ID {} is defined with different fields in {} and {}",
+                message,
+                id,
+                path_1.display(),
+                path_2.display(),
+            ),
+            Error::ConflictingExtensions {
+                message,
+                id,
+                path_1,
+                path_2,
+            } => write!(
+                f,
+                "message {} with ID {} has different extension fields in {} and {}",
+                message,
+                id,
+                path_1.display(),
+                path_2.display(),
+            ),
+            Error::InvalidFieldType {
+                message,
                 field,
                 r#type,
-            ),
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "message {message} field {field}: unknown type '{}'",
+                    r#type
+                )?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean '{suggestion}'?")?;
+                }
+                Ok(())
+            }
             Error::InvalidEnumReference {
                 message,
                 field,
@@ -160,6 +265,12 @@ impl std::fmt::Display for Error {
                 field,
                 r#enum,
             ),
+            Error::ZeroLengthArray { message, field } => write!(
+                f,
+                "{} field {} is an array of zero length",
+                MaybeSuper(Some(message)),
+                field,
+            ),
             Error::NoSubItems {
                 item,
                 sub_items,
@@ -189,12 +300,97 @@ impl std::fmt::Display for Error {
                 size,
                 max_size,
             ),
+            Error::InvalidParamIndex { entry, index } => write!(
+                f,
+                "{} param has invalid index {}, expected 1 to {}",
+                MaybeSuper(Some(entry)),
+                index,
+                MAX_PARAM_INDEX,
+            ),
+            Error::RepeatedParamIndex { entry, index } => write!(
+                f,
+                "{} param with index {} is defined multiple times",
+                MaybeSuper(Some(entry)),
+                index,
+            ),
+            Error::InvalidDefaultValue {
+                message,
+                field,
+                value,
+            } => write!(
+                f,
+                "{} field {} has invalid default value {:?}",
+                MaybeSuper(Some(message)),
+                field,
+                value,
+            ),
+            Error::BadInvalidValue {
+                message,
+                field,
+                value,
+            } => write!(
+                f,
+                "{} field {} has invalid value {:?}, which doesn't fit its type",
+                MaybeSuper(Some(message)),
+                field,
+                value,
+            ),
+            Error::Strict(warning) => warning.fmt(f),
         }
     }
 }
 
 impl std::error::Error for Error {}
 
+/// Suspicious, but valid definition, see [`check_warnings`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// The entry name doesn't start with the name of its enum, e.g.
+    /// `LIMITS_INIT` of `LIMITS_STATE`, contrary to the MAVLink convention.
+    EntryWithoutEnumPrefix { r#enum: Ident, entry: Ident },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::EntryWithoutEnumPrefix { r#enum, entry } => write!(
+                f,
+                "[{}] entry {} doesn't start with the enum name",
+                r#enum, entry,
+            ),
+        }
+    }
+}
+
+/// Finds suspicious definitions in the normalised module, skipping the
+/// re-exported items, which are checked in their own modules.
+pub fn check_warnings(module: &MavlinkModule) -> Vec<Warning> {
+    let reexported = module
+        .reexports
+        .iter()
+        .map(|reexport| &reexport.name)
+        .collect::<HashSet<_>>();
+
+    let mut warnings = vec![];
+    for r#enum in &module.enums {
+        if reexported.contains(&r#enum.name) {
+            continue;
+        }
+
+        let prefix = format!("{}_", r#enum.name);
+        for entry in &r#enum.entries {
+            if !entry.name.as_ref().starts_with(&prefix) {
+                warnings.push(Warning::EntryWithoutEnumPrefix {
+                    r#enum: r#enum.name.clone(),
+                    entry: entry.name.clone(),
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
 fn non_empty(str: String) -> Option<String> {
     if str.is_empty() {
         None
@@ -203,16 +399,46 @@ fn non_empty(str: String) -> Option<String> {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct NormalisedEnum {
     size: RustSizeType,
+    bitmask: bool,
+    /// Names and values of entries to resolve default values of fields.
+    entries: Vec<(Ident, u64)>,
+}
+
+/// Wire-relevant part of a field definition, used to compare re-declared
+/// messages.
+#[derive(Debug, Clone, PartialEq)]
+struct FieldLayout {
+    name: String,
+    r#type: String,
+    r#enum: Option<String>,
+}
+
+impl From<&xml::Field> for FieldLayout {
+    fn from(field: &xml::Field) -> Self {
+        Self {
+            name: field.name.clone(),
+            r#type: field.r#type.clone(),
+            r#enum: field.r#enum.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AllocatedMessage {
+    name: Ident,
+    path: PathBuf,
+    fields: Vec<FieldLayout>,
+    extension_fields: Vec<FieldLayout>,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Normaliser {
     enums: HashMap<Ident, NormalisedEnum>,
     messages: HashSet<Ident>,
-    allocated_message_ids: HashMap<u32, Ident>,
+    allocated_message_ids: HashMap<u32, AllocatedMessage>,
 
     errors: Vec<Error>,
 }
@@ -223,7 +449,7 @@ impl Normaliser {
         module: flatten::MavlinkModule,
     ) -> Result<MavlinkModule, Vec<Error>> {
         let enums = self.normalise_enums(module.enums);
-        let messages = self.normalise_messages(module.messages);
+        let messages = self.normalise_messages(module.messages, module.message_paths);
 
         if self.errors.is_empty() {
             Ok(MavlinkModule {
@@ -232,6 +458,7 @@ impl Normaliser {
                 dialect: module.dialect,
                 enums,
                 messages,
+                reexports: vec![],
             })
         } else {
             Err(self.errors)
@@ -294,6 +521,12 @@ impl Normaliser {
             final_enum.name.clone(),
             NormalisedEnum {
                 size: final_enum.min_rust_size(),
+                bitmask: final_enum.bitmask,
+                entries: final_enum
+                    .entries
+                    .iter()
+                    .map(|entry| (entry.name.clone(), entry.value))
+                    .collect(),
             },
         );
 
@@ -317,6 +550,7 @@ impl Normaliser {
         let mut result = Vec::with_capacity(entries.len());
         let mut allocated_values = HashMap::with_capacity(entries.len());
         let mut allocated_names = HashSet::with_capacity(entries.len());
+        let mut allocated_rust_names = HashMap::with_capacity(entries.len());
 
         for (entry, value) in entries.into_iter().zip(values) {
             let Ok(name) = entry.name.parse::<Ident>() else {
@@ -329,6 +563,7 @@ impl Normaliser {
 
             let description = entry.description.map(|desc| desc.into_string());
             let dev_status = entry.dev_status.map(DevStatus::from);
+            let params = normalise_params(&name, entry.params)?;
 
             let new = allocated_names.insert(name.clone());
             if !new {
@@ -339,6 +574,25 @@ impl Normaliser {
                 });
             }
 
+            let rust_name = naming::pascal_case(name.as_ref());
+            if rust_name.is_empty() {
+                return Err(Error::InvalidName {
+                    item: "entry",
+                    super_item: Some(r#enum.clone()),
+                    name: name.to_string(),
+                });
+            }
+
+            let old = allocated_rust_names.insert(rust_name, name.clone());
+            if let Some(old_entry) = old {
+                return Err(Error::NameCollision {
+                    item: "entry",
+                    super_item: Some(r#enum.clone()),
+                    name_1: old_entry,
+                    name_2: name,
+                });
+            }
+
             let old = allocated_values.insert(value, name.clone());
             if let Some(old_entry) = old {
                 return Err(Error::RepeatedEntryValue {
@@ -354,25 +608,107 @@ impl Normaliser {
                 description,
                 dev_status,
                 value,
+                params,
             })
         }
 
         Ok(result)
     }
 
-    fn normalise_messages(&mut self, messages: Vec<xml::Message>) -> Vec<Message> {
+    fn normalise_messages(
+        &mut self,
+        messages: Vec<xml::Message>,
+        paths: Vec<PathBuf>,
+    ) -> Vec<Message> {
         messages
             .into_iter()
-            .filter_map(|message| match self.normalise_message(message) {
-                Ok(ok) => Some(ok),
-                Err(err) => {
-                    self.errors.push(err);
-                    None
+            .zip(paths)
+            .filter_map(|(message, path)| {
+                match self.allocate_message_id(&message, &path) {
+                    Ok(true) => {}
+                    // The same message is included from several files
+                    Ok(false) => return None,
+                    Err(err) => {
+                        self.errors.push(err);
+                        return None;
+                    }
+                }
+
+                match self.normalise_message(message) {
+                    Ok(ok) => Some(ok),
+                    Err(err) => {
+                        self.errors.push(err);
+                        None
+                    }
                 }
             })
             .collect()
     }
 
+    /// Allocates the message ID, returns `false` if the very same message
+    /// was already declared.
+    fn allocate_message_id(&mut self, message: &xml::Message, path: &Path) -> Result<bool, Error> {
+        // Invalid names are reported during normalisation of the message
+        let Ok(name) = message.name.parse::<Ident>() else {
+            return Ok(true);
+        };
+
+        let fields = message.fields.iter().map(FieldLayout::from).collect();
+        let extension_fields = message
+            .extension_fields
+            .iter()
+            .map(FieldLayout::from)
+            .collect();
+
+        let Some(allocated) = self.allocated_message_ids.get(&message.id) else {
+            self.allocated_message_ids.insert(
+                message.id,
+                AllocatedMessage {
+                    name,
+                    path: path.to_owned(),
+                    fields,
+                    extension_fields,
+                },
+            );
+            return Ok(true);
+        };
+
+        if allocated.name != name {
+            return Err(Error::RepeatedMessageId {
+                msg_1: allocated.name.clone(),
+                msg_2: name,
+                id: message.id,
+                path_1: allocated.path.clone(),
+                path_2: path.to_owned(),
+            });
+        }
+
+        if allocated.fields == fields && allocated.extension_fields == extension_fields {
+            return Ok(false);
+        }
+
+        let same_layout = allocated
+            .fields
+            .iter()
+            .chain(&allocated.extension_fields)
+            .eq(fields.iter().chain(&extension_fields));
+        if same_layout {
+            return Err(Error::ConflictingExtensions {
+                message: name,
+                id: message.id,
+                path_1: allocated.path.clone(),
+                path_2: path.to_owned(),
+            });
+        }
+
+        Err(Error::ConflictingMessageDefinition {
+            message: name,
+            id: message.id,
+            path_1: allocated.path.clone(),
+            path_2: path.to_owned(),
+        })
+    }
+
     fn normalise_message(&mut self, message: xml::Message) -> Result<Message, Error> {
         let Ok(name) = message.name.parse::<Ident>() else {
             return Err(Error::InvalidName {
@@ -391,28 +727,12 @@ impl Normaliser {
             });
         }
 
-        let old = self.allocated_message_ids.insert(message.id, name.clone());
-        if let Some(old) = old {
-            return Err(Error::RepeatedMessageId {
-                msg_1: old,
-                msg_2: name,
-                id: message.id,
-            });
-        }
-
         let description = message.description.map(|desc| desc.into_string());
         let dev_status = message.dev_status.map(DevStatus::from);
 
-        if message.fields.is_empty() {
-            return Err(Error::NoSubItems {
-                item: "message",
-                sub_items: "fields",
-                name,
-            });
-        }
-
         let (fields, extension_fields) =
             self.normalise_fields(&name, message.fields, message.extension_fields)?;
+        check_payload_size(&name, &fields, &extension_fields)?;
 
         Ok(Message {
             name,
@@ -435,6 +755,8 @@ impl Normaliser {
 
         let mut allocated_field_names =
             HashSet::with_capacity(fields.len() + extension_fields.len());
+        let mut allocated_rust_names =
+            HashMap::with_capacity(fields.len() + extension_fields.len());
 
         #[derive(Clone, Copy)]
         enum FieldKind {
@@ -461,28 +783,23 @@ impl Normaliser {
                 });
             }
 
+            let rust_name = naming::snake_case(field.name.as_ref());
+            let old = allocated_rust_names.insert(rust_name, field.name.clone());
+            if let Some(old_field) = old {
+                return Err(Error::NameCollision {
+                    item: "field",
+                    super_item: Some(message.clone()),
+                    name_1: old_field,
+                    name_2: field.name,
+                });
+            }
+
             match kind {
                 FieldKind::Regular => result_fields.push(field),
                 FieldKind::Extension => result_extension_fields.push(field),
             }
         }
 
-        let total_wire_size: usize = result_fields
-            .iter()
-            .chain(&result_extension_fields)
-            .map(|field| field.r#type.wire_size())
-            .sum();
-
-        // Maximum size of payload is 255 bytes
-        // https://mavlink.io/en/guide/serialization.html#mavlink2_packet_format
-        if total_wire_size > 255 {
-            return Err(Error::MessageIsTooBig {
-                message: message.clone(),
-                size: total_wire_size,
-                max_size: 255,
-            });
-        }
-
         Ok((result_fields, result_extension_fields))
     }
 
@@ -495,13 +812,24 @@ impl Normaliser {
             });
         };
 
-        let Ok(r#type) = field.r#type.parse::<FieldType>() else {
-            return Err(Error::InvalidFieldType {
+        let r#type = match field.r#type.parse::<FieldType>() {
+            Ok(r#type) => r#type,
+            Err(err) => {
+                return Err(Error::InvalidFieldType {
+                    message: message.clone(),
+                    field: name,
+                    suggestion: err.suggestion(),
+                    r#type: field.r#type,
+                });
+            }
+        };
+
+        if let FieldType::Array(_, 0) = r#type {
+            return Err(Error::ZeroLengthArray {
                 message: message.clone(),
                 field: name,
-                r#type: field.r#type,
             });
-        };
+        }
 
         let r#enum = if let Some(r#enum) = field.r#enum {
             let r#enum = self.resolve_enum_reference(r#enum, &r#type, message, &name)?;
@@ -510,7 +838,22 @@ impl Normaliser {
             None
         };
 
-        Ok(Field {
+        let default = match field.default {
+            Some(value) => {
+                let enum_meta = r#enum.as_ref().and_then(|r#enum| self.enums.get(r#enum));
+                let Some(default) = parse_default_value(&value, r#type, enum_meta) else {
+                    return Err(Error::InvalidDefaultValue {
+                        message: message.clone(),
+                        field: name,
+                        value,
+                    });
+                };
+                Some(default)
+            }
+            None => None,
+        };
+
+        let field = Field {
             name,
             r#type,
             print_format: field.print_format,
@@ -521,11 +864,26 @@ impl Normaliser {
             min_value: field.min_value,
             max_value: field.max_value,
             multiplier: field.multiplier,
-            default: field.default,
+            default,
             instance: field.instance,
             invalid: field.invalid,
             description: non_empty(field.description),
-        })
+        };
+
+        // Enum and char fields are skipped by `invalid_value`, anything else
+        // has to parse
+        let checked = field.r#enum.is_none() && r#type.primitive_type() != PrimitiveType::Char;
+        if let Some(value) = &field.invalid {
+            if checked && field.invalid_value().is_none() {
+                return Err(Error::BadInvalidValue {
+                    message: message.clone(),
+                    field: field.name,
+                    value: value.clone(),
+                });
+            }
+        }
+
+        Ok(field)
     }
 
     fn resolve_enum_reference(
@@ -577,29 +935,121 @@ impl Normaliser {
     }
 }
 
+/// Parses the `default` attribute into a value, which fits the field type or
+/// names an entry of the field's enum.
+fn parse_default_value(
+    value: &str,
+    r#type: FieldType,
+    r#enum: Option<&NormalisedEnum>,
+) -> Option<DefaultValue> {
+    if let Some(r#enum) = r#enum {
+        let int = parse_integer(value);
+        let entry = match int {
+            Some(int) => r#enum
+                .entries
+                .iter()
+                .find(|(_, entry_value)| i128::from(*entry_value) == int),
+            None => r#enum
+                .entries
+                .iter()
+                .find(|(entry_name, _)| entry_name.as_ref() == value),
+        };
+        if let Some((entry_name, _)) = entry {
+            return Some(DefaultValue::Entry(entry_name.clone()));
+        }
+
+        // Bitmasks may default to several flags or none of them
+        let int = int.filter(|_| r#enum.bitmask)?;
+        let bits = r#enum
+            .entries
+            .iter()
+            .fold(0, |bits, (_, entry_value)| bits | entry_value);
+        return u64::try_from(int)
+            .is_ok_and(|int| int & !bits == 0)
+            .then_some(DefaultValue::Int(int));
+    }
+
+    let Some((min, max)) = r#type.primitive_type().int_range() else {
+        return value.parse().ok().map(DefaultValue::Float);
+    };
+
+    let value = parse_integer(value)?;
+    (min..=max)
+        .contains(&value)
+        .then_some(DefaultValue::Int(value))
+}
+
+fn parse_integer(value: &str) -> Option<i128> {
+    match value.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// Commands have 7 parameters.
+const MAX_PARAM_INDEX: u8 = 7;
+
+fn normalise_params(entry: &Ident, params: Vec<xml::Param>) -> Result<Vec<Param>, Error> {
+    let mut result: Vec<Param> = Vec::with_capacity(params.len());
+
+    for param in params {
+        if !(1..=MAX_PARAM_INDEX).contains(&param.index) {
+            return Err(Error::InvalidParamIndex {
+                entry: entry.clone(),
+                index: param.index,
+            });
+        }
+
+        if result.iter().any(|other| other.index == param.index) {
+            return Err(Error::RepeatedParamIndex {
+                entry: entry.clone(),
+                index: param.index,
+            });
+        }
+
+        let description = param
+            .content
+            .map(|content| content.trim().to_owned())
+            .filter(|content| !content.is_empty());
+
+        result.push(Param {
+            index: param.index,
+            label: param.label,
+            units: param.units,
+            min_value: param.min_value,
+            max_value: param.max_value,
+            description,
+        });
+    }
+
+    result.sort_by_key(|param| param.index);
+
+    Ok(result)
+}
+
 fn derive_enum_entry_values(r#enum: &Ident, entries: &[xml::Entry]) -> Result<Vec<u64>, Error> {
     let mut result = Vec::with_capacity(entries.len());
 
-    let mut next_value = 1;
+    let mut next_value = Some(1);
 
     for entry in entries {
-        if let Some(value) = &entry.value {
-            match parse_entry_value(value.to_owned()) {
-                Ok(ok) => {
-                    result.push(ok);
-                    next_value = ok + 1;
-                }
-                Err(err) => {
-                    return Err(Error::InvalidEntry {
-                        err,
-                        r#enum: r#enum.clone(),
-                        entry: entry.name.clone(),
-                    })
-                }
+        let value = match &entry.value {
+            Some(value) => parse_entry_value(value.to_owned()),
+            None => next_value.ok_or(ParseEntryValueError::ImplicitValueOverflowed),
+        };
+
+        match value {
+            Ok(ok) => {
+                result.push(ok);
+                next_value = ok.checked_add(1);
+            }
+            Err(err) => {
+                return Err(Error::InvalidEntry {
+                    err,
+                    r#enum: r#enum.clone(),
+                    entry: entry.name.clone(),
+                })
             }
-        } else {
-            result.push(next_value);
-            next_value += 1;
         }
     }
 
@@ -610,9 +1060,28 @@ fn derive_enum_entry_values(r#enum: &Ident, entries: &[xml::Entry]) -> Result<Ve
 pub enum ParseEntryValueError {
     ParseInt(std::num::ParseIntError),
     ExponentiationOverflowed,
+    Negative,
+    NotInteger,
+    /// The entry without a value follows the entry with the maximum value.
+    ImplicitValueOverflowed,
     UnknownFormat,
 }
 
+impl std::fmt::Display for ParseEntryValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseEntryValueError::ParseInt(err) => write!(f, "{err}"),
+            ParseEntryValueError::ExponentiationOverflowed => write!(f, "value is too large"),
+            ParseEntryValueError::Negative => write!(f, "value is negative"),
+            ParseEntryValueError::NotInteger => write!(f, "value is not an integer"),
+            ParseEntryValueError::ImplicitValueOverflowed => {
+                write!(f, "implicit value is too large")
+            }
+            ParseEntryValueError::UnknownFormat => write!(f, "unknown value format"),
+        }
+    }
+}
+
 fn try_parse_python_exp_syntax(value: &str) -> Option<(&str, &str)> {
     let mut parts = value.split("**");
 
@@ -648,9 +1117,43 @@ fn parse_entry_value(mut value: String) -> Result<u64, ParseEntryValueError> {
             .ok_or(ParseEntryValueError::ExponentiationOverflowed);
     }
 
+    if let Ok(float) = value.parse::<f64>() {
+        return Err(if float < 0.0 {
+            ParseEntryValueError::Negative
+        } else {
+            ParseEntryValueError::NotInteger
+        });
+    }
+
     Err(ParseEntryValueError::UnknownFormat)
 }
 
+/// Checks that the fields fit into the payload, as arrays are limited only
+/// by their length type.
+fn check_payload_size(
+    message: &Ident,
+    fields: &[Field],
+    extension_fields: &[Field],
+) -> Result<(), Error> {
+    let total_wire_size: usize = fields
+        .iter()
+        .chain(extension_fields)
+        .map(|field| field.r#type.wire_size())
+        .sum();
+
+    // Maximum size of payload is 255 bytes
+    // https://mavlink.io/en/guide/serialization.html#mavlink2_packet_format
+    if total_wire_size > 255 {
+        return Err(Error::MessageIsTooBig {
+            message: message.clone(),
+            size: total_wire_size,
+            max_size: 255,
+        });
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use hard_xml::XmlRead;
@@ -672,6 +1175,18 @@ mod tests {
         assert!(parse_entry_value("".into()).is_err());
         assert!(parse_entry_value("2**2**2".into()).is_err());
         assert!(parse_entry_value("0xFFFFFFFFFFFFFFFFFFFFFFFFFFF".into()).is_err());
+        assert_eq!(
+            parse_entry_value("-1".into()),
+            Err(ParseEntryValueError::Negative)
+        );
+        assert_eq!(
+            parse_entry_value("1.5".into()),
+            Err(ParseEntryValueError::NotInteger)
+        );
+        assert_eq!(
+            parse_entry_value("one".into()),
+            Err(ParseEntryValueError::UnknownFormat)
+        );
     }
 
     #[test]
@@ -739,6 +1254,23 @@ mod tests {
             ],
         )
         .unwrap_err();
+
+        let err = derive_enum_entry_values(
+            &Ident::from_str("TEST").unwrap(),
+            &[
+                xml::Entry::new_min("TEST_1", Some("0xFFFFFFFFFFFFFFFF")),
+                xml::Entry::new_min("TEST_2", Option::<String>::None),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            Error::InvalidEntry {
+                err: ParseEntryValueError::ImplicitValueOverflowed,
+                r#enum: Ident::from_str("TEST").unwrap(),
+                entry: "TEST_2".into(),
+            }
+        );
     }
 
     #[test]
@@ -787,12 +1319,14 @@ mod tests {
                     description: None,
                     dev_status: None,
                     value: 1,
+                    params: vec![],
                 },
                 Entry {
                     name: Ident::from_str("TEST_2").unwrap(),
                     description: None,
                     dev_status: None,
                     value: 2,
+                    params: vec![],
                 }
             ]
         );
@@ -850,30 +1384,188 @@ mod tests {
     }
 
     #[test]
-    fn test_normalise_entries_repeated_value() {
+    fn test_normalise_entries_repeated_value() {
+        let normaliser = Normaliser::default();
+
+        let entries = vec![
+            xml::Entry::new_min("TEST_1", Option::<String>::None),
+            xml::Entry::new_min("TEST_2", Option::<String>::None),
+            xml::Entry::new_min("TEST_3", Option::<String>::None),
+            xml::Entry::new_min("TEST_4", Some("2")),
+            xml::Entry::new_min("TEST_5", Some("5")),
+        ];
+
+        let r#enum = Ident::from_str("TEST").unwrap();
+
+        let err = normaliser
+            .normalise_entries(&r#enum, false, entries)
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::RepeatedEntryValue {
+                r#enum,
+                entry_1: "TEST_4".parse().unwrap(),
+                entry_2: "TEST_2".parse().unwrap(),
+                value: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_normalise_entries_name_collision() {
+        let normaliser = Normaliser::default();
+
+        let entries = vec![
+            xml::Entry::new_min("TEST_A1", Option::<String>::None),
+            xml::Entry::new_min("TEST_B", Option::<String>::None),
+            xml::Entry::new_min("TestA1", Option::<String>::None),
+        ];
+
+        let r#enum = Ident::from_str("TEST").unwrap();
+
+        let err = normaliser
+            .normalise_entries(&r#enum, false, entries)
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::NameCollision {
+                item: "entry",
+                super_item: Some(r#enum),
+                name_1: "TEST_A1".parse().unwrap(),
+                name_2: "TestA1".parse().unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_normalise_entries_empty_rust_name() {
+        let normaliser = Normaliser::default();
+
+        let entries = vec![xml::Entry::new_min("__", Option::<String>::None)];
+
+        let r#enum = Ident::from_str("TEST").unwrap();
+
+        let err = normaliser
+            .normalise_entries(&r#enum, false, entries)
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::InvalidName {
+                item: "entry",
+                super_item: Some(r#enum),
+                name: "__".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_normalise_entries_params() {
+        let normaliser = Normaliser::default();
+
+        let entry = xml::Entry::from_str(
+            r#"<entry value="16" name="MAV_CMD_NAV_WAYPOINT" hasLocation="true" isDestination="true">
+                <description>Navigate to waypoint.</description>
+                <param index="1" label="Hold" units="s" minValue="0">Hold time.</param>
+                <param index="4" label="Yaw" units="deg">Desired yaw angle at waypoint.</param>
+                <param index="2" label="Accept Radius" units="m" minValue="0">Acceptance radius.</param>
+                <param index="7" label="Altitude" units="m">Altitude</param>
+                <param index="5"/>
+            </entry>"#,
+        )
+        .unwrap();
+
+        let r#enum = Ident::from_str("MAV_CMD").unwrap();
+
+        let ok = normaliser
+            .normalise_entries(&r#enum, false, vec![entry])
+            .unwrap();
+
+        let param = |index, label: &str, units: &str, min_value, description: &str| Param {
+            index,
+            label: Some(label.into()),
+            units: Some(units.into()),
+            min_value,
+            max_value: None,
+            description: Some(description.into()),
+        };
+
+        assert_eq!(
+            ok,
+            [Entry {
+                name: Ident::from_str("MAV_CMD_NAV_WAYPOINT").unwrap(),
+                description: Some("Navigate to waypoint.".into()),
+                dev_status: None,
+                value: 16,
+                params: vec![
+                    param(1, "Hold", "s", Some(0.0), "Hold time."),
+                    param(2, "Accept Radius", "m", Some(0.0), "Acceptance radius."),
+                    param(4, "Yaw", "deg", None, "Desired yaw angle at waypoint."),
+                    Param {
+                        index: 5,
+                        label: None,
+                        units: None,
+                        min_value: None,
+                        max_value: None,
+                        description: None,
+                    },
+                    param(7, "Altitude", "m", None, "Altitude"),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_normalise_entries_repeated_param_index() {
+        let normaliser = Normaliser::default();
+
+        let entry = xml::Entry::from_str(
+            r#"<entry value="16" name="MAV_CMD_NAV_WAYPOINT">
+                <param index="1">Hold time.</param>
+                <param index="1">Acceptance radius.</param>
+            </entry>"#,
+        )
+        .unwrap();
+
+        let r#enum = Ident::from_str("MAV_CMD").unwrap();
+
+        let err = normaliser
+            .normalise_entries(&r#enum, false, vec![entry])
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::RepeatedParamIndex {
+                entry: Ident::from_str("MAV_CMD_NAV_WAYPOINT").unwrap(),
+                index: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_normalise_entries_invalid_param_index() {
         let normaliser = Normaliser::default();
 
-        let entries = vec![
-            xml::Entry::new_min("TEST_1", Option::<String>::None),
-            xml::Entry::new_min("TEST_2", Option::<String>::None),
-            xml::Entry::new_min("TEST_3", Option::<String>::None),
-            xml::Entry::new_min("TEST_4", Some("2")),
-            xml::Entry::new_min("TEST_5", Some("5")),
-        ];
+        let entry = xml::Entry::from_str(
+            r#"<entry value="16" name="MAV_CMD_NAV_WAYPOINT">
+                <param index="8">Nothing.</param>
+            </entry>"#,
+        )
+        .unwrap();
 
-        let r#enum = Ident::from_str("TEST").unwrap();
+        let r#enum = Ident::from_str("MAV_CMD").unwrap();
 
         let err = normaliser
-            .normalise_entries(&r#enum, false, entries)
+            .normalise_entries(&r#enum, false, vec![entry])
             .unwrap_err();
 
         assert_eq!(
             err,
-            Error::RepeatedEntryValue {
-                r#enum,
-                entry_1: "TEST_4".parse().unwrap(),
-                entry_2: "TEST_2".parse().unwrap(),
-                value: 2
+            Error::InvalidParamIndex {
+                entry: Ident::from_str("MAV_CMD_NAV_WAYPOINT").unwrap(),
+                index: 8,
             }
         );
     }
@@ -919,30 +1611,35 @@ mod tests {
                         description: None,
                         dev_status: None,
                         value: 1,
+                        params: vec![],
                     },
                     Entry {
                         name: Ident::from_str("TEST_2").unwrap(),
                         description: None,
                         dev_status: None,
                         value: 2,
+                        params: vec![],
                     },
                     Entry {
                         name: Ident::from_str("TEST_3").unwrap(),
                         description: None,
                         dev_status: None,
                         value: 3,
+                        params: vec![],
                     },
                     Entry {
                         name: Ident::from_str("TEST_4").unwrap(),
                         description: None,
                         dev_status: None,
                         value: 10,
+                        params: vec![],
                     },
                     Entry {
                         name: Ident::from_str("TEST_5").unwrap(),
                         description: None,
                         dev_status: None,
                         value: 11,
+                        params: vec![],
                     },
                 ]
             }
@@ -1074,12 +1771,14 @@ mod tests {
                             description: None,
                             dev_status: None,
                             value: 0,
+                            params: vec![],
                         },
                         Entry {
                             name: "FAILURE_UNIT_SENSOR_ACCEL".parse().unwrap(),
                             description: None,
                             dev_status: None,
                             value: 1,
+                            params: vec![],
                         },
                     ],
                 },
@@ -1093,6 +1792,7 @@ mod tests {
                         description: Some("The camera has been found and is connected.".into()),
                         dev_status: None,
                         value: 1,
+                        params: vec![],
                     },],
                 },
                 Enum {
@@ -1106,24 +1806,28 @@ mod tests {
                             description: None,
                             dev_status: None,
                             value: 0,
+                            params: vec![],
                         },
                         Entry {
                             name: "OSD_PARAM_INVALID_SCREEN".parse().unwrap(),
                             description: None,
                             dev_status: None,
                             value: 1,
+                            params: vec![],
                         },
                         Entry {
                             name: "OSD_PARAM_INVALID_PARAMETER_INDEX".parse().unwrap(),
                             description: None,
                             dev_status: None,
                             value: 2,
+                            params: vec![],
                         },
                         Entry {
                             name: "OSD_PARAM_INVALID_PARAMETER".parse().unwrap(),
                             description: None,
                             dev_status: None,
                             value: 3,
+                            params: vec![],
                         },
                     ],
                 },
@@ -1201,6 +1905,8 @@ mod tests {
             "SOME_ENUM".parse().unwrap(),
             NormalisedEnum {
                 size: RustSizeType::U8,
+                bitmask: false,
+                entries: vec![],
             },
         );
 
@@ -1256,6 +1962,8 @@ mod tests {
             "SOME_ENUM".parse().unwrap(),
             NormalisedEnum {
                 size: RustSizeType::U32,
+                bitmask: false,
+                entries: vec![],
             },
         );
 
@@ -1301,6 +2009,8 @@ mod tests {
             "SOME_ENUM".parse().unwrap(),
             NormalisedEnum {
                 size: RustSizeType::U16,
+                bitmask: false,
+                entries: vec![],
             },
         );
 
@@ -1350,9 +2060,9 @@ mod tests {
                     min_value: Some(-1.0),
                     max_value: Some(12.0),
                     multiplier: Some("16".into()),
-                    default: Some("default".into()),
+                    default: Some("-1".into()),
                     instance: Some(true),
-                    invalid: Some("true".into()),
+                    invalid: Some("INT8_MAX".into()),
                     description: "Description".into(),
                 },
             )
@@ -1371,14 +2081,180 @@ mod tests {
                 min_value: Some(-1.0),
                 max_value: Some(12.0),
                 multiplier: Some("16".into()),
-                default: Some("default".into()),
+                default: Some(DefaultValue::Int(-1)),
                 instance: Some(true),
-                invalid: Some("true".into()),
+                invalid: Some("INT8_MAX".into()),
                 description: Some("Description".into()),
             }
         )
     }
 
+    #[test]
+    fn test_normalise_field_default() {
+        let mut normaliser = Normaliser::default();
+        normaliser.enums.insert(
+            "SOME_ENUM".parse().unwrap(),
+            NormalisedEnum {
+                size: RustSizeType::U8,
+                bitmask: false,
+                entries: vec![
+                    ("SOME_ENUM_FIRST".parse().unwrap(), 0),
+                    ("SOME_ENUM_SECOND".parse().unwrap(), 2),
+                ],
+            },
+        );
+        normaliser.enums.insert(
+            "SOME_FLAGS".parse().unwrap(),
+            NormalisedEnum {
+                size: RustSizeType::U8,
+                bitmask: true,
+                entries: vec![
+                    ("SOME_FLAGS_FIRST".parse().unwrap(), 1),
+                    ("SOME_FLAGS_SECOND".parse().unwrap(), 2),
+                    ("SOME_FLAGS_FOURTH".parse().unwrap(), 8),
+                ],
+            },
+        );
+        let message = Ident::from_str("TEST_MSG").unwrap();
+
+        let field = |r#type: &str, r#enum: Option<&str>, default: Option<&str>| {
+            let mut field = xml::Field::new_min("TEST_FIELD", r#type);
+            field.r#enum = r#enum.map(Into::into);
+            field.default = default.map(Into::into);
+            normaliser.normalise_field(&message, field)
+        };
+
+        let ok_cases = [
+            ("uint16_t", None, None, None),
+            (
+                "uint16_t",
+                None,
+                Some("65535"),
+                Some(DefaultValue::Int(65535)),
+            ),
+            (
+                "int8_t[4]",
+                None,
+                Some("-128"),
+                Some(DefaultValue::Int(-128)),
+            ),
+            ("uint32_t", None, Some("0x10"), Some(DefaultValue::Int(16))),
+            ("float", None, Some("1.5"), Some(DefaultValue::Float(1.5))),
+            (
+                "uint8_t",
+                Some("SOME_ENUM"),
+                Some("SOME_ENUM_SECOND"),
+                Some(DefaultValue::Entry("SOME_ENUM_SECOND".parse().unwrap())),
+            ),
+            (
+                "uint8_t",
+                Some("SOME_ENUM"),
+                Some("2"),
+                Some(DefaultValue::Entry("SOME_ENUM_SECOND".parse().unwrap())),
+            ),
+            (
+                "uint8_t",
+                Some("SOME_FLAGS"),
+                Some("2"),
+                Some(DefaultValue::Entry("SOME_FLAGS_SECOND".parse().unwrap())),
+            ),
+            (
+                "uint8_t",
+                Some("SOME_FLAGS"),
+                Some("0"),
+                Some(DefaultValue::Int(0)),
+            ),
+            (
+                "uint8_t",
+                Some("SOME_FLAGS"),
+                Some("0x9"),
+                Some(DefaultValue::Int(9)),
+            ),
+            (
+                "uint16_t",
+                Some("SOME_FLAGS"),
+                Some("11"),
+                Some(DefaultValue::Int(11)),
+            ),
+        ];
+
+        for (r#type, r#enum, default, expected) in ok_cases {
+            let field = field(r#type, r#enum, default)
+                .unwrap_or_else(|err| panic!("case {type:?} {default:?}: {err}"));
+            assert_eq!(field.default, expected, "case {type:?} {default:?}");
+        }
+
+        let err_cases = [
+            ("uint8_t", None, "256"),
+            ("int8_t", None, "-129"),
+            ("uint16_t", None, "turbofish"),
+            ("float", None, "one"),
+            ("uint8_t", Some("SOME_ENUM"), "SOME_ENUM_THIRD"),
+            ("uint8_t", Some("SOME_ENUM"), "1"),
+            ("uint8_t", Some("SOME_FLAGS"), "4"),
+            ("uint8_t", Some("SOME_FLAGS"), "-1"),
+            ("uint8_t", Some("SOME_FLAGS"), "SOME_FLAGS_THIRD"),
+        ];
+
+        for (r#type, r#enum, default) in err_cases {
+            let err = field(r#type, r#enum, Some(default)).unwrap_err();
+            assert_eq!(
+                err,
+                Error::InvalidDefaultValue {
+                    message: message.clone(),
+                    field: "TEST_FIELD".parse().unwrap(),
+                    value: default.into(),
+                },
+                "case {type:?} {default:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalise_field_invalid_value() {
+        let normaliser = Normaliser::default();
+        let message = Ident::from_str("TEST_MSG").unwrap();
+
+        let field = |r#type: &str, invalid: &str| {
+            let mut field = xml::Field::new_min("TEST_FIELD", r#type);
+            field.invalid = Some(invalid.into());
+            normaliser.normalise_field(&message, field)
+        };
+
+        let ok_cases = [
+            ("uint8_t", "UINT8_MAX"),
+            ("int16_t", "-1"),
+            ("uint32_t", "0xFFFF"),
+            ("float", "NaN"),
+            ("uint16_t[4]", "[UINT16_MAX]"),
+            ("uint16_t[4]", "[0,]"),
+            ("char[10]", "whatever"),
+        ];
+        for (r#type, invalid) in ok_cases {
+            field(r#type, invalid).unwrap_or_else(|err| panic!("case {type:?} {invalid:?}: {err}"));
+        }
+
+        let err_cases = [
+            ("uint8_t", "UINT16_MAX"),
+            ("uint8_t", "-1"),
+            ("int16_t", "turbofish"),
+            ("float", "[NaN]"),
+            ("uint16_t[4]", "UINT16_MAX"),
+        ];
+        for (r#type, invalid) in err_cases {
+            let err = field(r#type, invalid).unwrap_err();
+            assert_eq!(
+                err,
+                Error::BadInvalidValue {
+                    message: message.clone(),
+                    field: "TEST_FIELD".parse().unwrap(),
+                    value: invalid.into(),
+                },
+                "case {type:?} {invalid:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_normalise_field_bad_name_type() {
         let normaliser = Normaliser::default();
@@ -1407,11 +2283,43 @@ mod tests {
         assert_eq!(
             err,
             Error::InvalidFieldType {
+                message: message.clone(),
+                field: "TEST_FIELD".parse().unwrap(),
+                r#type: "turbo fish".into(),
+                suggestion: None,
+            }
+        );
+
+        let err = normaliser
+            .normalise_field(&message, xml::Field::new_min("TEST_FIELD", "uint8t[4]"))
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "message TEST_MSG field TEST_FIELD: unknown type 'uint8t[4]', did you mean 'uint8_t[4]'?"
+        );
+    }
+
+    #[test]
+    fn test_normalise_field_zero_length_array() {
+        let normaliser = Normaliser::default();
+        let message = Ident::from_str("TEST_MSG").unwrap();
+
+        let err = normaliser
+            .normalise_field(&message, xml::Field::new_min("TEST_FIELD", "float[0]"))
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::ZeroLengthArray {
                 message,
                 field: "TEST_FIELD".parse().unwrap(),
-                r#type: "turbo fish".into()
             }
         );
+        assert_eq!(
+            err.to_string(),
+            "[TEST_MSG] field TEST_FIELD is an array of zero length"
+        );
     }
 
     #[test]
@@ -1595,6 +2503,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalise_extension_field_redefinition() {
+        let normaliser = Normaliser::default();
+        let fields = vec![
+            xml::Field::new_min("target_system", "uint8_t"),
+            xml::Field::new_min("target_component", "uint8_t"),
+        ];
+        let extension_fields = vec![xml::Field::new_min("target_system", "uint8_t")];
+
+        let message = Ident::from_str("TEST_MSG").unwrap();
+
+        let err = normaliser
+            .normalise_fields(&message, fields, extension_fields)
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::ItemRedefinition {
+                item: "field",
+                super_item: Some(message),
+                name: "target_system".parse().unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_normalise_fields_name_collision() {
+        let normaliser = Normaliser::default();
+        let fields = vec![
+            xml::Field::new_min("target_system", "uint8_t"),
+            xml::Field::new_min("TARGET_SYSTEM", "uint8_t"),
+        ];
+
+        let message = Ident::from_str("TEST_MSG").unwrap();
+
+        let err = normaliser
+            .normalise_fields(&message, fields, vec![])
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::NameCollision {
+                item: "field",
+                super_item: Some(message.clone()),
+                name_1: "target_system".parse().unwrap(),
+                name_2: "TARGET_SYSTEM".parse().unwrap(),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "[TEST_MSG] fields target_system and TARGET_SYSTEM have the same name in generated code"
+        );
+    }
+
     #[test]
     fn test_normalise_fields_message_too_big() {
         let normaliser = Normaliser::default();
@@ -1606,9 +2568,10 @@ mod tests {
 
         let message = Ident::from_str("TEST_MSG").unwrap();
 
-        let err = normaliser
+        let (fields, extension_fields) = normaliser
             .normalise_fields(&message, fields, vec![])
-            .unwrap_err();
+            .unwrap();
+        let err = check_payload_size(&message, &fields, &extension_fields).unwrap_err();
 
         assert_eq!(
             err,
@@ -1620,6 +2583,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalise_message_payload_too_large() {
+        let mut normaliser = Normaliser::default();
+        let message = xml::Message {
+            name: "SOME_MESSAGE".into(),
+            id: 1234,
+            dev_status: None,
+            description: None,
+            fields: vec![xml::Field::new_min("TEST_FIELD_1", "int16_t[300]")],
+            extension_fields: vec![],
+        };
+
+        let err = normaliser.normalise_message(message).unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::MessageIsTooBig {
+                message: "SOME_MESSAGE".parse().unwrap(),
+                size: 600,
+                max_size: 255
+            }
+        );
+    }
+
     #[test]
     fn test_normalise_message() {
         let mut normaliser = Normaliser::default();
@@ -1750,23 +2737,30 @@ mod tests {
             extension_fields: vec![xml::Field::new_min("EXT_FIELD_1", "uint32_t")],
         };
 
-        normaliser.normalise_message(message.clone()).unwrap();
+        let allocated = normaliser
+            .allocate_message_id(&message, Path::new("first.xml"))
+            .unwrap();
+        assert!(allocated);
 
         message.name = "ANOTHER_MESSAGE".parse().unwrap();
 
-        let err = normaliser.normalise_message(message).unwrap_err();
+        let err = normaliser
+            .allocate_message_id(&message, Path::new("second.xml"))
+            .unwrap_err();
         assert_eq!(
             err,
             Error::RepeatedMessageId {
                 msg_1: "SOME_MESSAGE".parse().unwrap(),
                 msg_2: "ANOTHER_MESSAGE".parse().unwrap(),
-                id: 1234
+                id: 1234,
+                path_1: "first.xml".into(),
+                path_2: "second.xml".into(),
             },
         );
     }
 
     #[test]
-    fn test_normalise_empty_message() {
+    fn test_normalise_message_redeclaration() {
         let mut normaliser = Normaliser::default();
 
         let message = xml::Message {
@@ -1774,21 +2768,130 @@ mod tests {
             id: 1234,
             dev_status: None,
             description: Some("Description.".into()),
-            fields: vec![],
+            fields: vec![
+                xml::Field::new_min("TEST_FIELD_1", "uint8_t"),
+                xml::Field::new_min("TEST_FIELD_2", "uint16_t"),
+            ],
+            extension_fields: vec![xml::Field::new_min("EXT_FIELD_1", "uint32_t")],
+        };
+
+        let mut redeclared = message.clone();
+        redeclared.description = Some("Another description.".into());
+
+        let messages = normaliser.normalise_messages(
+            vec![message, redeclared],
+            vec!["first.xml".into(), "second.xml".into()],
+        );
+
+        assert!(normaliser.errors.is_empty());
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].description.as_deref(), Some("Description."),);
+    }
+
+    #[test]
+    fn test_normalise_message_conflicting_definition() {
+        let mut normaliser = Normaliser::default();
+
+        let message = xml::Message {
+            name: "SOME_MESSAGE".into(),
+            id: 151,
+            dev_status: None,
+            description: None,
+            fields: vec![
+                xml::Field::new_min("TEST_FIELD_1", "uint8_t"),
+                xml::Field::new_min("TEST_FIELD_2", "uint16_t"),
+            ],
             extension_fields: vec![],
         };
 
-        let err = normaliser.normalise_message(message).unwrap_err();
+        let mut conflicting = message.clone();
+        conflicting.fields[1].r#type = "int32_t".into();
+
+        let mut another = message.clone();
+        another.name = "ANOTHER_MESSAGE".into();
+
+        let messages = normaliser.normalise_messages(
+            vec![message, conflicting, another],
+            vec!["first.xml".into(), "second.xml".into(), "third.xml".into()],
+        );
+
+        assert_eq!(messages.len(), 1);
         assert_eq!(
-            err,
-            Error::NoSubItems {
-                item: "message",
-                sub_items: "fields",
-                name: "SOME_MESSAGE".parse().unwrap()
-            },
+            normaliser.errors,
+            [
+                Error::ConflictingMessageDefinition {
+                    message: "SOME_MESSAGE".parse().unwrap(),
+                    id: 151,
+                    path_1: "first.xml".into(),
+                    path_2: "second.xml".into(),
+                },
+                Error::RepeatedMessageId {
+                    msg_1: "SOME_MESSAGE".parse().unwrap(),
+                    msg_2: "ANOTHER_MESSAGE".parse().unwrap(),
+                    id: 151,
+                    path_1: "first.xml".into(),
+                    path_2: "third.xml".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalise_message_conflicting_extensions() {
+        let mut normaliser = Normaliser::default();
+
+        let message = xml::Message {
+            name: "SOME_MESSAGE".into(),
+            id: 151,
+            dev_status: None,
+            description: None,
+            fields: vec![
+                xml::Field::new_min("TEST_FIELD_1", "uint8_t"),
+                xml::Field::new_min("TEST_FIELD_2", "uint16_t"),
+            ],
+            extension_fields: vec![xml::Field::new_min("EXT_FIELD_1", "uint32_t")],
+        };
+
+        let mut conflicting = message.clone();
+        let field = conflicting.fields.pop().unwrap();
+        conflicting.extension_fields.insert(0, field);
+
+        let messages = normaliser.normalise_messages(
+            vec![message, conflicting],
+            vec!["first.xml".into(), "second.xml".into()],
+        );
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(
+            normaliser.errors,
+            [Error::ConflictingExtensions {
+                message: "SOME_MESSAGE".parse().unwrap(),
+                id: 151,
+                path_1: "first.xml".into(),
+                path_2: "second.xml".into(),
+            }]
         );
     }
 
+    #[test]
+    fn test_normalise_empty_message() {
+        let mut normaliser = Normaliser::default();
+
+        let message = xml::Message {
+            name: "SOME_MESSAGE".into(),
+            id: 1234,
+            dev_status: None,
+            description: Some("Description.".into()),
+            fields: vec![],
+            extension_fields: vec![],
+        };
+
+        let message = normaliser.normalise_message(message).unwrap();
+        assert!(message.fields.is_empty());
+        assert!(message.extension_fields.is_empty());
+        assert_eq!(message.wire_size(), 0);
+    }
+
     #[test]
     fn test_normalise_module() {
         let malink = xml::Mavlink::from_str(
@@ -1920,6 +3023,7 @@ mod tests {
                 version: Some(1),
                 dialect: None,
                 enums: malink.enums.unwrap().0,
+                message_paths: vec!["some_path.xml".into(); 3],
                 messages: malink.messages.unwrap().0,
             })
             .unwrap();
@@ -1930,6 +3034,7 @@ mod tests {
                 description: None,
                 dev_status: None,
                 value,
+                params: vec![],
             }
         }
 
@@ -2446,6 +3551,7 @@ mod tests {
                     extension_fields: vec![],
                 },
             ],
+            reexports: vec![],
         };
 
         assert_eq!(module, expected);
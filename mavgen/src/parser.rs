@@ -1,6 +1,6 @@
 use std::{
-    collections::HashMap,
-    path::{Path, PathBuf},
+    collections::{HashMap, HashSet},
+    path::{Component, Path, PathBuf},
 };
 
 use hard_xml::XmlRead;
@@ -9,15 +9,57 @@ use crate::xml;
 
 const MAX_INCLUDE_RECURSION: usize = 10;
 
-pub trait World {
-    fn read_file(&self, path: &Path) -> std::io::Result<String>;
-    fn normalise_path(&self, path: &Path) -> std::io::Result<PathBuf>;
+/// Provides the contents of definition files, e.g. to generate code from
+/// definitions in memory instead of the disk.
+pub trait FileSource {
+    fn read(&self, path: &Path) -> std::io::Result<String>;
+
+    /// Returns the path, which identifies the file, so that a file included
+    /// several times is parsed once.
+    ///
+    /// By default `.` and `..` are resolved without accessing the source.
+    fn normalise_path(&self, path: &Path) -> std::io::Result<PathBuf> {
+        let mut normalised = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir if normalised.file_name().is_some() => {
+                    normalised.pop();
+                }
+                component => normalised.push(component),
+            }
+        }
+        Ok(normalised)
+    }
 }
 
-pub struct FsWorld;
+impl<S: FileSource + ?Sized> FileSource for &S {
+    fn read(&self, path: &Path) -> std::io::Result<String> {
+        (**self).read(path)
+    }
 
-impl World for FsWorld {
-    fn read_file(&self, path: &Path) -> std::io::Result<String> {
+    fn normalise_path(&self, path: &Path) -> std::io::Result<PathBuf> {
+        (**self).normalise_path(path)
+    }
+}
+
+/// Definitions in memory by their path.
+impl FileSource for HashMap<PathBuf, String> {
+    fn read(&self, path: &Path) -> std::io::Result<String> {
+        self.get(path).cloned().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} is not in the source", path.display()),
+            )
+        })
+    }
+}
+
+/// Reads definitions from the disk.
+pub struct FsSource;
+
+impl FileSource for FsSource {
+    fn read(&self, path: &Path) -> std::io::Result<String> {
         std::fs::read_to_string(path)
     }
 
@@ -32,6 +74,102 @@ pub struct MavlinkFile {
     pub normalised_includes: Vec<PathBuf>,
 }
 
+/// Position in a definition file, both line and column start from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Location {
+    fn from_offset(content: &str, offset: usize) -> Self {
+        let before = &content[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Self {
+            line: line as u32,
+            column: column as u32,
+        }
+    }
+
+    /// Tries to find where the error occurred.
+    ///
+    /// `hard_xml` reports positions only for syntax errors, so for the other
+    /// errors the content is scanned again looking for the failing element.
+    fn of_xml_error(err: &hard_xml::XmlError, content: &str) -> Option<Self> {
+        use hard_xml::xmlparser::{ElementEnd, Token, Tokenizer};
+        use hard_xml::XmlError;
+
+        match err {
+            XmlError::Parser(err) => {
+                let pos = err.pos();
+                Some(Self {
+                    line: pos.row,
+                    column: pos.col,
+                })
+            }
+            XmlError::UnexpectedEof => Some(Self::from_offset(content, content.len())),
+            XmlError::TagMismatch { found, .. } => {
+                let mut stack = Vec::new();
+                let mut last_start = None;
+                for token in Tokenizer::from(content) {
+                    match token.ok()? {
+                        Token::ElementStart { local, .. } => {
+                            last_start = Some(local);
+                        }
+                        Token::ElementEnd {
+                            end: ElementEnd::Open,
+                            ..
+                        } => stack.extend(last_start.take()),
+                        Token::ElementEnd {
+                            end: ElementEnd::Close(_, local),
+                            span,
+                        } => {
+                            let open = stack.pop();
+                            if open.map(|tag| tag.as_str()) != Some(local.as_str())
+                                && local.as_str() == found
+                            {
+                                return Some(Self::from_offset(content, span.start()));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                None
+            }
+            XmlError::MissingField { name, field } => {
+                let tag = name.to_lowercase();
+                let mut candidate = None;
+                for token in Tokenizer::from(content) {
+                    match token.ok()? {
+                        Token::ElementStart { local, span, .. } => {
+                            candidate = (local.as_str() == tag).then(|| span.start());
+                        }
+                        Token::Attribute { local, .. } if local.as_str() == field => {
+                            candidate = None;
+                        }
+                        Token::ElementEnd { .. } => {
+                            if let Some(offset) = candidate {
+                                return Some(Self::from_offset(content, offset));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     Io {
@@ -42,81 +180,225 @@ pub enum Error {
         err: hard_xml::XmlError,
         path: PathBuf,
         content: String,
+        location: Option<Location>,
     },
     RecursionLimitExceeded {
         stack: Vec<PathBuf>,
     },
-    CycleDetected,
+    /// A file includes itself, directly or through other files.
+    CycleDetected {
+        /// Files on the include path, the first one is repeated at the end.
+        cycle: Vec<PathBuf>,
+    },
+    /// A definition without a file includes another one.
+    UnresolvableInclude {
+        path: PathBuf,
+        include: String,
+    },
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Io { path, .. } => write!(f, "IO error while opening {:?}", path),
+            Error::Xml {
+                err,
+                path,
+                location: Some(location),
+                ..
+            } => write!(f, "{}:{}: XML error: {}", path.display(), location, err),
             Error::Xml { path, .. } => write!(f, "XML error while parsing {:?}", path),
             Error::RecursionLimitExceeded { .. } => write!(f, "recursion limit exceeded"),
-            Error::CycleDetected => write!(f, "inclusion cycle detected"),
+            Error::CycleDetected { cycle } => {
+                write!(f, "inclusion cycle detected: ")?;
+                for (i, path) in cycle.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, " -> ")?;
+                    }
+                    let name = path.file_name().unwrap_or(path.as_os_str());
+                    write!(f, "{}", Path::new(name).display())?;
+                }
+                Ok(())
+            }
+            Error::UnresolvableInclude { path, include } => write!(
+                f,
+                "{:?} includes {:?}, but includes are supported only for files",
+                path, include
+            ),
         }
     }
 }
 
 impl std::error::Error for Error {}
 
-pub struct Parser<W> {
+/// An included file, which doesn't exist and was skipped, see
+/// [`Parser::allow_missing_includes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingInclude {
+    /// File with the include.
+    pub path: PathBuf,
+    pub include: PathBuf,
+}
+
+impl std::fmt::Display for MissingInclude {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} includes {}, which doesn't exist",
+            self.path.display(),
+            self.include.display()
+        )
+    }
+}
+
+fn parse_mavlink(path: &Path, raw: String) -> Result<xml::Mavlink, Error> {
+    xml::Mavlink::from_str(&raw).map_err(|err| Error::Xml {
+        location: Location::of_xml_error(&err, &raw),
+        err,
+        path: path.to_owned(),
+        content: raw,
+    })
+}
+
+/// Walks the includes depth-first, `stack` holds the files on the current
+/// path and `done` the files whose includes are already checked, so that
+/// files included from several places are visited once.
+fn find_cycle<'a>(
+    files: &'a HashMap<PathBuf, MavlinkFile>,
+    path: &'a Path,
+    stack: &mut Vec<&'a Path>,
+    done: &mut HashSet<&'a Path>,
+) -> Option<Vec<PathBuf>> {
+    if done.contains(path) {
+        return None;
+    }
+
+    if let Some(start) = stack.iter().position(|visited| *visited == path) {
+        let mut cycle = stack[start..]
+            .iter()
+            .map(|visited| visited.to_path_buf())
+            .collect::<Vec<_>>();
+        cycle.push(path.to_owned());
+        return Some(cycle);
+    }
+
+    // Files which failed to parse are reported separately
+    let file = files.get(path)?;
+
+    stack.push(path);
+    for include in &file.normalised_includes {
+        if let Some(cycle) = find_cycle(files, include, stack, done) {
+            return Some(cycle);
+        }
+    }
+    stack.pop();
+
+    done.insert(path);
+    None
+}
+
+/// Reports the number of items defined in a parsed file.
+#[cfg(feature = "tracing")]
+fn trace_parsed(mavlink: &xml::Mavlink) {
+    tracing::debug!(
+        enums = mavlink.enums.as_ref().map_or(0, |enums| enums.0.len()),
+        messages = mavlink
+            .messages
+            .as_ref()
+            .map_or(0, |messages| messages.0.len()),
+        includes = mavlink.include.len(),
+        "parsed definition"
+    );
+}
+
+pub struct Parser<S> {
     parsed: HashMap<PathBuf, MavlinkFile>,
-    world: W,
+    source: S,
     errors: Vec<Error>,
     max_include_recursion: usize,
     inclusion_stack: Vec<PathBuf>,
+    allow_missing_includes: bool,
+    missing_includes: Vec<MissingInclude>,
 }
 
-impl<W: World> Parser<W> {
-    pub fn new(world: W) -> Self {
+impl<S: FileSource> Parser<S> {
+    pub fn new(source: S) -> Self {
         Self {
             parsed: Default::default(),
-            world,
+            source,
             errors: Default::default(),
             max_include_recursion: MAX_INCLUDE_RECURSION,
             inclusion_stack: Vec::with_capacity(MAX_INCLUDE_RECURSION),
+            allow_missing_includes: false,
+            missing_includes: Default::default(),
         }
     }
 
+    /// Skips included files, which don't exist, instead of reporting them as
+    /// errors, so that the definitions are generated without their items.
+    ///
+    /// The files passed to [`Self::parse`] still have to exist.
+    pub fn allow_missing_includes(&mut self, allow: bool) {
+        self.allow_missing_includes = allow;
+    }
+
+    /// Included files skipped so far, see [`Self::allow_missing_includes`].
+    pub fn missing_includes(&self) -> &[MissingInclude] {
+        &self.missing_includes
+    }
+
+    /// Whether the include should be skipped after failing to access it.
+    fn is_missing_include(&self, err: &std::io::Error) -> bool {
+        self.allow_missing_includes && err.kind() == std::io::ErrorKind::NotFound
+    }
+
     fn try_parse_recursively(&mut self, path: PathBuf) -> Result<(), Error> {
         if self.parsed.contains_key(&path) {
             return Ok(());
         }
 
-        let raw = match self.world.read_file(&path) {
-            Ok(ok) => ok,
-            Err(err) => {
-                return Err(Error::Io { err, path });
-            }
-        };
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("parse", path = %path.display()).entered();
 
-        let mavlink = match xml::Mavlink::from_str(&raw) {
+        let raw = match self.source.read(&path) {
             Ok(ok) => ok,
+            // The stack has the file itself, so the includer goes before it
+            Err(err) if self.inclusion_stack.len() > 1 && self.is_missing_include(&err) => {
+                let includer = &self.inclusion_stack[self.inclusion_stack.len() - 2];
+                self.missing_includes.push(MissingInclude {
+                    path: includer.clone(),
+                    include: path,
+                });
+                return Ok(());
+            }
             Err(err) => {
-                return Err(Error::Xml {
-                    err,
-                    path,
-                    content: raw,
-                })
+                return Err(Error::Io { err, path });
             }
         };
 
-        let normalised_includes = mavlink
-            .include
-            .iter()
-            .map(|include| {
-                let include_path = path.with_file_name(include);
-                self.world
-                    .normalise_path(&include_path)
-                    .map_err(|err| Error::Io {
+        let mavlink = parse_mavlink(&path, raw)?;
+        #[cfg(feature = "tracing")]
+        trace_parsed(&mavlink);
+
+        let mut normalised_includes = Vec::with_capacity(mavlink.include.len());
+        for include in &mavlink.include {
+            let include_path = path.with_file_name(include);
+            match self.source.normalise_path(&include_path) {
+                Ok(normalised) => normalised_includes.push(normalised),
+                Err(err) if self.is_missing_include(&err) => {
+                    self.missing_includes.push(MissingInclude {
+                        path: path.clone(),
+                        include: include_path,
+                    });
+                }
+                Err(err) => {
+                    return Err(Error::Io {
                         err,
                         path: include_path,
                     })
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+                }
+            }
+        }
 
         self.parsed.insert(
             path.clone(),
@@ -126,10 +408,24 @@ impl<W: World> Parser<W> {
             },
         );
 
+        let missing_before = self.missing_includes.len();
         for include in normalised_includes {
             self.parse_normalised(include);
         }
 
+        // Flattening expects every include to be parsed
+        if self.missing_includes.len() > missing_before {
+            let missing = self.missing_includes[missing_before..]
+                .iter()
+                .filter(|missing| missing.path == path)
+                .map(|missing| missing.include.clone())
+                .collect::<HashSet<_>>();
+            if let Some(file) = self.parsed.get_mut(&path) {
+                file.normalised_includes
+                    .retain(|include| !missing.contains(include));
+            }
+        }
+
         Ok(())
     }
 
@@ -150,8 +446,43 @@ impl<W: World> Parser<W> {
         self.inclusion_stack.pop();
     }
 
+    /// Parses a definition which is not backed by a file, `path` is used
+    /// only to identify it.
+    ///
+    /// Includes can't be resolved for such definitions and are reported as
+    /// errors.
+    pub fn parse_str(&mut self, path: &Path, content: &str) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("parse", path = %path.display()).entered();
+
+        let mavlink = match parse_mavlink(path, content.to_owned()) {
+            Ok(ok) => ok,
+            Err(err) => {
+                self.errors.push(err);
+                return;
+            }
+        };
+        #[cfg(feature = "tracing")]
+        trace_parsed(&mavlink);
+
+        for include in &mavlink.include {
+            self.errors.push(Error::UnresolvableInclude {
+                path: path.to_owned(),
+                include: include.clone(),
+            });
+        }
+
+        self.parsed.insert(
+            path.to_owned(),
+            MavlinkFile {
+                mavlink,
+                normalised_includes: vec![],
+            },
+        );
+    }
+
     pub fn parse(&mut self, file: &Path) {
-        match self.world.normalise_path(file) {
+        match self.source.normalise_path(file) {
             Ok(ok) => self.parse_normalised(ok),
             Err(err) => self.errors.push(Error::Io {
                 err,
@@ -161,25 +492,22 @@ impl<W: World> Parser<W> {
     }
 
     fn detect_cycles(&mut self) {
-        let mut topo = topo_sort::TopoSort::with_capacity(self.parsed.len());
-        for (path, file) in &self.parsed {
-            if file.normalised_includes.contains(path) {
-                self.errors.push(Error::CycleDetected);
-                return;
-            }
-
-            topo.insert(path, file.normalised_includes.iter());
-        }
+        let mut paths = self.parsed.keys().collect::<Vec<_>>();
+        paths.sort();
 
-        for node in &topo {
-            if node.is_err() {
-                self.errors.push(Error::CycleDetected);
+        let mut done = HashSet::new();
+        for path in paths {
+            if let Some(cycle) = find_cycle(&self.parsed, path, &mut vec![], &mut done) {
+                self.errors.push(Error::CycleDetected { cycle });
                 return;
             }
         }
     }
 
     pub fn finish(mut self) -> Result<HashMap<PathBuf, MavlinkFile>, Vec<Error>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("finish", files = self.parsed.len()).entered();
+
         self.detect_cycles();
 
         if self.errors.is_empty() {
@@ -197,8 +525,8 @@ pub(crate) mod tests {
 
     pub struct MockWorld(pub HashMap<PathBuf, String>);
 
-    impl World for MockWorld {
-        fn read_file(&self, path: &Path) -> std::io::Result<String> {
+    impl FileSource for MockWorld {
+        fn read(&self, path: &Path) -> std::io::Result<String> {
             self.0
                 .get(path)
                 .cloned()
@@ -255,6 +583,91 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn test_in_memory_source() {
+        let source = HashMap::from([
+            (
+                PathBuf::from("defs/test.xml"),
+                String::from("<mavlink><include>../common/base.xml</include></mavlink>"),
+            ),
+            (
+                PathBuf::from("common/base.xml"),
+                String::from("<mavlink><messages/></mavlink>"),
+            ),
+        ]);
+        assert_eq!(
+            source
+                .normalise_path(Path::new("./defs/../common/./base.xml"))
+                .unwrap(),
+            Path::new("common/base.xml")
+        );
+
+        let mut parser = Parser::new(&source);
+        parser.parse(Path::new("defs/test.xml"));
+        let parsed = parser.finish().unwrap();
+        assert_eq!(
+            parsed[Path::new("defs/test.xml")].normalised_includes,
+            [PathBuf::from("common/base.xml")]
+        );
+        assert!(parsed.contains_key(Path::new("common/base.xml")));
+
+        let mut parser = Parser::new(&source);
+        parser.parse(Path::new("missing.xml"));
+        assert!(matches!(
+            &parser.finish().unwrap_err()[..],
+            [Error::Io { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_missing_include() {
+        let source = HashMap::from([
+            (
+                PathBuf::from("defs/test.xml"),
+                String::from(
+                    "<mavlink><include>base.xml</include><include>huge.xml</include></mavlink>",
+                ),
+            ),
+            (
+                PathBuf::from("defs/base.xml"),
+                String::from("<mavlink><messages/></mavlink>"),
+            ),
+        ]);
+
+        let mut parser = Parser::new(&source);
+        parser.parse(Path::new("defs/test.xml"));
+        assert!(matches!(
+            &parser.finish().unwrap_err()[..],
+            [Error::Io { .. }]
+        ));
+
+        let mut parser = Parser::new(&source);
+        parser.allow_missing_includes(true);
+        parser.parse(Path::new("defs/test.xml"));
+        assert_eq!(
+            parser.missing_includes(),
+            [MissingInclude {
+                path: PathBuf::from("defs/test.xml"),
+                include: PathBuf::from("defs/huge.xml"),
+            }]
+        );
+        let parsed = parser.finish().unwrap();
+        assert_eq!(
+            parsed[Path::new("defs/test.xml")].normalised_includes,
+            [PathBuf::from("defs/base.xml")]
+        );
+
+        // The inputs themselves are still required
+        let mut parser = Parser::new(&source);
+        parser.allow_missing_includes(true);
+        parser.parse(Path::new("defs/huge.xml"));
+        assert!(parser.missing_includes().is_empty());
+        assert!(matches!(
+            &parser.finish().unwrap_err()[..],
+            [Error::Io { .. }]
+        ));
+    }
+
     #[test]
     fn test_self_import() {
         let world = MockWorld(HashMap::from_iter([(
@@ -288,7 +701,16 @@ pub(crate) mod tests {
         );
 
         let err = parser.finish().unwrap_err();
-        assert!(matches!(err[0], Error::CycleDetected));
+        let [Error::CycleDetected { cycle }] = err.as_slice() else {
+            panic!("errors: {err:?}");
+        };
+        assert_eq!(
+            cycle,
+            &[
+                PathBuf::from("/cwd/test.xml"),
+                PathBuf::from("/cwd/test.xml")
+            ]
+        );
     }
 
     #[test]
@@ -336,7 +758,21 @@ pub(crate) mod tests {
         );
 
         let err = parser.finish().unwrap_err();
-        assert!(matches!(err[0], Error::CycleDetected));
+        let [Error::CycleDetected { cycle }] = err.as_slice() else {
+            panic!("errors: {err:?}");
+        };
+        assert_eq!(
+            cycle,
+            &[
+                PathBuf::from("/cwd/test-1.xml"),
+                PathBuf::from("/cwd/test-2.xml"),
+                PathBuf::from("/cwd/test-1.xml"),
+            ]
+        );
+        assert_eq!(
+            err[0].to_string(),
+            "inclusion cycle detected: test-1.xml -> test-2.xml -> test-1.xml"
+        );
     }
 
     #[test]
@@ -401,7 +837,7 @@ pub(crate) mod tests {
         );
 
         let err = parser.finish().unwrap_err();
-        assert!(matches!(err[0], Error::CycleDetected));
+        assert!(matches!(err[0], Error::CycleDetected { .. }));
     }
 
     #[test]
@@ -641,4 +1077,58 @@ pub(crate) mod tests {
             parsed
         );
     }
+
+    #[test]
+    fn test_xml_error_location() {
+        let world = MockWorld(HashMap::from_iter([(
+            PathBuf::from("/cwd/test.xml"),
+            String::from(
+                r#"<?xml version="1.0"?>
+<mavlink>
+    <messages>
+        <message id="1" name="SOME_MESSAGE">
+            <field type="uint8_t" name="some_field">Field</field>
+        </mesage>
+    </messages>
+</mavlink>
+"#,
+            ),
+        )]));
+
+        let mut parser = Parser::new(world);
+        parser.parse(Path::new("test.xml"));
+
+        let errors = parser.finish().unwrap_err();
+        let Error::Xml { location, .. } = &errors[0] else {
+            panic!("err: {:?}", errors[0]);
+        };
+        assert_eq!(location, &Some(Location { line: 6, column: 9 }));
+        assert!(
+            errors[0].to_string().starts_with("/cwd/test.xml:6:9: "),
+            "err: {}",
+            errors[0]
+        );
+    }
+
+    #[test]
+    fn test_xml_missing_field_location() {
+        let content = r#"<?xml version="1.0"?>
+<mavlink>
+    <messages>
+        <message id="1" name="SOME_MESSAGE">
+            <field type="uint8_t" name="some_field">Field</field>
+        </message>
+        <message name="ANOTHER_MESSAGE">
+            <field type="uint8_t" name="some_field">Field</field>
+        </message>
+    </messages>
+</mavlink>
+"#;
+
+        let err = xml::Mavlink::from_str(content).unwrap_err();
+        assert_eq!(
+            Location::of_xml_error(&err, content),
+            Some(Location { line: 7, column: 9 })
+        );
+    }
 }